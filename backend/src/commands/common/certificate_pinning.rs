@@ -0,0 +1,168 @@
+use std::net::IpAddr;
+
+use crate::{
+    commands::CommandInput,
+    common::{self, ClientTls},
+    datastore,
+    models::{
+        error::AppError,
+        plugin::notification::{Level, Notification},
+        server::Param,
+    },
+    plugin_execution,
+};
+
+/// name of the feature param in which the fingerprint of a certificate that is not signed by a trusted CA is pinned
+pub const CERTIFICATE_FINGERPRINT: &str = "certificate_fingerprint";
+
+const MISMATCH_NOTIFICATION_ID: &str = "certificate_fingerprint_mismatch";
+
+/// name of the feature param in which the fingerprint of the SSH host key is pinned
+pub const HOST_KEY_FINGERPRINT: &str = "host_key_fingerprint";
+
+const HOST_KEY_MISMATCH_NOTIFICATION_ID: &str = "host_key_fingerprint_mismatch";
+
+/// Sets the pinning id and the pinned fingerprint of the feature the input belongs to.
+/// Inputs without a feature (ie. during the discovery) are not pinned
+pub fn with_pinning(input: &CommandInput, tls: ClientTls) -> ClientTls {
+    let (Some(ipaddress), Some(plugin_id)) = (input.get_ipaddress(), &input.plugin_id) else {
+        return tls;
+    };
+
+    ClientTls {
+        pinning_id: Some(format!("{}/{}", ipaddress, plugin_id)),
+        pinned_fingerprint: input
+            .find_param(CERTIFICATE_FINGERPRINT)
+            .ok()
+            .filter(|fingerprint| !fingerprint.trim().is_empty())
+            .map(|fingerprint| fingerprint.to_owned()),
+        ..tls
+    }
+}
+
+/// Checks if an unverified certificate was seen during the last request.
+/// On first use the fingerprint is stored in the params of the feature, if it doesn't match the pinned one a notification is raised
+pub async fn process_unverified_certificate(
+    input: &CommandInput,
+    tls: &ClientTls,
+) -> Result<(), AppError> {
+    let (Some(pinning_id), Some(ipaddress), Some(plugin_id)) =
+        (&tls.pinning_id, input.get_ipaddress(), &input.plugin_id)
+    else {
+        return Ok(());
+    };
+
+    let Some(fingerprint) = common::take_unverified_certificate_fingerprint(pinning_id) else {
+        return Ok(());
+    };
+
+    match &tls.pinned_fingerprint {
+        None => pin_fingerprint(&ipaddress, plugin_id, CERTIFICATE_FINGERPRINT, &fingerprint).await,
+        Some(pinned) if common::is_same_fingerprint(pinned, &fingerprint) => Ok(()),
+        Some(pinned) => {
+            raise_mismatch_notification(
+                &ipaddress,
+                plugin_id,
+                MISMATCH_NOTIFICATION_ID,
+                "Certificate changed",
+                &format!(
+                "The certificate for feature {} has the fingerprint {} instead of the pinned {}.",
+                plugin_id, fingerprint, pinned
+            ),
+            )
+            .await
+        }
+    }
+}
+
+/// the pinned fingerprint of the SSH host key of the feature the input belongs to
+pub fn get_pinned_host_key(input: &CommandInput) -> Option<String> {
+    input
+        .find_param(HOST_KEY_FINGERPRINT)
+        .ok()
+        .filter(|fingerprint| !fingerprint.trim().is_empty())
+        .map(|fingerprint| fingerprint.to_owned())
+}
+
+/// Pins the SSH host key on first use and raises a notification if it doesn't match the pinned one.
+/// Inputs without a feature (ie. during the discovery) are not pinned
+pub async fn process_host_key(
+    input: &CommandInput,
+    pinned_fingerprint: Option<&str>,
+    fingerprint: &str,
+) -> Result<(), AppError> {
+    let (Some(ipaddress), Some(plugin_id)) = (input.get_ipaddress(), &input.plugin_id) else {
+        return Ok(());
+    };
+
+    match pinned_fingerprint {
+        None => pin_fingerprint(&ipaddress, plugin_id, HOST_KEY_FINGERPRINT, fingerprint).await,
+        Some(pinned) if common::is_same_fingerprint(pinned, fingerprint) => Ok(()),
+        Some(pinned) => {
+            raise_mismatch_notification(
+                &ipaddress,
+                plugin_id,
+                HOST_KEY_MISMATCH_NOTIFICATION_ID,
+                "SSH host key changed",
+                &format!(
+                    "The SSH host key for feature {} has the fingerprint {} instead of the pinned {}. The credentials were not sent.",
+                    plugin_id, fingerprint, pinned
+                ),
+            )
+            .await
+        }
+    }
+}
+
+async fn pin_fingerprint(
+    ipaddress: &IpAddr,
+    plugin_id: &str,
+    param_name: &str,
+    fingerprint: &str,
+) -> Result<(), AppError> {
+    let server = datastore::get_server(ipaddress).await?;
+
+    let Some(mut feature) = server.find_feature(plugin_id) else {
+        return Ok(());
+    };
+
+    log::info!(
+        "Pinning {} {} for feature {} of server {}",
+        param_name,
+        fingerprint,
+        plugin_id,
+        ipaddress
+    );
+
+    feature.params.retain(|p| p.name != param_name);
+    feature.params.push(Param {
+        name: param_name.to_owned(),
+        value: fingerprint.to_owned(),
+    });
+
+    datastore::update_server(&server.replace_feature(feature)).await?;
+    Ok(())
+}
+
+async fn raise_mismatch_notification(
+    ipaddress: &IpAddr,
+    plugin_id: &str,
+    notification_id: &str,
+    name: &str,
+    message: &str,
+) -> Result<(), AppError> {
+    log::warn!("{} of server {}: {}", name, ipaddress, message);
+
+    let mut notification = Notification::new(
+        &format!("{}_{}", notification_id, plugin_id),
+        name,
+        &format!(
+            "{} Requests are rejected until the pinned fingerprint is reset in the feature configuration.",
+            message
+        ),
+        Level::Critical,
+    );
+    notification.plugin_id = Some(plugin_id.to_owned());
+
+    plugin_execution::raise_notification(&format!("{}", ipaddress), notification).await
+}
@@ -15,7 +15,9 @@ use crate::{
 
 use super::CommandArg;
 
+pub mod certificate_pinning;
 pub mod replace;
+pub mod session;
 
 pub async fn args_to_command_args(
     vec: &[ArgDef],
@@ -14,6 +14,7 @@ enum Placeholder {
     Param,
     Args,
     Credential,
+    Session,
     Base64,
 }
 
@@ -24,6 +25,8 @@ lazy_static! {
         Regex::new(Placeholder::Args.get_pattern()).expect("Pattern is invalid");
     static ref CREDENTIAL_REGEX: Regex =
         Regex::new(Placeholder::Credential.get_pattern()).expect("Pattern is invalid");
+    static ref SESSION_REGEX: Regex =
+        Regex::new(Placeholder::Session.get_pattern()).expect("Pattern is invalid");
     static ref BASE64_REGEX: Regex =
         Regex::new(Placeholder::Base64.get_pattern()).expect("Pattern is invalid");
 }
@@ -34,6 +37,7 @@ impl Placeholder {
             Placeholder::Param => r"(\$\{params\..*?\})",
             Placeholder::Args => r"(\$\{args\..*?\})",
             Placeholder::Credential => r"(\$\{credentials\..*?\})",
+            Placeholder::Session => r"(\$\{session\..*?\})",
             Placeholder::Base64 => r"(\$\{encode_base64\(.*?\)\})",
         }
     }
@@ -43,6 +47,7 @@ impl Placeholder {
             Placeholder::Param => PARAM_REGEX.find_iter(input.as_str()),
             Placeholder::Args => ARGS_REGEX.find_iter(input.as_str()),
             Placeholder::Credential => CREDENTIAL_REGEX.find_iter(input.as_str()),
+            Placeholder::Session => SESSION_REGEX.find_iter(input.as_str()),
             Placeholder::Base64 => BASE64_REGEX.find_iter(input.as_str()),
         };
 
@@ -54,6 +59,7 @@ impl Placeholder {
             Placeholder::Param => value.replace("${params.", "").replace('}', ""),
             Placeholder::Args => value.replace("${args.", "").replace('}', ""),
             Placeholder::Credential => value.replace("${credentials.", "").replace('}', ""),
+            Placeholder::Session => value.replace("${session.", "").replace('}', ""),
             Placeholder::Base64 => value.replace("${encode_base64(", "").replace(")}", ""),
         }
     }
@@ -83,6 +89,7 @@ pub fn replace(input_string: &str, input: &CommandInput) -> Result<(String, Stri
     result = replace_param(result, input)?;
     result = replace_args(result, input)?;
     let both: (String, String) = replace_credentials(result, input)?; // we now have two string - the unmasked and the masked which can be logged for example
+    let both: (String, String) = replace_session(both.0, both.1, input)?;
     result = both.0;
     masked = both.1;
     result = replace_base64_encoded(result); // base 64 encode should happen on both idependently
@@ -139,6 +146,25 @@ fn replace_credentials(
     Ok((result, masked))
 }
 
+fn replace_session(
+    input_string: String,
+    masked_string: String,
+    input: &CommandInput,
+) -> Result<(String, String), AppError> {
+    let mut result = input_string.clone();
+    let mut masked = masked_string;
+
+    for placeholder in Placeholder::Session.extract_placeholders(input_string) {
+        let name = Placeholder::Session.strip_of_marker(&placeholder);
+
+        let replacement = input.find_session_value(name.as_str())?;
+
+        result = result.replace(placeholder.as_str(), replacement);
+        masked = masked.replace(placeholder.as_str(), "******"); // session tokens are as secret as the credentials used for the login
+    }
+    Ok((result, masked))
+}
+
 fn replace_base64_encoded(input: String) -> String {
     let mut result = input.clone();
 
@@ -157,7 +183,7 @@ fn replace_base64_encoded(input: String) -> String {
     result
 }
 
-fn get_credential_value(name: &str, input: &CommandInput) -> Result<(String, bool), AppError> {
+pub fn get_credential_value(name: &str, input: &CommandInput) -> Result<(String, bool), AppError> {
     let credential = input.find_credential(name)?;
 
     let key = input
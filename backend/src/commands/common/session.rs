@@ -0,0 +1,194 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use jsonpath_rust::JsonPathQuery;
+use lazy_static::lazy_static;
+use tokio::sync::RwLock;
+
+use crate::{
+    commands::CommandInput,
+    common::{self, ClientTls},
+    models::{
+        error::AppError,
+        plugin::authentication::{AuthenticationDef, SessionValueDef},
+    },
+};
+
+use super::replace;
+
+const SET_COOKIE: &str = "set-cookie";
+
+lazy_static! {
+    static ref SESSIONS: RwLock<HashMap<SessionKey, Session>> = RwLock::new(HashMap::new());
+}
+
+type SessionKey = (Option<IpAddr>, String);
+
+#[derive(Debug, Clone)]
+struct Session {
+    values: HashMap<String, String>,
+    valid_until: Instant,
+}
+
+/// Returns the values of the session for the server and plugin of the given input.
+/// If there is no session yet or it is expired, the login defined in the authentication is done first.
+pub async fn get_session_values(
+    input: &CommandInput,
+    authentication: &AuthenticationDef,
+    tls: Option<&ClientTls>,
+) -> Result<HashMap<String, String>, AppError> {
+    let key = get_key(input);
+
+    if let Some(session) = SESSIONS.read().await.get(&key) {
+        if session.valid_until > Instant::now() {
+            return Ok(session.values.clone());
+        }
+    }
+
+    let values = login(input, authentication, tls).await?;
+
+    SESSIONS.write().await.insert(
+        key,
+        Session {
+            values: values.clone(),
+            valid_until: Instant::now() + Duration::from_secs(authentication.expiry_seconds),
+        },
+    );
+    Ok(values)
+}
+
+/// Removes the session for the server and plugin of the given input, so that the next request does a new login
+pub async fn invalidate_session(input: &CommandInput) {
+    SESSIONS.write().await.remove(&get_key(input));
+}
+
+fn get_key(input: &CommandInput) -> SessionKey {
+    (
+        input.get_ipaddress(),
+        input.plugin_id.clone().unwrap_or_default(),
+    )
+}
+
+async fn login(
+    input: &CommandInput,
+    authentication: &AuthenticationDef,
+    tls: Option<&ClientTls>,
+) -> Result<HashMap<String, String>, AppError> {
+    let find_arg = |name: &str| {
+        authentication
+            .args
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.value.as_str())
+    };
+
+    let url = replace::replace(
+        find_arg("url").ok_or(AppError::MissingArgument("url".to_owned()))?,
+        input,
+    )?;
+    let method = find_arg("method").unwrap_or(common::POST);
+    let body = replace::replace(find_arg("body").unwrap_or_default(), input)?;
+    let headers = replace::replace_list(
+        authentication
+            .args
+            .iter()
+            .filter(|a| a.name == "header")
+            .map(|a| a.value.as_str())
+            .collect(),
+        input,
+    )?;
+
+    log::debug!("About to login with method {} on url {}", method, url.1);
+
+    let (response, response_headers) = common::execute_http_request_with_headers(
+        url.0.as_str(),
+        method,
+        Some(headers),
+        Some(body.0),
+        tls,
+    )
+    .await?;
+
+    extract_session_values(&authentication.extract, &response, &response_headers)
+}
+
+fn extract_session_values(
+    value_defs: &[SessionValueDef],
+    response: &str,
+    response_headers: &[(String, String)],
+) -> Result<HashMap<String, String>, AppError> {
+    let mut values = HashMap::new();
+
+    for value_def in value_defs {
+        let value = if let Some(json_path) = &value_def.json_path {
+            let json = serde_json::from_str::<serde_json::Value>(response)?;
+            let extracted = json.path(json_path).map_err(AppError::ParseError)?;
+
+            // the query always returns an array with all matches
+            extracted
+                .as_array()
+                .and_then(|matches| matches.first())
+                .and_then(common::convert_value_to_str)
+        } else if let Some(header) = &value_def.header {
+            let header = header.to_lowercase();
+
+            response_headers
+                .iter()
+                .find(|(name, _)| *name == header)
+                .map(|(_, value)| match header.as_str() {
+                    SET_COOKIE => value.split(';').next().unwrap_or_default().to_owned(),
+                    _ => value.to_owned(),
+                })
+        } else {
+            return Err(AppError::InvalidArgument(
+                "extract".to_owned(),
+                Some(value_def.name.clone()),
+            ));
+        };
+
+        let value = value.ok_or(AppError::DataNotFound(format!(
+            "session value {} not found in login response",
+            value_def.name
+        )))?;
+
+        values.insert(value_def.name.clone(), value);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_session_values() {
+        let value_defs = vec![
+            SessionValueDef {
+                name: "token".to_owned(),
+                json_path: Some("$.data.ticket".to_owned()),
+                header: None,
+            },
+            SessionValueDef {
+                name: "cookie".to_owned(),
+                json_path: None,
+                header: Some("Set-Cookie".to_owned()),
+            },
+        ];
+        let response = "{\"data\": {\"ticket\": \"PVE:root@pam:1234\"}}";
+        let headers = vec![(
+            "set-cookie".to_owned(),
+            "id=4711; Path=/; HttpOnly".to_owned(),
+        )];
+
+        let values = extract_session_values(&value_defs, response, &headers).unwrap();
+
+        assert_eq!(
+            values.get("token").map(|v| v.as_str()),
+            Some("PVE:root@pam:1234")
+        );
+        assert_eq!(values.get("cookie").map(|v| v.as_str()), Some("id=4711"));
+    }
+}
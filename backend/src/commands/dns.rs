@@ -0,0 +1,170 @@
+use std::any::Any;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::{common, Command, CommandInput, CommandResult, Parameters};
+use crate::commands::common::replace;
+use crate::models::error::AppError;
+use crate::models::plugin::action::ActionDef;
+use crate::models::plugin::data::DataDef;
+use crate::models::plugin::Plugin;
+use crate::models::server::{Feature, Server};
+
+pub const DNS: &str = "dns";
+
+#[derive(Clone)]
+pub struct DnsCommand {}
+impl DnsCommand {
+    pub fn new() -> Self {
+        DnsCommand {}
+    }
+}
+
+#[async_trait]
+impl Command for DnsCommand {
+    fn get_name(&self) -> &str {
+        DNS
+    }
+
+    async fn execute(&self, input: &CommandInput) -> Result<Box<dyn Any + Sync + Send>, AppError> {
+        let name = input.find_single_arg("name")?;
+        let record_type = find_arg_or_param(input, "record_type").unwrap_or("A");
+        let port = find_arg_or_param(input, "dns_port").unwrap_or("53");
+        let use_tcp = find_arg_or_param(input, "tcp").unwrap_or("false");
+        let timeout = find_arg_or_param(input, "timeout").unwrap_or("5");
+
+        // by default the server itself is queried, since it is most likely a DNS server like Pi-hole or Unbound
+        let server: IpAddr = match find_arg_or_param(input, "dns_server") {
+            Some(server) => replace::replace(server, input)?.0.parse()?,
+            None => input
+                .get_ipaddress()
+                .ok_or(AppError::MissingArgument("dns_server".to_owned()))?,
+        };
+        let server = SocketAddr::new(server, replace::replace(port, input)?.0.parse()?);
+
+        let name = replace::replace(name, input)?.0;
+        let record_type = replace::replace(record_type, input)?.0;
+
+        log::debug!(
+            "About to query {} record of {} from {}",
+            record_type,
+            name,
+            server
+        );
+
+        let response = crate::common::execute_dns_query(
+            server,
+            name.as_str(),
+            record_type.as_str(),
+            use_tcp.parse()?,
+            Duration::from_secs(timeout.parse()?),
+        )
+        .await?;
+
+        let resolved = response.error.is_none() && !response.answers.is_empty();
+
+        Ok(Box::new(DnsCommandResult::new(
+            serde_json::to_string(&response)?.as_str(),
+            resolved,
+        )))
+    }
+}
+
+fn find_arg_or_param<'a>(input: &'a CommandInput, name: &str) -> Option<&'a str> {
+    input
+        .find_single_arg(name)
+        .or_else(|_| input.find_param(name))
+        .ok()
+}
+
+#[derive(Clone)]
+pub struct DnsCommandResult {
+    response: String,
+    resolved: bool,
+}
+impl DnsCommandResult {
+    fn new(response: &str, resolved: bool) -> Self {
+        DnsCommandResult {
+            response: response.to_owned(),
+            resolved,
+        }
+    }
+
+    pub fn get_response(&self) -> String {
+        self.response.clone()
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.resolved
+    }
+}
+
+impl CommandResult for DnsCommandResult {}
+
+pub async fn make_command_input_from_subaction(
+    server: &Server,
+    crypto_key: &str,
+    action: &ActionDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&action.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            DNS,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
+
+pub async fn make_command_input_from_data(
+    server: &Server,
+    crypto_key: &str,
+    data: &DataDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&data.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            DNS,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
@@ -1,18 +1,28 @@
 use std::{any::Any, net::IpAddr};
 
 use super::{
-    common::{self, replace},
+    common::{self, certificate_pinning, replace, session},
     Command, CommandInput, CommandResult, Parameters,
 };
+use crate::common::ClientTls;
 use crate::models::{
     error::AppError,
-    plugin::{action::ActionDef, data::DataDef, detection::DetectionEntry, Plugin},
+    plugin::{
+        action::ActionDef, authentication::AuthenticationDef, data::DataDef,
+        detection::DetectionEntry, Plugin,
+    },
     server::{Feature, Server},
 };
 use async_trait::async_trait;
+use http::StatusCode;
 
 pub const HTTP: &str = "http";
 
+/// credential types of a plugin that are used for the TLS connection instead of being used as placeholders
+pub const CLIENT_CERTIFICATE: &str = "client_certificate";
+pub const CLIENT_KEY: &str = "client_key";
+pub const CA_CERTIFICATE: &str = "ca_certificate";
+
 #[derive(Clone)]
 pub struct HttpCommand {}
 impl HttpCommand {
@@ -28,82 +38,153 @@ impl Command for HttpCommand {
     }
 
     async fn execute(&self, input: &CommandInput) -> Result<Box<dyn Any + Sync + Send>, AppError> {
-        let url = input.find_single_arg("url")?;
-        let method = input.find_single_arg("method")?;
-        let headers = input.find_all_args("header")?;
-
-        let body: &str = match method {
-            "post" => input.find_single_arg("body").unwrap_or({
-                log::warn!(
-                    "Actually expected a body for a post request. Continuing with an empty body."
-                );
-                ""
-            }),
-            "put" => input.find_single_arg("body").unwrap_or({
-                log::error!(
-                    "Actually expected a body for a put request. Continuing with an empty body."
-                );
-                ""
-            }),
-            _ => "",
+        let tls = get_client_tls(input)?;
+
+        let result = match &input.authentication {
+            Some(authentication) => {
+                execute_authenticated_request(input, authentication, tls.as_ref()).await
+            }
+            None => execute_request(input, tls.as_ref()).await,
         };
 
-        let normal_and_masked_url: (String, String) = replace::replace(url, input)?;
-        let normal_and_masked_body: (String, String) = replace::replace(body, input)?;
-        let normal_and_replaced_headers: Vec<(String, String)> =
-            replace::replace_list(headers, input)?;
-
-        if !body.is_empty() {
-            log::debug!(
-                "About to execute method {} on url {} with body {}",
-                method,
-                normal_and_masked_url.1,
-                normal_and_masked_body.1
-            );
+        if let Some(tls) = &tls {
+            certificate_pinning::process_unverified_certificate(input, tls).await?;
+        }
+        let response_string = result?;
 
-            log::debug!(
-                "About to execute method {} on url {} with body {}",
-                method,
-                normal_and_masked_url.0,
-                normal_and_masked_body.0
-            );
-        } else {
-            log::debug!(
-                "About to execute method {} on url {}",
-                method,
-                normal_and_masked_url.1
-            );
+        Ok(Box::new(HttpCommandResult::new(response_string.as_str())))
+    }
+}
 
-            log::debug!(
-                "About to execute method {} on url {}",
-                method,
-                normal_and_masked_url.0
-            );
+/// executes the request with the values of the current session.
+/// If the API responds with 401, the session is most likely expired - so a new login is done and the request is repeated once
+async fn execute_authenticated_request(
+    input: &CommandInput,
+    authentication: &AuthenticationDef,
+    tls: Option<&ClientTls>,
+) -> Result<String, AppError> {
+    let mut input = input.clone();
+    input.session = session::get_session_values(&input, authentication, tls).await?;
+
+    match execute_request(&input, tls).await {
+        Err(AppError::NokOKResponse(StatusCode::UNAUTHORIZED, _)) => {
+            log::debug!("Request was unauthorized. Logging in again.");
+
+            session::invalidate_session(&input).await;
+            input.session = session::get_session_values(&input, authentication, tls).await?;
+
+            execute_request(&input, tls).await
         }
+        result => result,
+    }
+}
+
+async fn execute_request(
+    input: &CommandInput,
+    tls: Option<&ClientTls>,
+) -> Result<String, AppError> {
+    let url = input.find_single_arg("url")?;
+    let method = input.find_single_arg("method")?;
+    let headers = input.find_all_args("header")?;
 
-        if normal_and_masked_url.0.trim().is_empty() {
+    let body: &str = match method {
+        "post" => input.find_single_arg("body").unwrap_or({
             log::warn!(
-            "Given url is empty after replacing placeholders. Was before replace: {}. Request will not be executed",
-            url
+                "Actually expected a body for a post request. Continuing with an empty body."
+            );
+            ""
+        }),
+        "put" => input.find_single_arg("body").unwrap_or({
+            log::error!(
+                "Actually expected a body for a put request. Continuing with an empty body."
+            );
+            ""
+        }),
+        _ => "",
+    };
+
+    let normal_and_masked_url: (String, String) = replace::replace(url, input)?;
+    let normal_and_masked_body: (String, String) = replace::replace(body, input)?;
+    let normal_and_replaced_headers: Vec<(String, String)> = replace::replace_list(headers, input)?;
+
+    if !body.is_empty() {
+        log::debug!(
+            "About to execute method {} on url {} with body {}",
+            method,
+            normal_and_masked_url.1,
+            normal_and_masked_body.1
         );
-            return Err(AppError::InvalidArgument("url".to_string(), None));
-        }
+
         log::debug!(
-            "{} {} {}",
-            normal_and_masked_url.0,
+            "About to execute method {} on url {} with body {}",
             method,
+            normal_and_masked_url.0,
             normal_and_masked_body.0
         );
+    } else {
+        log::debug!(
+            "About to execute method {} on url {}",
+            method,
+            normal_and_masked_url.1
+        );
 
-        let response_string = crate::common::execute_http_request(
-            normal_and_masked_url.0.as_str(),
+        log::debug!(
+            "About to execute method {} on url {}",
             method,
-            Some(normal_and_replaced_headers),
-            Some(normal_and_masked_body.0),
-        )
-        .await?;
+            normal_and_masked_url.0
+        );
+    }
 
-        Ok(Box::new(HttpCommandResult::new(response_string.as_str())))
+    if normal_and_masked_url.0.trim().is_empty() {
+        log::warn!(
+        "Given url is empty after replacing placeholders. Was before replace: {}. Request will not be executed",
+        url
+    );
+        return Err(AppError::InvalidArgument("url".to_string(), None));
+    }
+    log::debug!(
+        "{} {} {}",
+        normal_and_masked_url.0,
+        method,
+        normal_and_masked_body.0
+    );
+
+    crate::common::execute_http_request_with_tls(
+        normal_and_masked_url.0.as_str(),
+        method,
+        Some(normal_and_replaced_headers),
+        Some(normal_and_masked_body.0),
+        tls,
+    )
+    .await
+}
+
+/// collects the client certificate, key and CA certificates from the credentials of the feature and the pinned certificate fingerprint
+fn get_client_tls(input: &CommandInput) -> Result<Option<ClientTls>, AppError> {
+    let get_value = |credential_type: &str| -> Result<Option<String>, AppError> {
+        match input.find_credential_by_type(credential_type) {
+            Some(credential) => {
+                let value = replace::get_credential_value(credential.name.as_str(), input)?.0;
+                Ok(Some(value).filter(|v| !v.trim().is_empty()))
+            }
+            None => Ok(None),
+        }
+    };
+
+    let tls = certificate_pinning::with_pinning(
+        input,
+        ClientTls {
+            certificate: get_value(CLIENT_CERTIFICATE)?,
+            key: get_value(CLIENT_KEY)?,
+            ca_certificates: get_value(CA_CERTIFICATE)?,
+            ..Default::default()
+        },
+    );
+
+    if tls == ClientTls::default() {
+        Ok(None)
+    } else {
+        Ok(Some(tls))
     }
 }
 
@@ -146,14 +227,18 @@ pub async fn make_command_input_from_subaction(
         common::args_to_command_args(&action.args, server, plugin, crypto_key, silent).await?;
 
     for args_list in list_of_args_list {
-        vec.push(CommandInput::new(
-            HTTP,
-            Some(crypto_key),
-            Some(server.get_ipaddress()),
-            args_list,
-            params.clone(),
-            feature.credentials.clone(),
-        ));
+        vec.push(
+            CommandInput::new(
+                HTTP,
+                Some(crypto_key),
+                Some(server.get_ipaddress()),
+                args_list,
+                params.clone(),
+                feature.credentials.clone(),
+            )
+            .with_authentication(&plugin.id, &plugin.authentication)
+            .with_credential_types(&plugin.credentials),
+        );
     }
     Ok(vec)
 }
@@ -179,14 +264,18 @@ pub async fn make_command_input_from_data(
         common::args_to_command_args(&data.args, server, plugin, crypto_key, silent).await?;
 
     for args_list in list_of_args_list {
-        vec.push(CommandInput::new(
-            HTTP,
-            Some(crypto_key),
-            Some(server.get_ipaddress()),
-            args_list,
-            params.clone(),
-            feature.credentials.clone(),
-        ));
+        vec.push(
+            CommandInput::new(
+                HTTP,
+                Some(crypto_key),
+                Some(server.get_ipaddress()),
+                args_list,
+                params.clone(),
+                feature.credentials.clone(),
+            )
+            .with_authentication(&plugin.id, &plugin.authentication)
+            .with_credential_types(&plugin.credentials),
+        );
     }
     Ok(vec)
 }
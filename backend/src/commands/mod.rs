@@ -1,17 +1,28 @@
-use std::{any::Any, net::IpAddr};
+use std::{any::Any, collections::HashMap, net::IpAddr};
 
 use async_trait::async_trait;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
-use crate::models::{error::AppError, server::Credential};
+use crate::models::{
+    error::AppError,
+    plugin::{authentication::AuthenticationDef, CredentialDef},
+    server::Credential,
+};
 
 mod common;
+pub mod dns;
 pub mod http;
 pub mod kafka;
+pub mod modbus;
+pub mod mqtt;
 pub mod ping;
+pub mod script;
+pub mod snmp;
 pub mod socket;
+pub mod ssh;
+pub mod tcp;
 pub mod wol;
 
 lazy_static! {
@@ -28,6 +39,13 @@ fn get_command_list() -> Vec<Box<dyn Command + Sync + Send>> {
         Box::new(wol::WoLCommand::new()),
         Box::new(ping::PingCommand::new()),
         Box::new(kafka::KafkaCommand::new()),
+        Box::new(ssh::SshCommand::new()),
+        Box::new(snmp::SnmpCommand::new()),
+        Box::new(mqtt::MqttCommand::new()),
+        Box::new(tcp::TcpCommand::new()),
+        Box::new(dns::DnsCommand::new()),
+        Box::new(modbus::ModbusCommand::new()),
+        Box::new(script::ScriptCommand::new()),
     ];
     list
 }
@@ -112,6 +130,29 @@ impl Parameters {
     }
 }
 
+/// an input for the server 192.168.178.2 with the given args and feature params - for the tests of the commands
+#[cfg(test)]
+pub fn make_test_input(name: &str, args: &[(&str, &str)], params: &[(&str, &str)]) -> CommandInput {
+    let to_command_args = |values: &[(&str, &str)]| {
+        values
+            .iter()
+            .map(|(name, value)| CommandArg {
+                name: (*name).to_owned(),
+                value: (*value).to_owned(),
+            })
+            .collect()
+    };
+
+    CommandInput::new(
+        name,
+        None,
+        Some("192.168.178.2".parse().expect("should not happen")),
+        to_command_args(args),
+        Parameters::new(Vec::new(), to_command_args(params), Vec::new()),
+        Vec::new(),
+    )
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandInput {
     name: String,
@@ -120,6 +161,10 @@ pub struct CommandInput {
     action_args: Vec<CommandArg>,
     parameters: Parameters,
     credentials: Vec<Credential>,
+    credential_types: HashMap<String, String>,
+    plugin_id: Option<String>,
+    authentication: Option<AuthenticationDef>,
+    session: HashMap<String, String>,
 }
 
 impl CommandInput {
@@ -138,8 +183,30 @@ impl CommandInput {
             action_args,
             parameters,
             credentials,
+            credential_types: HashMap::new(),
+            plugin_id: None,
+            authentication: None,
+            session: HashMap::new(),
         }
     }
+    /// sets the login that needs to be done before executing the command - see `AuthenticationDef`
+    pub fn with_authentication(
+        mut self,
+        plugin_id: &str,
+        authentication: &Option<AuthenticationDef>,
+    ) -> Self {
+        self.plugin_id = Some(plugin_id.to_owned());
+        self.authentication = authentication.clone();
+        self
+    }
+    /// sets the types of the credentials as defined by the plugin - needed to find credentials like client certificates by their type
+    pub fn with_credential_types(mut self, credential_defs: &[CredentialDef]) -> Self {
+        self.credential_types = credential_defs
+            .iter()
+            .map(|c| (c.name.clone(), c.credential_type.clone()))
+            .collect();
+        self
+    }
     pub fn get_name(&self) -> &str {
         self.name.as_str()
     }
@@ -191,6 +258,30 @@ impl CommandInput {
             .map(|p| p.value.as_str())
             .ok_or_else(|| AppError::ArgumentNotFound(name.to_owned()))
     }
+    fn get_all_params(&self) -> HashMap<String, String> {
+        // collected with increasing precedence, so that params override the defaults and override params override both
+        self.parameters
+            .default_params
+            .iter()
+            .chain(self.parameters.params.iter())
+            .chain(self.parameters.override_params.iter())
+            .map(|p| (p.name.clone(), p.value.clone()))
+            .collect()
+    }
+    fn find_session_value(&self, name: &str) -> Result<&str, AppError> {
+        self.session
+            .get(name)
+            .map(|v| v.as_str())
+            .ok_or_else(|| AppError::DataNotFound(format!("session.{}", name)))
+    }
+    fn find_credential_by_type(&self, credential_type: &str) -> Option<Credential> {
+        self.credentials
+            .iter()
+            .find(|c| {
+                self.credential_types.get(&c.name).map(|t| t.as_str()) == Some(credential_type)
+            })
+            .map(|c| c.to_owned())
+    }
     fn find_credential(&self, name: &str) -> Result<Credential, AppError> {
         self.credentials
             .iter()
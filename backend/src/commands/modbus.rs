@@ -0,0 +1,171 @@
+use std::any::Any;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::{common, Command, CommandInput, CommandResult, Parameters};
+use crate::commands::common::replace;
+use crate::common::{ModbusByteOrder, ModbusRegister};
+use crate::models::error::AppError;
+use crate::models::plugin::action::ActionDef;
+use crate::models::plugin::data::DataDef;
+use crate::models::plugin::Plugin;
+use crate::models::server::{Feature, Server};
+
+pub const MODBUS: &str = "modbus";
+
+#[derive(Clone)]
+pub struct ModbusCommand {}
+impl ModbusCommand {
+    pub fn new() -> Self {
+        ModbusCommand {}
+    }
+}
+
+#[async_trait]
+impl Command for ModbusCommand {
+    fn get_name(&self) -> &str {
+        MODBUS
+    }
+
+    async fn execute(&self, input: &CommandInput) -> Result<Box<dyn Any + Sync + Send>, AppError> {
+        let ipaddress = input
+            .get_ipaddress()
+            .ok_or(AppError::MissingArgument("ipaddress".to_owned()))?;
+
+        let port = find_arg_or_param(input, "port").unwrap_or("502");
+        let unit_id = find_arg_or_param(input, "unit_id").unwrap_or("1");
+        let byte_order = find_arg_or_param(input, "byte_order").unwrap_or("ABCD");
+        let timeout = find_arg_or_param(input, "timeout").unwrap_or("5");
+
+        let registers = input
+            .find_all_args("register")?
+            .iter()
+            .map(|register| {
+                replace::replace(register, input)?
+                    .0
+                    .parse::<ModbusRegister>()
+            })
+            .collect::<Result<Vec<ModbusRegister>, AppError>>()?;
+
+        if registers.is_empty() {
+            return Err(AppError::MissingArgument("register".to_owned()));
+        }
+
+        let address = SocketAddr::new(ipaddress, replace::replace(port, input)?.0.parse()?);
+        let unit_id: u8 = replace::replace(unit_id, input)?.0.parse()?;
+        let byte_order: ModbusByteOrder = byte_order.parse()?;
+
+        log::debug!(
+            "About to read {} values from Modbus device {} (unit {})",
+            registers.len(),
+            address,
+            unit_id
+        );
+
+        let response = crate::common::execute_modbus_request(
+            address,
+            unit_id,
+            &registers,
+            &byte_order,
+            Duration::from_secs(timeout.parse()?),
+        )
+        .await?;
+
+        Ok(Box::new(ModbusCommandResult::new(
+            serde_json::to_string(&response)?.as_str(),
+        )))
+    }
+}
+
+fn find_arg_or_param<'a>(input: &'a CommandInput, name: &str) -> Option<&'a str> {
+    input
+        .find_single_arg(name)
+        .or_else(|_| input.find_param(name))
+        .ok()
+}
+
+#[derive(Clone)]
+pub struct ModbusCommandResult {
+    response: String,
+}
+impl ModbusCommandResult {
+    fn new(response: &str) -> Self {
+        ModbusCommandResult {
+            response: response.to_owned(),
+        }
+    }
+
+    pub fn get_response(&self) -> String {
+        self.response.clone()
+    }
+}
+
+impl CommandResult for ModbusCommandResult {}
+
+pub async fn make_command_input_from_subaction(
+    server: &Server,
+    crypto_key: &str,
+    action: &ActionDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&action.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            MODBUS,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
+
+pub async fn make_command_input_from_data(
+    server: &Server,
+    crypto_key: &str,
+    data: &DataDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&data.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            MODBUS,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
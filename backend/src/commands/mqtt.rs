@@ -0,0 +1,258 @@
+use std::any::Any;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::{common, Command, CommandInput, CommandResult, Parameters};
+use crate::commands::common::replace;
+use crate::models::error::AppError;
+use crate::models::plugin::action::ActionDef;
+use crate::models::plugin::data::DataDef;
+use crate::models::plugin::Plugin;
+use crate::models::server::{Feature, Server};
+
+pub const MQTT: &str = "mqtt";
+const SUBSCRIBE_ARG: &str = "subscribe";
+
+#[derive(Clone)]
+pub struct MqttCommand {}
+impl MqttCommand {
+    pub fn new() -> Self {
+        MqttCommand {}
+    }
+}
+
+#[async_trait]
+impl Command for MqttCommand {
+    fn get_name(&self) -> &str {
+        MQTT
+    }
+
+    async fn execute(&self, input: &CommandInput) -> Result<Box<dyn Any + Sync + Send>, AppError> {
+        if let Some(topic) = get_subscription_topic(input)? {
+            // subscriptions are not polled - the last received (retained) message is returned instead
+            crate::common::subscribe_mqtt_topic(topic.as_str()).await?;
+
+            let response = crate::common::get_last_mqtt_message(topic.as_str())?;
+            return Ok(Box::new(MqttCommandResult::new(
+                response.unwrap_or_default().as_str(),
+            )));
+        }
+
+        let request = parse_request(input)?;
+
+        log::debug!(
+            "About to publish {} to mqtt topic {}",
+            request.masked_command,
+            request.topic
+        );
+
+        let response = crate::common::execute_mqtt_request(
+            request.topic.as_str(),
+            request.command.as_str(),
+            request.response_topic.as_deref(),
+            request.timeout,
+        )
+        .await?;
+
+        Ok(Box::new(MqttCommandResult::new(response.as_str())))
+    }
+}
+
+/// the message to publish with all placeholders replaced
+struct MqttRequest {
+    topic: String,
+    response_topic: Option<String>,
+    command: String,
+    /// the command with masked credentials, so that it can be logged
+    masked_command: String,
+    timeout: Duration,
+}
+
+/// Reads the args of the input - topic, response topic and timeout can also be set as params of the feature
+fn parse_request(input: &CommandInput) -> Result<MqttRequest, AppError> {
+    let topic = input
+        .find_single_arg("topic")
+        .or_else(|_| input.find_param("topic"))?;
+    let response_topic = input
+        .find_single_arg("response_topic")
+        .or_else(|_| input.find_param("response_topic"))
+        .ok();
+    let command = input.find_single_arg("command").unwrap_or_default();
+    let timeout = input
+        .find_single_arg("timeout")
+        .unwrap_or(input.find_param("timeout").unwrap_or("5"));
+
+    let response_topic = match response_topic {
+        Some(response_topic) => Some(replace::replace(response_topic, input)?.0),
+        None => None,
+    };
+    let normal_and_masked_command: (String, String) = replace::replace(command, input)?;
+
+    Ok(MqttRequest {
+        topic: replace::replace(topic, input)?.0,
+        response_topic,
+        command: normal_and_masked_command.0,
+        masked_command: normal_and_masked_command.1,
+        timeout: Duration::from_secs(timeout.parse()?),
+    })
+}
+
+/// Returns true if the data is provided by a MQTT subscription instead of a request
+pub fn is_subscription(data: &DataDef) -> bool {
+    data.command == MQTT && data.args.iter().any(|a| a.name == SUBSCRIBE_ARG)
+}
+
+/// Returns the topic (filter) with all placeholders replaced if the input is for a subscription
+pub fn get_subscription_topic(input: &CommandInput) -> Result<Option<String>, AppError> {
+    match input.find_single_arg(SUBSCRIBE_ARG) {
+        Ok(topic) => Ok(Some(replace::replace(topic, input)?.0)),
+        Err(_) => Ok(None),
+    }
+}
+
+#[derive(Clone)]
+pub struct MqttCommandResult {
+    response: String,
+}
+impl MqttCommandResult {
+    fn new(response: &str) -> Self {
+        MqttCommandResult {
+            response: response.to_owned(),
+        }
+    }
+
+    pub fn get_response(&self) -> String {
+        self.response.clone()
+    }
+}
+
+impl CommandResult for MqttCommandResult {}
+
+pub async fn make_command_input_from_subaction(
+    server: &Server,
+    crypto_key: &str,
+    action: &ActionDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&action.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            MQTT,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
+
+pub async fn make_command_input_from_data(
+    server: &Server,
+    crypto_key: &str,
+    data: &DataDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&data.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            MQTT,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::make_test_input;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let input = make_test_input(
+            MQTT,
+            &[
+                ("topic", "cmnd/${params.device}/POWER"),
+                ("command", "TOGGLE"),
+                ("timeout", "2"),
+            ],
+            &[
+                ("device", "tasmota_1"),
+                ("response_topic", "stat/${params.device}/RESULT"),
+            ],
+        );
+
+        let request = parse_request(&input).expect("should not happen");
+
+        assert_eq!(request.topic, "cmnd/tasmota_1/POWER");
+        assert_eq!(
+            request.response_topic,
+            Some("stat/tasmota_1/RESULT".to_owned())
+        );
+        assert_eq!(request.command, "TOGGLE");
+        assert_eq!(request.timeout, Duration::from_secs(2));
+
+        // without a topic there is nothing to publish to
+        let without_topic = make_test_input(MQTT, &[("command", "ON")], &[]);
+        assert!(parse_request(&without_topic).is_err());
+    }
+
+    #[test]
+    fn test_subscription() {
+        let data: DataDef = serde_json::from_str(
+            "{\"id\":\"power\",\"command\":\"mqtt\",\"args\":[{\"name\":\"subscribe\",\"value\":\"tele/${params.device}/SENSOR\"}]}",
+        )
+        .expect("should not happen");
+        assert!(is_subscription(&data));
+
+        let input = make_test_input(
+            MQTT,
+            &[(SUBSCRIBE_ARG, "tele/${params.device}/SENSOR")],
+            &[("device", "tasmota_1")],
+        );
+        assert_eq!(
+            get_subscription_topic(&input).expect("should not happen"),
+            Some("tele/tasmota_1/SENSOR".to_owned())
+        );
+
+        let input = make_test_input(MQTT, &[("topic", "cmnd/tasmota_1/POWER")], &[]);
+        assert_eq!(
+            get_subscription_topic(&input).expect("should not happen"),
+            None
+        );
+    }
+}
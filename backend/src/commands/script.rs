@@ -0,0 +1,147 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use super::{common, Command, CommandInput, CommandResult, Parameters};
+use crate::commands::common::replace;
+use crate::common::ScriptContext;
+use crate::models::error::AppError;
+use crate::models::plugin::action::ActionDef;
+use crate::models::plugin::common::Script;
+use crate::models::plugin::data::DataDef;
+use crate::models::plugin::Plugin;
+use crate::models::server::{Feature, Server};
+
+pub const SCRIPT: &str = "script";
+
+#[derive(Clone)]
+pub struct ScriptCommand {}
+impl ScriptCommand {
+    pub fn new() -> Self {
+        ScriptCommand {}
+    }
+}
+
+#[async_trait]
+impl Command for ScriptCommand {
+    fn get_name(&self) -> &str {
+        SCRIPT
+    }
+
+    async fn execute(&self, input: &CommandInput) -> Result<Box<dyn Any + Sync + Send>, AppError> {
+        let script = Script {
+            script_type: input.find_single_arg("script_type")?.to_owned(),
+            script: input.find_single_arg("script")?.to_owned(),
+        };
+
+        let context = ScriptContext {
+            ipaddress: input.get_ipaddress().map(|ip| format!("{}", ip)),
+            params: input.get_all_params(),
+            credentials: get_credentials(input)?,
+        };
+
+        log::debug!("About to execute {} script", script.script_type);
+
+        // the scripts are synchronous and http requests block within them, so they must not run on the async workers
+        let response =
+            tokio::task::spawn_blocking(move || crate::common::script_execute(&script, context))
+                .await??;
+
+        Ok(Box::new(ScriptCommandResult::new(response.as_str())))
+    }
+}
+
+fn get_credentials(input: &CommandInput) -> Result<HashMap<String, String>, AppError> {
+    let mut credentials = HashMap::new();
+
+    for credential in &input.credentials {
+        let value = replace::get_credential_value(credential.name.as_str(), input)?.0;
+        credentials.insert(credential.name.clone(), value);
+    }
+    Ok(credentials)
+}
+
+#[derive(Clone)]
+pub struct ScriptCommandResult {
+    response: String,
+}
+impl ScriptCommandResult {
+    fn new(response: &str) -> Self {
+        ScriptCommandResult {
+            response: response.to_owned(),
+        }
+    }
+
+    pub fn get_response(&self) -> String {
+        self.response.clone()
+    }
+}
+
+impl CommandResult for ScriptCommandResult {}
+
+pub async fn make_command_input_from_subaction(
+    server: &Server,
+    crypto_key: &str,
+    action: &ActionDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&action.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            SCRIPT,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
+
+pub async fn make_command_input_from_data(
+    server: &Server,
+    crypto_key: &str,
+    data: &DataDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&data.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            SCRIPT,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
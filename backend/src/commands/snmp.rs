@@ -0,0 +1,206 @@
+use std::any::Any;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::{common, Command, CommandInput, CommandResult, Parameters};
+use crate::commands::common::replace;
+use crate::common::{SnmpCredentials, SnmpOperation};
+use crate::models::error::AppError;
+use crate::models::plugin::action::ActionDef;
+use crate::models::plugin::data::DataDef;
+use crate::models::plugin::Plugin;
+use crate::models::server::{Feature, Server};
+
+pub const SNMP: &str = "snmp";
+
+#[derive(Clone)]
+pub struct SnmpCommand {}
+impl SnmpCommand {
+    pub fn new() -> Self {
+        SnmpCommand {}
+    }
+}
+
+#[async_trait]
+impl Command for SnmpCommand {
+    fn get_name(&self) -> &str {
+        SNMP
+    }
+
+    async fn execute(&self, input: &CommandInput) -> Result<Box<dyn Any + Sync + Send>, AppError> {
+        let ipaddress = input
+            .get_ipaddress()
+            .ok_or(AppError::MissingArgument("ipaddress".to_string()))?;
+
+        let operation = find_arg_or_param(input, "operation").unwrap_or("get");
+        let version = find_arg_or_param(input, "version").unwrap_or("2c");
+        let port = find_arg_or_param(input, "port").unwrap_or("161");
+        let timeout = find_arg_or_param(input, "timeout").unwrap_or("5");
+
+        let mut oids = Vec::new();
+        for oid in input.find_all_args("oid")? {
+            oids.push(replace::replace(oid, input)?.0);
+        }
+
+        let operation = match operation.to_lowercase().as_str() {
+            "set" => SnmpOperation::Set {
+                value_type: input
+                    .find_single_arg("value_type")
+                    .unwrap_or("s")
+                    .to_owned(),
+                value: replace::replace(input.find_single_arg("value")?, input)?.0,
+            },
+            other => other.parse()?,
+        };
+
+        let credentials = match version {
+            "2c" | "v2c" | "2" => SnmpCredentials::V2c {
+                community: replace::replace(
+                    find_arg_or_param(input, "community").unwrap_or("public"),
+                    input,
+                )?
+                .0,
+            },
+            "3" | "v3" => SnmpCredentials::V3 {
+                username: replace::replace(input.find_single_arg("username")?, input)?.0,
+                auth_password: replace_optional(
+                    input.find_single_arg("auth_password").ok(),
+                    input,
+                )?,
+                auth_protocol: find_arg_or_param(input, "auth_protocol").map(str::to_owned),
+                priv_password: replace_optional(
+                    input.find_single_arg("priv_password").ok(),
+                    input,
+                )?,
+                priv_protocol: find_arg_or_param(input, "priv_protocol").map(str::to_owned),
+            },
+            other => {
+                return Err(AppError::InvalidArgument(
+                    "version".to_owned(),
+                    Some(other.to_owned()),
+                ))
+            }
+        };
+
+        log::debug!(
+            "About to execute snmp {:?} for oids {:?} on {}:{}",
+            operation,
+            oids,
+            ipaddress,
+            port
+        );
+
+        let response = crate::common::execute_snmp_request(
+            ipaddress,
+            replace::replace(port, input)?.0.parse()?,
+            credentials,
+            operation,
+            oids,
+            Duration::from_secs(timeout.parse()?),
+        )
+        .await?;
+
+        Ok(Box::new(SnmpCommandResult::new(
+            serde_json::to_string(&response)?.as_str(),
+        )))
+    }
+}
+
+fn find_arg_or_param<'a>(input: &'a CommandInput, name: &str) -> Option<&'a str> {
+    input
+        .find_single_arg(name)
+        .or_else(|_| input.find_param(name))
+        .ok()
+}
+
+fn replace_optional(value: Option<&str>, input: &CommandInput) -> Result<Option<String>, AppError> {
+    match value {
+        Some(value) => Ok(Some(replace::replace(value, input)?.0)),
+        None => Ok(None),
+    }
+}
+
+#[derive(Clone)]
+pub struct SnmpCommandResult {
+    response: String,
+}
+impl SnmpCommandResult {
+    fn new(response: &str) -> Self {
+        SnmpCommandResult {
+            response: response.to_owned(),
+        }
+    }
+
+    pub fn get_response(&self) -> String {
+        self.response.clone()
+    }
+}
+
+impl CommandResult for SnmpCommandResult {}
+
+pub async fn make_command_input_from_subaction(
+    server: &Server,
+    crypto_key: &str,
+    action: &ActionDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&action.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            SNMP,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
+
+pub async fn make_command_input_from_data(
+    server: &Server,
+    crypto_key: &str,
+    data: &DataDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&data.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            SNMP,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
@@ -0,0 +1,289 @@
+use std::any::Any;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::{common, Command, CommandInput, CommandResult, Parameters};
+use crate::commands::common::{certificate_pinning, replace};
+use crate::common::{SshCredentials, SshResponse};
+use crate::models::error::AppError;
+use crate::models::plugin::action::ActionDef;
+use crate::models::plugin::data::DataDef;
+use crate::models::plugin::Plugin;
+use crate::models::server::{Feature, Server};
+
+pub const SSH: &str = "ssh";
+
+#[derive(Clone)]
+pub struct SshCommand {}
+impl SshCommand {
+    pub fn new() -> Self {
+        SshCommand {}
+    }
+}
+
+#[async_trait]
+impl Command for SshCommand {
+    fn get_name(&self) -> &str {
+        SSH
+    }
+
+    async fn execute(&self, input: &CommandInput) -> Result<Box<dyn Any + Sync + Send>, AppError> {
+        let ipaddress = input
+            .get_ipaddress()
+            .ok_or(AppError::MissingArgument("ipaddress".to_string()))?;
+
+        let request = parse_request(input)?;
+
+        log::debug!(
+            "About to execute ssh command {} on {}:{} as user {}",
+            request.masked_command,
+            ipaddress,
+            request.port,
+            request.credentials.username
+        );
+
+        let pinned_host_key = certificate_pinning::get_pinned_host_key(input);
+        let pinned = pinned_host_key.clone();
+
+        let result = tokio::task::spawn_blocking(move || {
+            crate::common::execute_ssh_command(
+                ipaddress,
+                request.port,
+                request.credentials,
+                request.command.as_str(),
+                request.timeout,
+                pinned.as_deref(),
+            )
+        })
+        .await?;
+
+        let response = match result {
+            Ok(response) => response,
+            Err(AppError::HostKeyMismatch(fingerprint)) => {
+                certificate_pinning::process_host_key(
+                    input,
+                    pinned_host_key.as_deref(),
+                    &fingerprint,
+                )
+                .await?;
+                return Err(AppError::HostKeyMismatch(fingerprint));
+            }
+            Err(err) => return Err(err),
+        };
+
+        certificate_pinning::process_host_key(
+            input,
+            pinned_host_key.as_deref(),
+            &response.host_key_fingerprint,
+        )
+        .await?;
+
+        Ok(Box::new(SshCommandResult::from_response(&response)?))
+    }
+}
+
+/// the command and the connection settings with all placeholders replaced
+struct SshRequest {
+    command: String,
+    /// the command with masked credentials, so that it can be logged
+    masked_command: String,
+    credentials: SshCredentials,
+    port: u16,
+    timeout: Duration,
+}
+
+/// Reads the args of the input - port and timeout can also be set as params of the feature
+fn parse_request(input: &CommandInput) -> Result<SshRequest, AppError> {
+    let command = input.find_single_arg("command")?;
+    let username = input.find_single_arg("username")?;
+    let password = input.find_single_arg("password").ok();
+    let private_key = input.find_single_arg("private_key").ok();
+    let passphrase = input.find_single_arg("passphrase").ok();
+
+    let port = input
+        .find_single_arg("port")
+        .unwrap_or(input.find_param("port").unwrap_or("22"));
+    let timeout = input
+        .find_single_arg("timeout")
+        .unwrap_or(input.find_param("timeout").unwrap_or("10"));
+
+    let normal_and_masked_command: (String, String) = replace::replace(command, input)?;
+
+    let credentials = SshCredentials {
+        username: replace::replace(username, input)?.0,
+        password: replace_optional(password, input)?,
+        private_key: replace_optional(private_key, input)?,
+        passphrase: replace_optional(passphrase, input)?,
+    };
+
+    Ok(SshRequest {
+        command: normal_and_masked_command.0,
+        masked_command: normal_and_masked_command.1,
+        credentials,
+        port: replace::replace(port, input)?.0.parse()?,
+        timeout: Duration::from_secs(timeout.parse()?),
+    })
+}
+
+fn replace_optional(value: Option<&str>, input: &CommandInput) -> Result<Option<String>, AppError> {
+    match value {
+        Some(value) => Ok(Some(replace::replace(value, input)?.0)),
+        None => Ok(None),
+    }
+}
+
+#[derive(Clone)]
+pub struct SshCommandResult {
+    response: String,
+    exit_code: i32,
+}
+impl SshCommandResult {
+    /// stdout, stderr and the exit code are passed as JSON to the scripts of the plugin
+    fn from_response(response: &SshResponse) -> Result<Self, AppError> {
+        Ok(SshCommandResult {
+            response: serde_json::to_string(response)?,
+            exit_code: response.exit_code,
+        })
+    }
+
+    pub fn get_response(&self) -> String {
+        self.response.clone()
+    }
+
+    pub fn get_exit_code(&self) -> i32 {
+        self.exit_code
+    }
+}
+
+impl CommandResult for SshCommandResult {}
+
+pub async fn make_command_input_from_subaction(
+    server: &Server,
+    crypto_key: &str,
+    action: &ActionDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&action.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            SSH,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
+
+pub async fn make_command_input_from_data(
+    server: &Server,
+    crypto_key: &str,
+    data: &DataDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&data.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            SSH,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::make_test_input;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let input = make_test_input(
+            SSH,
+            &[
+                ("command", "df -h ${params.mount}"),
+                ("username", "${params.user}"),
+                ("password", "secret"),
+            ],
+            &[("mount", "/"), ("user", "admin"), ("port", "2222")],
+        );
+
+        let request = parse_request(&input).expect("should not happen");
+
+        assert_eq!(request.command, "df -h /");
+        assert_eq!(request.credentials.username, "admin");
+        assert_eq!(request.credentials.password, Some("secret".to_owned()));
+        assert_eq!(request.credentials.private_key, None);
+        // the port of the params is used, the timeout falls back to the default
+        assert_eq!(request.port, 2222);
+        assert_eq!(request.timeout, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_parse_request_with_invalid_args() {
+        let without_username = make_test_input(SSH, &[("command", "uptime")], &[]);
+        assert!(parse_request(&without_username).is_err());
+
+        let invalid_port = make_test_input(
+            SSH,
+            &[
+                ("command", "uptime"),
+                ("username", "admin"),
+                ("port", "ssh"),
+            ],
+            &[],
+        );
+        assert!(parse_request(&invalid_port).is_err());
+    }
+
+    #[test]
+    fn test_result_from_response() {
+        let result = SshCommandResult::from_response(&SshResponse {
+            stdout: "up 3 days\n".to_owned(),
+            stderr: String::new(),
+            exit_code: 1,
+            host_key_fingerprint: "AB:CD".to_owned(),
+        })
+        .expect("should not happen");
+
+        assert_eq!(result.get_exit_code(), 1);
+        assert_eq!(
+            result.get_response(),
+            "{\"stdout\":\"up 3 days\\n\",\"stderr\":\"\",\"exit_code\":1}"
+        );
+    }
+}
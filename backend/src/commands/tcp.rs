@@ -0,0 +1,194 @@
+use std::any::Any;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::{common, Command, CommandInput, CommandResult, Parameters};
+use crate::commands::common::replace;
+use crate::models::error::AppError;
+use crate::models::plugin::action::ActionDef;
+use crate::models::plugin::data::DataDef;
+use crate::models::plugin::Plugin;
+use crate::models::server::{Feature, Server};
+
+pub const TCP: &str = "tcp";
+
+#[derive(Clone)]
+pub struct TcpCommand {}
+impl TcpCommand {
+    pub fn new() -> Self {
+        TcpCommand {}
+    }
+}
+
+#[async_trait]
+impl Command for TcpCommand {
+    fn get_name(&self) -> &str {
+        TCP
+    }
+
+    async fn execute(&self, input: &CommandInput) -> Result<Box<dyn Any + Sync + Send>, AppError> {
+        let ipaddress = input
+            .get_ipaddress()
+            .ok_or(AppError::MissingArgument("ipaddress".to_string()))?;
+
+        let port =
+            find_arg_or_param(input, "port").ok_or(AppError::MissingArgument("port".to_owned()))?;
+        let protocol = find_arg_or_param(input, "protocol").unwrap_or("tcp");
+        let tls = find_arg_or_param(input, "tls").unwrap_or("false");
+        let read_banner = find_arg_or_param(input, "read_banner").unwrap_or("false");
+        let timeout = find_arg_or_param(input, "timeout").unwrap_or("5");
+
+        let server_name = match find_arg_or_param(input, "server_name") {
+            Some(server_name) => Some(replace::replace(server_name, input)?.0),
+            None => None,
+        };
+        let probe = match input.find_single_arg("probe") {
+            Ok(probe) => Some(replace::replace(probe, input)?),
+            Err(_) => None,
+        };
+
+        let port: u16 = replace::replace(port, input)?.0.parse()?;
+        let timeout = Duration::from_secs(timeout.parse()?);
+
+        log::debug!(
+            "About to check {} port {} on {} with probe {:?}",
+            protocol,
+            port,
+            ipaddress,
+            probe.as_ref().map(|p| p.1.as_str())
+        );
+
+        let response = match protocol.to_lowercase().as_str() {
+            "udp" => {
+                crate::common::check_udp_port(
+                    ipaddress,
+                    port,
+                    probe.as_ref().map(|p| p.0.as_str()),
+                    timeout,
+                )
+                .await?
+            }
+            "tcp" => {
+                crate::common::check_tcp_port(
+                    ipaddress,
+                    port,
+                    tls.parse()?,
+                    server_name.as_deref(),
+                    probe.as_ref().map(|p| p.0.as_str()),
+                    read_banner.parse()?,
+                    timeout,
+                )
+                .await?
+            }
+            other => {
+                return Err(AppError::InvalidArgument(
+                    "protocol".to_owned(),
+                    Some(other.to_owned()),
+                ))
+            }
+        };
+
+        Ok(Box::new(TcpCommandResult::new(
+            serde_json::to_string(&response)?.as_str(),
+            response.reachable,
+        )))
+    }
+}
+
+fn find_arg_or_param<'a>(input: &'a CommandInput, name: &str) -> Option<&'a str> {
+    input
+        .find_single_arg(name)
+        .or_else(|_| input.find_param(name))
+        .ok()
+}
+
+#[derive(Clone)]
+pub struct TcpCommandResult {
+    response: String,
+    reachable: bool,
+}
+impl TcpCommandResult {
+    fn new(response: &str, reachable: bool) -> Self {
+        TcpCommandResult {
+            response: response.to_owned(),
+            reachable,
+        }
+    }
+
+    pub fn get_response(&self) -> String {
+        self.response.clone()
+    }
+
+    pub fn is_reachable(&self) -> bool {
+        self.reachable
+    }
+}
+
+impl CommandResult for TcpCommandResult {}
+
+pub async fn make_command_input_from_subaction(
+    server: &Server,
+    crypto_key: &str,
+    action: &ActionDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&action.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            TCP,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
+
+pub async fn make_command_input_from_data(
+    server: &Server,
+    crypto_key: &str,
+    data: &DataDef,
+    action_params: Option<String>,
+    feature: &Feature,
+    plugin: &Plugin,
+    silent: &bool,
+) -> Result<Vec<CommandInput>, AppError> {
+    let params = Parameters::new(
+        common::string_params_to_command_args(action_params)?,
+        common::params_to_command_args(&feature.params),
+        common::param_def_to_command_args(&plugin.params),
+    );
+
+    let mut vec = Vec::new();
+
+    let list_of_args_list =
+        common::args_to_command_args(&data.args, server, plugin, crypto_key, silent).await?;
+
+    for args_list in list_of_args_list {
+        vec.push(CommandInput::new(
+            TCP,
+            Some(crypto_key),
+            Some(server.get_ipaddress()),
+            args_list,
+            params.clone(),
+            feature.credentials.clone(),
+        ));
+    }
+    Ok(vec)
+}
@@ -11,7 +11,7 @@ use log_derive::logfn;
 use magic_crypt::{new_magic_crypt, MagicCryptTrait};
 use pbkdf2::pbkdf2_hmac_array;
 use rand::{rngs::OsRng, thread_rng, RngCore};
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 
 use crate::models::error::AppError;
 
@@ -126,6 +126,22 @@ pub fn verify_password(password: &str, hashed_password: &str) -> Result<bool, Ap
     verify(password, hashed_password).map_err(AppError::from)
 }
 
+/// SHA-256 as hex string - unlike passwords, tokens are long random strings, so a fast hash is sufficient
+/// and allows to look up the token by its hash
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Compares tokens in constant time, so that the duration of the comparison doesn't tell how many characters match.
+/// The hashes are compared, so that the length of the token isn't revealed either
+pub fn is_same_token(token: &str, other: &str) -> bool {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .zip(Sha256::digest(other.as_bytes()).iter())
+        .fold(0, |difference, (a, b)| difference | (a ^ b))
+        == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,6 +151,21 @@ mod tests {
         assert_eq!(get_random_key32().expect("should not happen").len(), 64);
     }
 
+    #[test]
+    fn test_hash_token() {
+        assert_eq!(
+            hash_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_is_same_token() {
+        assert!(is_same_token("Bearer abc", "Bearer abc"));
+        assert!(!is_same_token("Bearer abc", "Bearer abd"));
+        assert!(!is_same_token("Bearer abc", "Bearer ab"));
+    }
+
     #[test]
     fn test_roundtrip() {
         let key = "this is a key";
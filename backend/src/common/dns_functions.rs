@@ -0,0 +1,367 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use dnsclient::{r#async::DNSClient, UpstreamServer};
+use serde::{Deserialize, Serialize};
+
+use crate::models::error::AppError;
+
+const HEADER_SIZE: usize = 12;
+const CLASS_IN: u16 = 1;
+const MAX_POINTER_JUMPS: usize = 32;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DnsResponse {
+    pub name: String,
+    pub record_type: String,
+    pub server: String,
+    pub response_code: Option<String>,
+    pub response_time_ms: Option<u64>,
+    pub answers: Vec<DnsAnswer>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DnsAnswer {
+    pub name: String,
+    pub record_type: String,
+    pub ttl: u32,
+    pub value: String,
+}
+
+/// Resolves the given name against the given DNS server and returns all answers together with the response time
+///
+/// # Arguments
+///
+/// * `server` the DNS server (IP and port) that should be queried
+/// * `name` the name to resolve. For PTR queries an IP address can be given which is converted into the reverse lookup name
+/// * `record_type` one of A, AAAA, CNAME, MX, NS, PTR, SOA, SRV, TXT or ANY
+/// * `use_tcp` query via TCP instead of UDP
+/// * `timeout` timeout for the query
+///
+/// A server that does not respond is no error - the response contains the error message and no answers instead
+pub async fn execute_dns_query(
+    server: SocketAddr,
+    name: &str,
+    record_type: &str,
+    use_tcp: bool,
+    timeout: Duration,
+) -> Result<DnsResponse, AppError> {
+    let type_code = record_type_to_code(record_type)?;
+
+    let name = match (type_code, name.parse::<IpAddr>()) {
+        (12, Ok(ipaddress)) => reverse_lookup_name(&ipaddress),
+        _ => name.trim_end_matches('.').to_owned(),
+    };
+
+    let mut client = DNSClient::new(vec![UpstreamServer::new(server)]);
+    client.set_timeout(timeout);
+    client.force_tcp(use_tcp);
+
+    let mut response = DnsResponse {
+        name: name.clone(),
+        record_type: record_type.to_uppercase(),
+        server: format!("{}", server),
+        response_code: None,
+        response_time_ms: None,
+        answers: Vec::new(),
+        error: None,
+    };
+
+    let start = Instant::now();
+    match client
+        .query_raw(build_query(&name, type_code)?.as_slice(), true)
+        .await
+    {
+        Ok(packet) => {
+            response.response_time_ms = Some(start.elapsed().as_millis() as u64);
+
+            let (response_code, answers) = parse_response(&packet)?;
+            response.response_code = Some(response_code);
+            response.answers = answers;
+        }
+        Err(err) => response.error = Some(format!("{}", err)),
+    }
+
+    Ok(response)
+}
+
+fn build_query(name: &str, type_code: u16) -> Result<Vec<u8>, AppError> {
+    let mut query = Vec::with_capacity(HEADER_SIZE + name.len() + 6);
+
+    // id, flags (recursion desired), 1 question, no answer/authority/additional records
+    query.extend_from_slice(&[0, 0, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+
+    for label in name.split('.').filter(|l| !l.is_empty()) {
+        if label.len() > 63 {
+            return Err(AppError::InvalidArgument(
+                "name".to_owned(),
+                Some(name.to_owned()),
+            ));
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&type_code.to_be_bytes());
+    query.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+    Ok(query)
+}
+
+fn parse_response(packet: &[u8]) -> Result<(String, Vec<DnsAnswer>), AppError> {
+    if packet.len() < HEADER_SIZE {
+        return Err(invalid_response());
+    }
+
+    let response_code = response_code_to_string(packet[3] & 0x0f);
+    let question_count = read_u16(packet, 4)?;
+    let answer_count = read_u16(packet, 6)?;
+
+    let mut offset = HEADER_SIZE;
+    for _ in 0..question_count {
+        offset = read_name(packet, offset)?.1 + 4; // type and class
+    }
+
+    let mut answers = Vec::new();
+    for _ in 0..answer_count {
+        let (name, next) = read_name(packet, offset)?;
+        let type_code = read_u16(packet, next)?;
+        let ttl = read_u32(packet, next + 4)?;
+        let data_length = read_u16(packet, next + 8)? as usize;
+        let data_offset = next + 10;
+
+        if data_offset + data_length > packet.len() {
+            return Err(invalid_response());
+        }
+
+        answers.push(DnsAnswer {
+            name,
+            record_type: code_to_record_type(type_code),
+            ttl,
+            value: read_record_data(packet, type_code, data_offset, data_length)?,
+        });
+        offset = data_offset + data_length;
+    }
+
+    Ok((response_code, answers))
+}
+
+fn read_record_data(
+    packet: &[u8],
+    type_code: u16,
+    offset: usize,
+    length: usize,
+) -> Result<String, AppError> {
+    let data = &packet[offset..offset + length];
+
+    let value = match type_code {
+        1 if length == 4 => format!("{}", Ipv4Addr::new(data[0], data[1], data[2], data[3])),
+        28 if length == 16 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(data);
+            format!("{}", Ipv6Addr::from(octets))
+        }
+        2 | 5 | 12 => read_name(packet, offset)?.0,
+        15 => format!(
+            "{} {}",
+            read_u16(packet, offset)?,
+            read_name(packet, offset + 2)?.0
+        ),
+        16 => {
+            let mut strings = Vec::new();
+            let mut pos = 0;
+            while pos < data.len() {
+                let len = data[pos] as usize;
+                let end = (pos + 1 + len).min(data.len());
+                strings.push(String::from_utf8_lossy(&data[pos + 1..end]).to_string());
+                pos = end;
+            }
+            strings.join("")
+        }
+        33 => format!(
+            "{} {} {} {}",
+            read_u16(packet, offset)?,
+            read_u16(packet, offset + 2)?,
+            read_u16(packet, offset + 4)?,
+            read_name(packet, offset + 6)?.0
+        ),
+        6 => {
+            let (mname, next) = read_name(packet, offset)?;
+            let (rname, next) = read_name(packet, next)?;
+            format!(
+                "{} {} {} {} {} {} {}",
+                mname,
+                rname,
+                read_u32(packet, next)?,
+                read_u32(packet, next + 4)?,
+                read_u32(packet, next + 8)?,
+                read_u32(packet, next + 12)?,
+                read_u32(packet, next + 16)?
+            )
+        }
+        _ => hex::encode(data),
+    };
+    Ok(value)
+}
+
+/// reads a (possibly compressed) name and returns it together with the offset directly behind the name
+fn read_name(packet: &[u8], offset: usize) -> Result<(String, usize), AppError> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut pos = offset;
+    let mut end_offset = None;
+    let mut jumps = 0;
+
+    loop {
+        let len = *packet.get(pos).ok_or_else(invalid_response)? as usize;
+
+        if len == 0 {
+            break;
+        }
+
+        if len & 0xc0 == 0xc0 {
+            let pointer = (read_u16(packet, pos)? & 0x3fff) as usize;
+            end_offset.get_or_insert(pos + 2);
+
+            jumps += 1;
+            if jumps > MAX_POINTER_JUMPS {
+                return Err(invalid_response());
+            }
+            pos = pointer;
+            continue;
+        }
+
+        let label = packet
+            .get(pos + 1..pos + 1 + len)
+            .ok_or_else(invalid_response)?;
+        labels.push(String::from_utf8_lossy(label).to_string());
+        pos += 1 + len;
+    }
+
+    Ok((
+        format!("{}.", labels.join(".")),
+        end_offset.unwrap_or(pos + 1),
+    ))
+}
+
+fn read_u16(packet: &[u8], offset: usize) -> Result<u16, AppError> {
+    packet
+        .get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(invalid_response)
+}
+
+fn read_u32(packet: &[u8], offset: usize) -> Result<u32, AppError> {
+    packet
+        .get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(invalid_response)
+}
+
+fn invalid_response() -> AppError {
+    AppError::ParseError("invalid DNS response".to_owned())
+}
+
+fn reverse_lookup_name(ipaddress: &IpAddr) -> String {
+    match ipaddress {
+        IpAddr::V4(ip) => {
+            let o = ip.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa", o[3], o[2], o[1], o[0])
+        }
+        IpAddr::V6(ip) => {
+            let nibbles: Vec<String> = ip
+                .octets()
+                .iter()
+                .rev()
+                .flat_map(|b| [b & 0x0f, b >> 4])
+                .map(|n| format!("{:x}", n))
+                .collect();
+            format!("{}.ip6.arpa", nibbles.join("."))
+        }
+    }
+}
+
+fn record_type_to_code(record_type: &str) -> Result<u16, AppError> {
+    match record_type.to_uppercase().as_str() {
+        "A" => Ok(1),
+        "NS" => Ok(2),
+        "CNAME" => Ok(5),
+        "SOA" => Ok(6),
+        "PTR" => Ok(12),
+        "MX" => Ok(15),
+        "TXT" => Ok(16),
+        "AAAA" => Ok(28),
+        "SRV" => Ok(33),
+        "ANY" => Ok(255),
+        other => Err(AppError::InvalidArgument(
+            "record_type".to_owned(),
+            Some(other.to_owned()),
+        )),
+    }
+}
+
+fn code_to_record_type(code: u16) -> String {
+    match code {
+        1 => "A".to_owned(),
+        2 => "NS".to_owned(),
+        5 => "CNAME".to_owned(),
+        6 => "SOA".to_owned(),
+        12 => "PTR".to_owned(),
+        15 => "MX".to_owned(),
+        16 => "TXT".to_owned(),
+        28 => "AAAA".to_owned(),
+        33 => "SRV".to_owned(),
+        other => format!("TYPE{}", other),
+    }
+}
+
+fn response_code_to_string(code: u8) -> String {
+    match code {
+        0 => "NOERROR".to_owned(),
+        1 => "FORMERR".to_owned(),
+        2 => "SERVFAIL".to_owned(),
+        3 => "NXDOMAIN".to_owned(),
+        4 => "NOTIMP".to_owned(),
+        5 => "REFUSED".to_owned(),
+        other => format!("RCODE{}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response_with_compressed_names() {
+        let mut packet = build_query("example.com", 15).unwrap();
+        // turn the query into a response with one MX answer
+        packet[2] = 0x81;
+        packet[3] = 0x80;
+        packet[7] = 1;
+        packet.extend_from_slice(&[0xc0, 0x0c, 0, 15, 0, 1, 0, 0, 0x0e, 0x10, 0, 9, 0, 10]);
+        packet.extend_from_slice(&[4, b'm', b'a', b'i', b'l', 0xc0, 0x0c]);
+
+        let (response_code, answers) = parse_response(&packet).unwrap();
+
+        assert_eq!(response_code, "NOERROR");
+        assert_eq!(
+            answers,
+            vec![DnsAnswer {
+                name: "example.com.".to_owned(),
+                record_type: "MX".to_owned(),
+                ttl: 3600,
+                value: "10 mail.example.com.".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_reverse_lookup_name() {
+        assert_eq!(
+            reverse_lookup_name(&"192.168.178.1".parse().unwrap()),
+            "1.178.168.192.in-addr.arpa"
+        );
+    }
+}
@@ -1,8 +1,13 @@
+use std::collections::HashMap;
 #[cfg(all(target_os = "linux"))]
 use std::path::Path;
-use std::time::Duration;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
 
 use http::StatusCode;
+use lazy_static::lazy_static;
+use regex::Regex;
+use sha2::{Digest, Sha256};
 
 use crate::datastore;
 use crate::models::error::AppError;
@@ -22,6 +27,34 @@ pub const DELETE: &str = "delete";
 #[cfg(all(target_os = "linux"))]
 const SOCKET_HTTP_POSTFIX: &str = "HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n";
 
+lazy_static! {
+    static ref TLS_CLIENTS: RwLock<HashMap<String, reqwest::Client>> = RwLock::new(HashMap::new());
+    static ref UNVERIFIED_FINGERPRINTS: RwLock<HashMap<String, String>> =
+        RwLock::new(HashMap::new());
+    static ref PEM_REGEX: Regex =
+        Regex::new(r"(?s)-----BEGIN ([A-Z0-9 ]+)-----(.*?)-----END ([A-Z0-9 ]+)-----")
+            .expect("Pattern is invalid");
+    /// the CA certificates of the system are only read once, since the bundle contains some hundred certificates
+    static ref SYSTEM_ROOT_CERTIFICATES: Vec<Vec<u8>> = load_system_root_certificates();
+}
+
+/// PEM encoded certificates and key for mutual TLS and servers with a certificate of a private CA
+/// and the data needed for pinning self-signed certificates
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct ClientTls {
+    /// client certificate - only used together with the key
+    pub certificate: Option<String>,
+    /// private key of the client certificate (PKCS#1, PKCS#8 or SEC1)
+    pub key: Option<String>,
+    /// one or more CA certificates that are trusted in addition to the public ones
+    pub ca_certificates: Option<String>,
+    /// identifies the endpoint (server and feature) for pinning certificates that are not signed by a trusted CA.
+    /// Without it the setting accept_self_signed_certificates decides if such certificates are accepted
+    pub pinning_id: Option<String>,
+    /// fingerprint of the certificate that was accepted on first use - see `certificate_fingerprint`
+    pub pinned_fingerprint: Option<String>,
+}
+
 /// Executes an http request on the given url using the given method.
 ///
 /// # Arguments
@@ -44,7 +77,61 @@ pub async fn execute_http_request(
     headers: Option<Vec<(String, String)>>,
     body: Option<String>,
 ) -> Result<String, AppError> {
-    let client = create_http_client()?;
+    execute_http_request_with_tls(url, method, headers, body, None).await
+}
+
+/// Same as `execute_http_request` but uses the given client certificate and CA certificates for the TLS connection
+pub async fn execute_http_request_with_tls(
+    url: &str,
+    method: &str,
+    headers: Option<Vec<(String, String)>>,
+    body: Option<String>,
+    tls: Option<&ClientTls>,
+) -> Result<String, AppError> {
+    let response = send_http_request(url, method, headers, body, tls).await?;
+
+    Ok(response.text().await.unwrap_or("".to_string()))
+}
+
+/// Same as `execute_http_request` but returns the response headers (name in lowercase, value) together with the body.
+/// Needed for logins that return the session in a header or cookie
+pub async fn execute_http_request_with_headers(
+    url: &str,
+    method: &str,
+    headers: Option<Vec<(String, String)>>,
+    body: Option<String>,
+    tls: Option<&ClientTls>,
+) -> Result<(String, Vec<(String, String)>), AppError> {
+    let response = send_http_request(url, method, headers, body, tls).await?;
+
+    let response_headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().to_owned(), value.to_owned()))
+        })
+        .collect();
+
+    Ok((
+        response.text().await.unwrap_or("".to_string()),
+        response_headers,
+    ))
+}
+
+async fn send_http_request(
+    url: &str,
+    method: &str,
+    headers: Option<Vec<(String, String)>>,
+    body: Option<String>,
+    tls: Option<&ClientTls>,
+) -> Result<reqwest::Response, AppError> {
+    let client = match tls {
+        Some(tls) => get_tls_http_client(tls)?,
+        None => create_http_client()?,
+    };
 
     let header_map: http::HeaderMap = headers_to_map(headers)?;
 
@@ -75,8 +162,8 @@ pub async fn execute_http_request(
 
     match response {
         Ok(res) => match res.status() {
-            StatusCode::ACCEPTED => Ok(res.text().await.unwrap_or("".to_string())),
-            StatusCode::OK => Ok(res.text().await.unwrap_or("".to_string())),
+            StatusCode::ACCEPTED => Ok(res),
+            StatusCode::OK => Ok(res),
             y => {
                 log::debug!("Returned StatusCode was not ACCEPTED or OK but {:?}", y);
                 Err(AppError::NokOKResponse(
@@ -107,6 +194,256 @@ fn create_http_client() -> Result<reqwest::Client, AppError> {
         .map_err(AppError::from)
 }
 
+/// Returns a client for the given certificates. The clients are cached, since building them (parsing the certificates) is expensive
+fn get_tls_http_client(tls: &ClientTls) -> Result<reqwest::Client, AppError> {
+    let accept_self_signed_certificates = datastore::get_config()?
+        .get_bool("accept_self_signed_certificates")
+        .unwrap_or(false);
+
+    let key = super::hash_as_string((tls, accept_self_signed_certificates));
+
+    if let Some(client) = TLS_CLIENTS
+        .read()
+        .map_err(|err| AppError::Unknown(format!("Could not get read lock. Error: {}", err)))?
+        .get(&key)
+    {
+        return Ok(client.clone());
+    }
+
+    // the TLS config is built directly with rustls, since the certificate verification needs to be customized for the pinning.
+    // rustls also supports the PKCS#1 keys that are generated for docker for example, which native-tls doesn't
+    let client = reqwest::Client::builder()
+        .use_preconfigured_tls(create_tls_config(tls, accept_self_signed_certificates)?)
+        .timeout(Duration::from_secs(1))
+        .build()?;
+
+    TLS_CLIENTS
+        .write()
+        .map_err(|err| AppError::Unknown(format!("Could not get write lock. Error: {}", err)))?
+        .insert(key, client.clone());
+
+    Ok(client)
+}
+
+fn create_tls_config(
+    tls: &ClientTls,
+    accept_self_signed_certificates: bool,
+) -> Result<rustls::ClientConfig, AppError> {
+    let mut root_store = rustls::RootCertStore::empty();
+    root_store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+    // the CAs installed on the host (ie. the CA of the company or homelab) are trusted as well.
+    // Certificates that cannot be parsed are skipped
+    root_store.add_parsable_certificates(&SYSTEM_ROOT_CERTIFICATES);
+
+    if let Some(ca_certificates) = &tls.ca_certificates {
+        for certificate in read_pem_certificates(ca_certificates)? {
+            root_store.add(&certificate).map_err(|err| {
+                AppError::InvalidArgument("ca_certificate".to_owned(), Some(format!("{}", err)))
+            })?;
+        }
+    }
+
+    let verifier = PinningVerifier {
+        verifier: rustls::client::WebPkiVerifier::new(root_store, None),
+        pinning_id: tls.pinning_id.clone(),
+        pinned_fingerprint: tls.pinned_fingerprint.clone(),
+        accept_self_signed_certificates,
+    };
+
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(verifier));
+
+    let config = match (&tls.certificate, &tls.key) {
+        (Some(certificate), Some(private_key)) => builder
+            .with_client_auth_cert(
+                read_pem_certificates(certificate)?,
+                read_pem_private_key(private_key)?,
+            )
+            .map_err(|err| {
+                AppError::InvalidArgument("client_certificate".to_owned(), Some(format!("{}", err)))
+            })?,
+        _ => builder.with_no_client_auth(),
+    };
+
+    Ok(config)
+}
+
+/// Reads the CA bundle of the system the way OpenSSL finds it (incl. SSL_CERT_FILE and SSL_CERT_DIR).
+/// The files of the certificate directories are only read if there is no bundle, since they usually contain the same certificates
+fn load_system_root_certificates() -> Vec<Vec<u8>> {
+    let probe = openssl_probe::probe();
+
+    let files: Vec<std::path::PathBuf> = match probe.cert_file {
+        Some(cert_file) => vec![cert_file],
+        None => probe
+            .cert_dir
+            .iter()
+            .filter_map(|dir| std::fs::read_dir(dir).ok())
+            .flat_map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+            })
+            .filter(|path| path.is_file())
+            .collect(),
+    };
+
+    let mut certificates = Vec::new();
+    for file in files {
+        match std::fs::read(&file) {
+            Ok(content) => match rustls_pemfile::certs(&mut content.as_slice()) {
+                Ok(mut certs) => certificates.append(&mut certs),
+                Err(err) => log::debug!("Could not parse certificates of {:?}: {}", file, err),
+            },
+            Err(err) => log::debug!("Could not read certificates of {:?}: {}", file, err),
+        }
+    }
+
+    log::debug!(
+        "Loaded {} CA certificates of the system",
+        certificates.len()
+    );
+    certificates
+}
+
+fn read_pem_certificates(pem: &str) -> Result<Vec<rustls::Certificate>, AppError> {
+    Ok(rustls_pemfile::certs(&mut normalize_pem(pem).as_bytes())?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect())
+}
+
+fn read_pem_private_key(pem: &str) -> Result<rustls::PrivateKey, AppError> {
+    rustls_pemfile::read_all(&mut normalize_pem(pem).as_bytes())?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(rustls::PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or(AppError::InvalidArgument("client_key".to_owned(), None))
+}
+
+/// Verifies the certificate of the server against the known CAs. If that fails and the connection belongs to a feature,
+/// the certificate is trusted on first use: the fingerprint of a certificate that is seen for the first time is accepted and
+/// recorded (see `take_unverified_certificate_fingerprint`), afterwards only a certificate with the pinned fingerprint is accepted.
+struct PinningVerifier {
+    verifier: rustls::client::WebPkiVerifier,
+    pinning_id: Option<String>,
+    pinned_fingerprint: Option<String>,
+    accept_self_signed_certificates: bool,
+}
+
+impl rustls::client::ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::Certificate,
+        intermediates: &[rustls::Certificate],
+        server_name: &rustls::ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        let result = self.verifier.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            scts,
+            ocsp_response,
+            now,
+        );
+
+        let (Err(err), Some(pinning_id)) = (&result, &self.pinning_id) else {
+            return match result {
+                Err(_) if self.accept_self_signed_certificates => {
+                    Ok(rustls::client::ServerCertVerified::assertion())
+                }
+                result => result,
+            };
+        };
+
+        let fingerprint = certificate_fingerprint(&end_entity.0);
+        log::debug!(
+            "certificate of {} could not be verified ({}). Fingerprint is {}",
+            pinning_id,
+            err,
+            fingerprint
+        );
+
+        if let Ok(mut fingerprints) = UNVERIFIED_FINGERPRINTS.write() {
+            fingerprints.insert(pinning_id.clone(), fingerprint.clone());
+        }
+
+        match &self.pinned_fingerprint {
+            None => Ok(rustls::client::ServerCertVerified::assertion()),
+            Some(pinned) if is_same_fingerprint(pinned, &fingerprint) => {
+                Ok(rustls::client::ServerCertVerified::assertion())
+            }
+            Some(_) => Err(rustls::Error::General(format!(
+                "certificate fingerprint {} does not match the pinned fingerprint",
+                fingerprint
+            ))),
+        }
+    }
+}
+
+/// Returns and removes the fingerprint of the last certificate that was seen for the given pinning id and that could not be verified by a CA
+pub fn take_unverified_certificate_fingerprint(pinning_id: &str) -> Option<String> {
+    UNVERIFIED_FINGERPRINTS
+        .write()
+        .ok()
+        .and_then(|mut fingerprints| fingerprints.remove(pinning_id))
+}
+
+/// SHA-256 fingerprint of a DER encoded certificate in the format that is also shown by browsers and openssl (ie. AB:CD:...)
+fn certificate_fingerprint(der: &[u8]) -> String {
+    format_fingerprint(&Sha256::digest(der))
+}
+
+/// the hex bytes of a hash separated by colons (ie. AB:CD:...)
+pub fn format_fingerprint(hash: &[u8]) -> String {
+    hash.iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<String>>()
+        .join(":")
+}
+
+pub fn is_same_fingerprint(fingerprint: &str, other: &str) -> bool {
+    let normalize = |fingerprint: &str| fingerprint.replace(':', "").trim().to_lowercase();
+
+    normalize(fingerprint) == normalize(other)
+}
+
+/// Restores the line breaks of PEM data - they get lost when the PEM data is entered in a single line input field
+fn normalize_pem(pem: &str) -> String {
+    PEM_REGEX
+        .captures_iter(pem)
+        .map(|captures| {
+            let content: String = captures[2].split_whitespace().collect();
+            let lines: Vec<&str> = content
+                .as_bytes()
+                .chunks(64)
+                .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+                .collect();
+
+            format!(
+                "-----BEGIN {}-----\n{}\n-----END {}-----\n",
+                &captures[1],
+                lines.join("\n"),
+                &captures[3]
+            )
+        })
+        .collect()
+}
+
 pub async fn execute_timeseries_db_query(query: &[(&str, &str)]) -> Result<String, AppError> {
     let config = datastore::get_timeseriesdb_config()?;
 
@@ -217,3 +554,40 @@ fn read_from_stream(unix_stream: &mut UnixStream) -> Result<String, AppError> {
     );
     Ok(response)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_pem() {
+        let certificate = "-----BEGIN CERTIFICATE----- MIIBszCCAVmgAwIBAgIUO0c3 \
+            Q2FfXy1tZXJnZWQ= -----END CERTIFICATE-----";
+
+        assert_eq!(
+            normalize_pem(certificate),
+            "-----BEGIN CERTIFICATE-----\nMIIBszCCAVmgAwIBAgIUO0c3Q2FfXy1tZXJnZWQ=\n-----END CERTIFICATE-----\n"
+        );
+
+        let bundle = format!("{}\n{}", certificate, certificate);
+        assert_eq!(
+            normalize_pem(&bundle).matches("BEGIN CERTIFICATE").count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_certificate_fingerprint() {
+        let fingerprint = certificate_fingerprint(b"certificate");
+
+        assert_eq!(fingerprint.len(), 32 * 3 - 1);
+        assert!(is_same_fingerprint(
+            &fingerprint,
+            &fingerprint.replace(':', "").to_lowercase()
+        ));
+        assert!(!is_same_fingerprint(
+            &fingerprint,
+            &certificate_fingerprint(b"other certificate")
+        ));
+    }
+}
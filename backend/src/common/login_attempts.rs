@@ -0,0 +1,139 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Duration, Utc};
+use lazy_static::lazy_static;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::models::error::AppError;
+
+/// failed logins that are possible before the user is locked
+const FREE_FAILED_ATTEMPTS: u32 = 5;
+const FIRST_LOCK_SECONDS: i64 = 30;
+const MAX_LOCK_SECONDS: i64 = 15 * 60;
+
+lazy_static! {
+    static ref LOGIN_ATTEMPTS: Mutex<HashMap<String, Arc<Mutex<LoginAttempts>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// The failed logins of a user - after some failures the user is locked for a time that doubles with every further failure,
+/// so that passwords, TOTP codes and recovery codes cannot be brute-forced
+#[derive(Debug, Default)]
+pub struct LoginAttempts {
+    failed: u32,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginAttempts {
+    pub fn check_not_locked(&self, now: DateTime<Utc>) -> Result<(), AppError> {
+        match self.locked_until {
+            Some(locked_until) if locked_until > now => Err(AppError::TooManyLoginAttempts(
+                (locked_until - now).num_seconds().max(1),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn record_failure(&mut self, now: DateTime<Utc>) {
+        self.failed += 1;
+
+        if self.failed > FREE_FAILED_ATTEMPTS {
+            let exponent = (self.failed - FREE_FAILED_ATTEMPTS - 1).min(16);
+            let seconds = (FIRST_LOCK_SECONDS << exponent).min(MAX_LOCK_SECONDS);
+            self.locked_until = Some(now + Duration::seconds(seconds));
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.failed = 0;
+        self.locked_until = None;
+    }
+}
+
+/// Returns the login attempts of a user. The guard is held until the check of the credentials is persisted,
+/// so that parallel requests of the same user are serialized and a TOTP code cannot be used twice
+pub async fn lock_login_attempts(user_id: &str) -> OwnedMutexGuard<LoginAttempts> {
+    let attempts = {
+        let mut all_attempts = LOGIN_ATTEMPTS.lock().await;
+
+        // users without failures that are not in a login are not needed anymore
+        all_attempts.retain(|_, attempts| {
+            Arc::strong_count(attempts) > 1
+                || attempts
+                    .try_lock()
+                    .map_or(true, |attempts| attempts.failed > 0)
+        });
+
+        all_attempts.entry(user_id.to_owned()).or_default().clone()
+    };
+
+    attempts.lock_owned().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_after_failed_attempts() {
+        let now = Utc::now();
+        let mut attempts = LoginAttempts::default();
+
+        for _ in 0..FREE_FAILED_ATTEMPTS {
+            attempts.record_failure(now);
+            assert!(attempts.check_not_locked(now).is_ok());
+        }
+
+        attempts.record_failure(now);
+        assert!(matches!(
+            attempts.check_not_locked(now),
+            Err(AppError::TooManyLoginAttempts(30))
+        ));
+        assert!(attempts
+            .check_not_locked(now + Duration::seconds(FIRST_LOCK_SECONDS))
+            .is_ok());
+
+        // the lock doubles up to the maximum
+        attempts.record_failure(now);
+        assert!(matches!(
+            attempts.check_not_locked(now),
+            Err(AppError::TooManyLoginAttempts(60))
+        ));
+        for _ in 0..20 {
+            attempts.record_failure(now);
+        }
+        assert!(matches!(
+            attempts.check_not_locked(now),
+            Err(AppError::TooManyLoginAttempts(MAX_LOCK_SECONDS))
+        ));
+
+        attempts.reset();
+        assert!(attempts.check_not_locked(now).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_logins_of_a_user_are_serialized() {
+        let attempts = lock_login_attempts("serialized").await;
+
+        let parallel = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            lock_login_attempts("serialized"),
+        )
+        .await;
+        assert!(parallel.is_err());
+        assert!(tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            lock_login_attempts("other")
+        )
+        .await
+        .is_ok());
+
+        drop(attempts);
+        assert!(tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            lock_login_attempts("serialized")
+        )
+        .await
+        .is_ok());
+    }
+}
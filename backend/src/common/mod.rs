@@ -5,12 +5,20 @@ use std::hash::{Hash, Hasher};
 
 pub mod clientkey;
 mod crypt;
+mod dns_functions;
 mod http_functions;
 mod kafka_functions;
+mod login_attempts;
 mod mail;
+mod modbus_functions;
+mod mqtt_functions;
 mod onetimekey;
 mod script_languages;
+mod snmp_functions;
+mod ssh_functions;
+mod tcp_functions;
 mod token;
+mod totp;
 
 pub use http_functions::DELETE;
 pub use http_functions::GET;
@@ -22,26 +30,69 @@ pub use crypt::default_decrypt;
 pub use crypt::default_encrypt;
 pub use crypt::get_random_key32;
 pub use crypt::hash_password;
+pub use crypt::hash_token;
+pub use crypt::is_same_token;
 pub use crypt::make_aes_secrect;
 pub use crypt::verify_password;
 pub use http_functions::execute_http_request;
+pub use http_functions::execute_http_request_with_headers;
+pub use http_functions::execute_http_request_with_tls;
 #[cfg(all(target_os = "linux"))]
 pub use http_functions::execute_socket_request;
 pub use http_functions::execute_timeseries_db_query;
+pub use http_functions::format_fingerprint;
+pub use http_functions::is_same_fingerprint;
+pub use http_functions::take_unverified_certificate_fingerprint;
+pub use http_functions::ClientTls;
 
 pub use kafka_functions::execute_kafka_request;
 
+pub use ssh_functions::execute_ssh_command;
+pub use ssh_functions::SshCredentials;
+pub use ssh_functions::SshResponse;
+
+pub use mqtt_functions::execute_mqtt_request;
+pub use mqtt_functions::get_last_mqtt_message;
+pub use mqtt_functions::is_mqtt_configured;
+pub use mqtt_functions::run_mqtt_event_loop;
+pub use mqtt_functions::subscribe_mqtt_topic;
+pub use mqtt_functions::subscribe_to_mqtt_messages;
+pub use mqtt_functions::MqttMessage;
+
+pub use snmp_functions::execute_snmp_request;
+pub use snmp_functions::SnmpCredentials;
+pub use snmp_functions::SnmpOperation;
+
+pub use dns_functions::execute_dns_query;
+
+pub use modbus_functions::execute_modbus_request;
+pub use modbus_functions::ModbusByteOrder;
+pub use modbus_functions::ModbusRegister;
+
+pub use tcp_functions::check_tcp_port;
+pub use tcp_functions::check_udp_port;
+
+pub use script_languages::script_execute;
 pub use script_languages::script_match;
 pub use script_languages::script_process;
+pub use script_languages::ScriptContext;
 
 pub use mail::is_smtp_config_valid;
 pub use mail::send_email;
 
+pub use login_attempts::lock_login_attempts;
+pub use login_attempts::LoginAttempts;
+
 pub use onetimekey::invalidate_expired_one_time_keys;
 pub use onetimekey::OneTimeKey;
 
 pub use token::Token;
 
+pub use totp::generate_recovery_codes;
+pub use totp::generate_totp_secret;
+pub use totp::make_otpauth_uri;
+pub use totp::verify_totp_code;
+
 pub use clientkey::ClientKey;
 
 use crate::models::error::AppError;
@@ -58,6 +109,8 @@ pub const TIMESTAMP: &str = "timestamp";
 pub const IPADDRESS: &str = "ipaddress";
 
 pub static DB_FILENAME: &str = "./external_files/server-manager.db";
+/// only used if the embedded time series store is used instead of QuestDB
+pub static TIMESERIES_DB_FILENAME: &str = "./external_files/timeseries.db";
 
 pub fn generate_long_random_string() -> String {
     let key: String = {
@@ -77,6 +130,19 @@ pub fn generate_short_random_string() -> String {
     key
 }
 
+/// encodes everything except the unreserved characters of RFC 3986, so that the value can be used in a query parameter
+pub fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
 pub fn encode_base64(str: &str) -> String {
     general_purpose::STANDARD_NO_PAD.encode(str)
 }
@@ -109,9 +175,28 @@ pub fn hash_as_string<T: Hash>(object: T) -> String {
     format!("{}", s.finish())
 }
 
+/// formats a duration as ie. 1h 2m 5s
+pub fn format_duration(duration: chrono::Duration) -> String {
+    let seconds = duration.num_seconds();
+
+    match (seconds / 3600, seconds % 3600 / 60, seconds % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, s) => format!("{}h {}m {}s", h, m, s),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(chrono::Duration::seconds(3725)), "1h 2m 5s");
+        assert_eq!(format_duration(chrono::Duration::seconds(125)), "2m 5s");
+        assert_eq!(format_duration(chrono::Duration::seconds(42)), "42s");
+    }
+
     #[test]
     fn test_encode_base64() {
         assert_eq!(encode_base64("USERNAME"), "VVNFUk5BTUU");
@@ -0,0 +1,449 @@
+use std::{collections::BTreeMap, future::Future, net::SocketAddr, str::FromStr, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tokio_modbus::{
+    client::{tcp, Context, Reader},
+    prelude::Client,
+    Slave,
+};
+
+use crate::models::error::AppError;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModbusFunction {
+    Coil,
+    DiscreteInput,
+    HoldingRegister,
+    InputRegister,
+}
+
+impl FromStr for ModbusFunction {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "coil" | "coils" => Ok(ModbusFunction::Coil),
+            "discrete" | "discrete_input" | "discrete_inputs" => Ok(ModbusFunction::DiscreteInput),
+            "holding" | "holding_register" | "holding_registers" => {
+                Ok(ModbusFunction::HoldingRegister)
+            }
+            "input" | "input_register" | "input_registers" => Ok(ModbusFunction::InputRegister),
+            other => Err(AppError::InvalidArgument(
+                "function".to_owned(),
+                Some(other.to_owned()),
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModbusDataType {
+    Bool,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+}
+
+impl ModbusDataType {
+    fn register_count(&self) -> u16 {
+        match self {
+            ModbusDataType::Bool | ModbusDataType::U16 | ModbusDataType::I16 => 1,
+            ModbusDataType::U32 | ModbusDataType::I32 | ModbusDataType::F32 => 2,
+        }
+    }
+}
+
+impl FromStr for ModbusDataType {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bool" => Ok(ModbusDataType::Bool),
+            "u16" => Ok(ModbusDataType::U16),
+            "i16" => Ok(ModbusDataType::I16),
+            "u32" => Ok(ModbusDataType::U32),
+            "i32" => Ok(ModbusDataType::I32),
+            "f32" => Ok(ModbusDataType::F32),
+            other => Err(AppError::InvalidArgument(
+                "data_type".to_owned(),
+                Some(other.to_owned()),
+            )),
+        }
+    }
+}
+
+/// Order of the bytes of a value as sent by the device, where A is the most significant byte.
+/// Devices differ a lot here, especially for 32 bit values that span two registers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModbusByteOrder {
+    /// ABCD - big endian, the Modbus default
+    BigEndian,
+    /// DCBA - little endian
+    LittleEndian,
+    /// BADC - big endian with swapped bytes in each register
+    BigEndianByteSwap,
+    /// CDAB - little endian word order with big endian registers (ie. many SMA, Fronius and Eastron devices)
+    LittleEndianByteSwap,
+}
+
+impl FromStr for ModbusByteOrder {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "ABCD" | "BIG" | "BIG_ENDIAN" => Ok(ModbusByteOrder::BigEndian),
+            "DCBA" | "LITTLE" | "LITTLE_ENDIAN" => Ok(ModbusByteOrder::LittleEndian),
+            "BADC" => Ok(ModbusByteOrder::BigEndianByteSwap),
+            "CDAB" => Ok(ModbusByteOrder::LittleEndianByteSwap),
+            other => Err(AppError::InvalidArgument(
+                "byte_order".to_owned(),
+                Some(other.to_owned()),
+            )),
+        }
+    }
+}
+
+/// Definition of a value that should be read from the device.
+///
+/// Can be parsed from a string with the format `<name>:<function>:<address>:<data_type>[:<scale>]`,
+/// ie. `power:holding:30775:i32:0.1`. The address is the zero based register/coil address as sent on the wire.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModbusRegister {
+    pub name: String,
+    pub function: ModbusFunction,
+    pub address: u16,
+    pub data_type: ModbusDataType,
+    pub scale: f64,
+}
+
+impl FromStr for ModbusRegister {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').map(|part| part.trim()).collect();
+
+        if parts.len() < 4 || parts.len() > 5 || parts[0].is_empty() {
+            return Err(AppError::InvalidArgument(
+                "register".to_owned(),
+                Some(s.to_owned()),
+            ));
+        }
+
+        let scale = match parts.get(4) {
+            Some(scale) => scale
+                .parse()
+                .map_err(|_| AppError::InvalidArgument("scale".to_owned(), Some(s.to_owned())))?,
+            None => 1.0,
+        };
+
+        Ok(ModbusRegister {
+            name: parts[0].to_owned(),
+            function: parts[1].parse()?,
+            address: parts[2].parse()?,
+            data_type: parts[3].parse()?,
+            scale,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModbusResponse {
+    pub unit_id: u8,
+    pub values: BTreeMap<String, f64>,
+    pub registers: Vec<ModbusValue>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModbusValue {
+    pub name: String,
+    pub address: u16,
+    pub raw: Vec<u16>,
+    pub value: f64,
+}
+
+/// Reads the given registers/coils from a Modbus TCP device and decodes them
+///
+/// # Arguments
+///
+/// * `address` IP and port of the device or Modbus TCP gateway
+/// * `unit_id` the unit id (slave id) of the device. Devices that are directly connected usually ignore it, gateways need it to address the device
+/// * `registers` the values to read
+/// * `byte_order` the byte order used by the device for all values
+/// * `timeout` timeout for the connect and each single read
+///
+/// The decoded values are returned as a map by name - so they can be extracted with a JSONPath like `$.values.power`
+pub async fn execute_modbus_request(
+    address: SocketAddr,
+    unit_id: u8,
+    registers: &[ModbusRegister],
+    byte_order: &ModbusByteOrder,
+    timeout: Duration,
+) -> Result<ModbusResponse, AppError> {
+    let mut context = with_timeout(
+        timeout,
+        "connect",
+        tcp::connect_slave(address, Slave(unit_id)),
+    )
+    .await?;
+
+    let mut response = ModbusResponse {
+        unit_id,
+        values: BTreeMap::new(),
+        registers: Vec::new(),
+    };
+
+    let result = read_registers(&mut context, registers, byte_order, timeout, &mut response).await;
+
+    if let Err(err) = context.disconnect().await {
+        log::debug!(
+            "Could not disconnect from Modbus device {}: {}",
+            address,
+            err
+        );
+    }
+
+    result.map(|_| response)
+}
+
+async fn read_registers(
+    context: &mut Context,
+    registers: &[ModbusRegister],
+    byte_order: &ModbusByteOrder,
+    timeout: Duration,
+    response: &mut ModbusResponse,
+) -> Result<(), AppError> {
+    for register in registers {
+        let count = register.data_type.register_count();
+
+        let raw: Vec<u16> = match register.function {
+            ModbusFunction::Coil => to_words(
+                with_timeout(timeout, "read", context.read_coils(register.address, count))
+                    .await??,
+            ),
+            ModbusFunction::DiscreteInput => to_words(
+                with_timeout(
+                    timeout,
+                    "read",
+                    context.read_discrete_inputs(register.address, count),
+                )
+                .await??,
+            ),
+            ModbusFunction::HoldingRegister => {
+                with_timeout(
+                    timeout,
+                    "read",
+                    context.read_holding_registers(register.address, count),
+                )
+                .await??
+            }
+            ModbusFunction::InputRegister => {
+                with_timeout(
+                    timeout,
+                    "read",
+                    context.read_input_registers(register.address, count),
+                )
+                .await??
+            }
+        };
+
+        let value = decode_value(&raw, &register.data_type, byte_order)? * register.scale;
+
+        response.values.insert(register.name.clone(), value);
+        response.registers.push(ModbusValue {
+            name: register.name.clone(),
+            address: register.address,
+            raw,
+            value,
+        });
+    }
+    Ok(())
+}
+
+async fn with_timeout<T, E>(
+    timeout: Duration,
+    operation: &str,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, AppError>
+where
+    AppError: From<E>,
+{
+    match tokio::time::timeout(timeout, future).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(AppError::CommunicationError(format!(
+            "Modbus {} timed out",
+            operation
+        ))),
+    }
+}
+
+fn to_words(coils: Vec<bool>) -> Vec<u16> {
+    coils.into_iter().map(u16::from).collect()
+}
+
+fn decode_value(
+    raw: &[u16],
+    data_type: &ModbusDataType,
+    byte_order: &ModbusByteOrder,
+) -> Result<f64, AppError> {
+    if raw.len() < data_type.register_count() as usize {
+        return Err(AppError::ParseError(format!(
+            "expected {} registers but got {}",
+            data_type.register_count(),
+            raw.len()
+        )));
+    }
+
+    if *data_type == ModbusDataType::Bool {
+        return Ok(if raw[0] != 0 { 1.0 } else { 0.0 });
+    }
+
+    let bytes = order_bytes(&raw[..data_type.register_count() as usize], byte_order);
+
+    let value = match data_type {
+        ModbusDataType::U16 => u16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+        ModbusDataType::I16 => i16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+        ModbusDataType::U32 => u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        ModbusDataType::I32 => i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        ModbusDataType::F32 => f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        ModbusDataType::Bool => unreachable!("handled above"),
+    };
+    Ok(value)
+}
+
+/// brings the bytes of the given registers into big endian order (ABCD)
+fn order_bytes(raw: &[u16], byte_order: &ModbusByteOrder) -> Vec<u8> {
+    let swap_words = matches!(
+        byte_order,
+        ModbusByteOrder::LittleEndian | ModbusByteOrder::LittleEndianByteSwap
+    );
+    let swap_bytes = matches!(
+        byte_order,
+        ModbusByteOrder::LittleEndian | ModbusByteOrder::BigEndianByteSwap
+    );
+
+    let mut words = raw.to_vec();
+    if swap_words {
+        words.reverse();
+    }
+
+    words
+        .iter()
+        .flat_map(|word| {
+            let bytes = word.to_be_bytes();
+            if swap_bytes {
+                [bytes[1], bytes[0]]
+            } else {
+                bytes
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, net::Ipv4Addr};
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// minimal Modbus TCP server that answers register and coil reads from the given table
+    async fn start_modbus_server(table: HashMap<u16, u16>) -> SocketAddr {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 12];
+
+            while socket.read_exact(&mut request).await.is_ok() {
+                let function = request[7];
+                let start = u16::from_be_bytes([request[8], request[9]]);
+                let count = u16::from_be_bytes([request[10], request[11]]);
+                let values: Vec<u16> = (start..start + count)
+                    .map(|address| *table.get(&address).unwrap_or(&0))
+                    .collect();
+
+                let data: Vec<u8> = match function {
+                    1 | 2 => vec![values
+                        .iter()
+                        .enumerate()
+                        .fold(0u8, |bits, (i, v)| bits | (((*v != 0) as u8) << i))],
+                    _ => values.iter().flat_map(|v| v.to_be_bytes()).collect(),
+                };
+
+                let mut response = Vec::new();
+                response.extend_from_slice(&request[0..4]); // transaction and protocol id
+                response.extend_from_slice(&(data.len() as u16 + 3).to_be_bytes());
+                response.extend_from_slice(&[request[6], function, data.len() as u8]);
+                response.extend_from_slice(&data);
+                socket.write_all(&response).await.unwrap();
+            }
+        });
+
+        address
+    }
+
+    #[tokio::test]
+    async fn test_execute_modbus_request() {
+        let power = 1234.5f32.to_be_bytes();
+        let table = HashMap::from([
+            (10, u16::from_be_bytes([power[2], power[3]])),
+            (11, u16::from_be_bytes([power[0], power[1]])),
+            (20, 2301),
+            (30, 1),
+        ]);
+        let address = start_modbus_server(table).await;
+
+        let registers: Vec<ModbusRegister> = vec![
+            "power:holding:10:f32".parse().unwrap(),
+            "voltage:input:20:u16:0.1".parse().unwrap(),
+            "relay:coil:30:bool".parse().unwrap(),
+        ];
+
+        let response = execute_modbus_request(
+            address,
+            1,
+            &registers,
+            &ModbusByteOrder::LittleEndianByteSwap,
+            Duration::from_secs(2),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.values.get("power"), Some(&1234.5));
+        assert!((response.values["voltage"] - 230.1).abs() < 0.0001);
+        assert_eq!(response.values.get("relay"), Some(&1.0));
+    }
+
+    #[test]
+    fn test_decode_value_byte_orders() {
+        let raw = [0x0102, 0x0304];
+
+        assert_eq!(
+            order_bytes(&raw, &ModbusByteOrder::BigEndian),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            order_bytes(&raw, &ModbusByteOrder::LittleEndian),
+            vec![4, 3, 2, 1]
+        );
+        assert_eq!(
+            order_bytes(&raw, &ModbusByteOrder::BigEndianByteSwap),
+            vec![2, 1, 4, 3]
+        );
+        assert_eq!(
+            order_bytes(&raw, &ModbusByteOrder::LittleEndianByteSwap),
+            vec![3, 4, 1, 2]
+        );
+        assert_eq!(
+            decode_value(&[0xfffe], &ModbusDataType::I16, &ModbusByteOrder::BigEndian).unwrap(),
+            -2.0
+        );
+    }
+}
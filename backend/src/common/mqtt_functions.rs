@@ -0,0 +1,293 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+    time::Duration,
+};
+
+use lazy_static::lazy_static;
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, Publish, QoS};
+use tokio::{sync::broadcast, time::sleep};
+
+use crate::{datastore, models::error::AppError};
+
+const DEFAULT_PORT: u16 = 1883;
+const DEFAULT_CLIENT_ID: &str = "server-manager";
+
+lazy_static! {
+    static ref CLIENT: RwLock<Option<AsyncClient>> = RwLock::new(None);
+    static ref SUBSCRIBED_TOPICS: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
+    static ref LAST_MESSAGES: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
+    static ref MESSAGES: broadcast::Sender<MqttMessage> = broadcast::channel(1000).0;
+}
+
+#[derive(Clone, Debug)]
+pub struct MqttMessage {
+    pub topic: String,
+    pub payload: String,
+}
+
+/// the payload is converted to a string - invalid UTF-8 is replaced, since the scripts of the plugins expect text
+impl From<Publish> for MqttMessage {
+    fn from(publish: Publish) -> Self {
+        MqttMessage {
+            payload: String::from_utf8_lossy(&publish.payload).to_string(),
+            topic: publish.topic,
+        }
+    }
+}
+
+/// Returns true if a MQTT broker is configured in the .env file
+pub fn is_mqtt_configured() -> bool {
+    match datastore::get_config() {
+        Ok(config) => config
+            .get_string("mqtt_broker_host")
+            .map(|host| !host.trim().is_empty())
+            .unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+/// Connects to the configured MQTT broker and handles all incoming messages until the process ends.
+/// The connection is automatically re-established on errors and all topics that were subscribed before are subscribed again.
+///
+/// Note: the function never returns if a broker is configured, so it needs to be started in a separate thread/task
+pub async fn run_mqtt_event_loop() -> Result<(), AppError> {
+    let (client, mut eventloop) = AsyncClient::new(get_mqtt_options()?, 100);
+    set_client(client.clone())?;
+
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                log::info!("Connected to MQTT broker");
+
+                for topic in get_subscribed_topics()? {
+                    client.subscribe(topic, QoS::AtMostOnce).await?;
+                }
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                let message = MqttMessage::from(publish);
+                log::trace!(
+                    "received message from mqtt topic {} msg: {}",
+                    message.topic,
+                    message.payload
+                );
+                store_last_message(&message)?;
+
+                // an error only means that nobody is listening right now
+                MESSAGES.send(message).ok();
+            }
+            Ok(_) => {}
+            Err(err) => {
+                log::error!("Error in MQTT connection: {}", err);
+                sleep(Duration::from_secs(5)).await;
+            }
+        }
+    }
+}
+
+/// Subscribes to the given topic (filter). Messages on the topic are afterwards cached and broadcasted to all receivers of `subscribe_to_mqtt_messages`.
+pub async fn subscribe_mqtt_topic(topic: &str) -> Result<(), AppError> {
+    let is_new = SUBSCRIBED_TOPICS
+        .write()
+        .map_err(|err| AppError::Unknown(format!("Could not get write lock. Error: {}", err)))?
+        .insert(topic.to_owned());
+
+    if is_new {
+        get_client()?.subscribe(topic, QoS::AtMostOnce).await?;
+    }
+    Ok(())
+}
+
+pub fn subscribe_to_mqtt_messages() -> broadcast::Receiver<MqttMessage> {
+    MESSAGES.subscribe()
+}
+
+/// Returns the last received message of a topic matching the given topic (filter) - ie. a retained message
+pub fn get_last_mqtt_message(topic: &str) -> Result<Option<String>, AppError> {
+    let messages = LAST_MESSAGES
+        .read()
+        .map_err(|err| AppError::Unknown(format!("Could not get read lock. Error: {}", err)))?;
+
+    Ok(find_last_message(&messages, topic))
+}
+
+/// an exact match of the topic is preferred, otherwise the topic is used as filter (ie. with + and # wildcards)
+fn find_last_message(messages: &HashMap<String, String>, topic: &str) -> Option<String> {
+    if let Some(message) = messages.get(topic) {
+        return Some(message.clone());
+    }
+
+    messages
+        .iter()
+        .find(|(received_topic, _)| rumqttc::matches(received_topic, topic))
+        .map(|(_, message)| message.clone())
+}
+
+/// Publishes a message to the given topic and, if a response topic is given, waits for the first message on that topic
+///
+/// # Arguments
+///
+/// * `topic` the topic to publish to
+/// * `payload` the message to publish
+/// * `response_topic` optional topic (filter) on which the device responds - ie. stat/<device>/RESULT for Tasmota
+/// * `timeout` how long to wait for a response
+///
+/// Returns the response message or an empty string if no response topic was given or no response was received in time
+pub async fn execute_mqtt_request(
+    topic: &str,
+    payload: &str,
+    response_topic: Option<&str>,
+    timeout: Duration,
+) -> Result<String, AppError> {
+    let client = get_client()?;
+
+    let Some(response_topic) = response_topic else {
+        client
+            .publish(topic, QoS::AtLeastOnce, false, payload.as_bytes())
+            .await?;
+        return Ok(String::new());
+    };
+
+    // subscribe before publishing, otherwise we might miss the response
+    let mut receiver = subscribe_to_mqtt_messages();
+    subscribe_mqtt_topic(response_topic).await?;
+
+    client
+        .publish(topic, QoS::AtLeastOnce, false, payload.as_bytes())
+        .await?;
+
+    let wait_for_response = async {
+        loop {
+            match receiver.recv().await {
+                Ok(message) if rumqttc::matches(&message.topic, response_topic) => {
+                    return Some(message.payload)
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!(
+                        "Skipped {} MQTT messages while waiting for response",
+                        skipped
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    };
+
+    match tokio::time::timeout(timeout, wait_for_response).await {
+        Ok(response) => Ok(response.unwrap_or_default()),
+        Err(_) => {
+            log::warn!(
+                "No response on MQTT topic {} within timeout",
+                response_topic
+            );
+            Ok(String::new())
+        }
+    }
+}
+
+fn get_mqtt_options() -> Result<MqttOptions, AppError> {
+    let config = datastore::get_config()?;
+
+    let host = config.get_string("mqtt_broker_host")?;
+    let port = config
+        .get_int("mqtt_broker_port")
+        .map(|port| port as u16)
+        .unwrap_or(DEFAULT_PORT);
+    let client_id = config
+        .get_string("mqtt_client_id")
+        .unwrap_or(DEFAULT_CLIENT_ID.to_owned());
+
+    let mut options = MqttOptions::new(client_id, host, port);
+    options.set_keep_alive(Duration::from_secs(30));
+
+    if let Ok(username) = config.get_string("mqtt_username") {
+        if !username.is_empty() {
+            options.set_credentials(
+                username,
+                config.get_string("mqtt_password").unwrap_or_default(),
+            );
+        }
+    }
+    Ok(options)
+}
+
+fn set_client(client: AsyncClient) -> Result<(), AppError> {
+    *CLIENT
+        .write()
+        .map_err(|err| AppError::Unknown(format!("Could not get write lock. Error: {}", err)))? =
+        Some(client);
+    Ok(())
+}
+
+fn get_client() -> Result<AsyncClient, AppError> {
+    CLIENT
+        .read()
+        .map_err(|err| AppError::Unknown(format!("Could not get read lock. Error: {}", err)))?
+        .clone()
+        .ok_or(AppError::CommunicationError(
+            "No connection to a MQTT broker. Please check the MQTT config in the .env file"
+                .to_owned(),
+        ))
+}
+
+fn get_subscribed_topics() -> Result<Vec<String>, AppError> {
+    Ok(SUBSCRIBED_TOPICS
+        .read()
+        .map_err(|err| AppError::Unknown(format!("Could not get read lock. Error: {}", err)))?
+        .iter()
+        .cloned()
+        .collect())
+}
+
+fn store_last_message(message: &MqttMessage) -> Result<(), AppError> {
+    LAST_MESSAGES
+        .write()
+        .map_err(|err| AppError::Unknown(format!("Could not get write lock. Error: {}", err)))?
+        .insert(message.topic.clone(), message.payload.clone());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_last_message() {
+        let messages = HashMap::from([
+            (
+                "tele/tasmota_1/SENSOR".to_owned(),
+                "{\"Power\": 12}".to_owned(),
+            ),
+            ("stat/tasmota_1/POWER".to_owned(), "ON".to_owned()),
+        ]);
+
+        assert_eq!(
+            find_last_message(&messages, "stat/tasmota_1/POWER"),
+            Some("ON".to_owned())
+        );
+        assert_eq!(
+            find_last_message(&messages, "tele/+/SENSOR"),
+            Some("{\"Power\": 12}".to_owned())
+        );
+        assert_eq!(
+            find_last_message(&messages, "stat/#"),
+            Some("ON".to_owned())
+        );
+        assert_eq!(find_last_message(&messages, "tele/tasmota_2/SENSOR"), None);
+        // a filter only matches whole levels
+        assert_eq!(find_last_message(&messages, "stat/tasmota"), None);
+    }
+
+    #[test]
+    fn test_message_from_publish() {
+        let message = MqttMessage::from(Publish::new(
+            "stat/tasmota_1/RESULT",
+            QoS::AtMostOnce,
+            vec![b'O', b'N', 0xff],
+        ));
+
+        assert_eq!(message.topic, "stat/tasmota_1/RESULT");
+        assert_eq!(message.payload, "ON\u{fffd}");
+    }
+}
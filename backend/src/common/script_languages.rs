@@ -1,9 +1,25 @@
-use rhai::{Engine, Scope};
-use rlua::Lua;
+use std::collections::HashMap;
+
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope};
+use rlua::{Context, HookTriggers, Lua};
+use tokio::runtime::Handle;
 
 use crate::models::{error::AppError, plugin::common::Script};
 
 const INPUT: &str = "input";
+const IPADDRESS: &str = "ipaddress";
+/// scripts are stopped after this number of operations, so that an endless loop doesn't block a thread forever
+const MAX_OPERATIONS: u64 = 10_000_000;
+/// the lua hook is only called every n instructions, since calling it for every instruction is slow
+const LUA_INSTRUCTIONS_PER_HOOK: u32 = 10_000;
+
+/// The values a script of the script command can access through the host functions
+#[derive(Debug, Clone, Default)]
+pub struct ScriptContext {
+    pub ipaddress: Option<String>,
+    pub params: HashMap<String, String>,
+    pub credentials: HashMap<String, String>,
+}
 
 pub fn script_process(script: &Script, input: &str) -> Result<String, AppError> {
     let is_lua = matches!(script.script_type.as_str(), "lua");
@@ -37,12 +53,66 @@ pub fn script_match(script: &Script, input: &str) -> Result<bool, AppError> {
     }
 }
 
+/// Executes a script that produces the data itself instead of processing the response of another command.
+///
+/// Besides the global `ipaddress` the following host functions are available in lua and rhai:
+/// * `http_request(method, url, headers, body)` executes a http request and returns the response body. Headers and body are optional
+/// * `parse_json(text)` converts a JSON string into a table (lua) or map/array (rhai)
+/// * `to_json(value)` converts a value into a JSON string
+/// * `param(name)` returns the value of a plugin param
+/// * `credential(name)` returns the decrypted value of a credential
+///
+/// The result of the script is returned as is if it is a string, otherwise it is converted to JSON.
+///
+/// Like all scripts it is stopped with a `ScriptError` once it exceeds `MAX_OPERATIONS`.
+///
+/// Note: `http_request` blocks the current thread, so this function needs to be called within `spawn_blocking`
+pub fn script_execute(script: &Script, context: ScriptContext) -> Result<String, AppError> {
+    match script.script_type.as_str() {
+        "lua" => execute_with_lua(&script.script, context),
+        "rhai" => execute_with_rhai(&script.script, context),
+        _ => Err(AppError::InvalidArgument(
+            "script".to_string(),
+            Some(script.script_type.clone()),
+        )),
+    }
+}
+
+fn new_rhai_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine
+}
+
+fn new_lua() -> Lua {
+    let lua = Lua::new();
+    let mut operations: u64 = 0;
+
+    lua.set_hook(
+        HookTriggers {
+            every_nth_instruction: Some(LUA_INSTRUCTIONS_PER_HOOK),
+            ..HookTriggers::default()
+        },
+        move |_, _| {
+            operations += LUA_INSTRUCTIONS_PER_HOOK as u64;
+            if operations > MAX_OPERATIONS {
+                return Err(rlua::Error::RuntimeError(format!(
+                    "the script exceeded the limit of {} operations",
+                    MAX_OPERATIONS
+                )));
+            }
+            Ok(())
+        },
+    );
+    lua
+}
+
 fn match_with_rhai(input: &str, script: &str) -> Result<bool, AppError> {
     let mut scope = Scope::new();
 
     scope.push(INPUT, input.to_owned());
 
-    let engine = Engine::new();
+    let engine = new_rhai_engine();
     engine
         .eval_with_scope::<bool>(&mut scope, script)
         .map_err(|err| {
@@ -54,7 +124,7 @@ fn match_with_rhai(input: &str, script: &str) -> Result<bool, AppError> {
 }
 
 pub fn match_with_lua(input: &str, script: &str) -> Result<bool, AppError> {
-    let lua = Lua::new();
+    let lua = new_lua();
     let mut result = Ok(false);
 
     lua.context(|lua_ctx| {
@@ -75,7 +145,7 @@ pub fn match_with_lua(input: &str, script: &str) -> Result<bool, AppError> {
 }
 
 fn process_with_lua(input: &str, script: &str) -> Result<String, AppError> {
-    let lua = Lua::new();
+    let lua = new_lua();
     let mut result: Result<String, AppError> = Ok("".to_string());
 
     lua.context(|lua_ctx| {
@@ -98,12 +168,265 @@ fn process_with_rhai(input: &str, script: &str) -> Result<String, AppError> {
 
     scope.push(INPUT, input.to_owned());
 
-    let engine = Engine::new();
+    let engine = new_rhai_engine();
     engine
         .eval_with_scope::<String>(&mut scope, script)
         .map_err(|e| AppError::ScriptError(format!("{}", e)))
 }
 
+fn execute_with_rhai(script: &str, context: ScriptContext) -> Result<String, AppError> {
+    let mut engine = new_rhai_engine();
+    let handle = Handle::try_current().ok();
+
+    let h = handle.clone();
+    engine.register_fn("http_request", move |method: &str, url: &str| {
+        rhai_http_request(&h, method, url, Map::new(), None)
+    });
+    let h = handle.clone();
+    engine.register_fn(
+        "http_request",
+        move |method: &str, url: &str, headers: Map| {
+            rhai_http_request(&h, method, url, headers, None)
+        },
+    );
+    engine.register_fn(
+        "http_request",
+        move |method: &str, url: &str, headers: Map, body: &str| {
+            rhai_http_request(&handle, method, url, headers, Some(body.to_owned()))
+        },
+    );
+    engine.register_fn(
+        "parse_json",
+        |text: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+            let value: serde_json::Value =
+                serde_json::from_str(text).map_err(|err| format!("{}", err))?;
+            rhai::serde::to_dynamic(value)
+        },
+    );
+    engine.register_fn(
+        "to_json",
+        |value: Dynamic| -> Result<String, Box<EvalAltResult>> {
+            Ok(serde_json::to_string(&value).map_err(|err| format!("{}", err))?)
+        },
+    );
+    let params = context.params;
+    engine.register_fn(
+        "param",
+        move |name: &str| -> Result<String, Box<EvalAltResult>> {
+            Ok(params
+                .get(name)
+                .cloned()
+                .ok_or(format!("param {} not found", name))?)
+        },
+    );
+    let credentials = context.credentials;
+    engine.register_fn(
+        "credential",
+        move |name: &str| -> Result<String, Box<EvalAltResult>> {
+            Ok(credentials
+                .get(name)
+                .cloned()
+                .ok_or(format!("credential {} not found", name))?)
+        },
+    );
+
+    let mut scope = Scope::new();
+    scope.push(IPADDRESS, context.ipaddress.unwrap_or_default());
+
+    let result = engine
+        .eval_with_scope::<Dynamic>(&mut scope, script)
+        .map_err(|e| AppError::ScriptError(format!("{}", e)))?;
+
+    if result.is_string() {
+        Ok(result.into_string().unwrap_or_default())
+    } else {
+        Ok(serde_json::to_string(&result)?)
+    }
+}
+
+fn rhai_http_request(
+    handle: &Option<Handle>,
+    method: &str,
+    url: &str,
+    headers: Map,
+    body: Option<String>,
+) -> Result<String, Box<EvalAltResult>> {
+    let headers = headers
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+    Ok(http_request(handle, method, url, headers, body).map_err(|err| format!("{}", err))?)
+}
+
+fn execute_with_lua(script: &str, context: ScriptContext) -> Result<String, AppError> {
+    let lua = new_lua();
+    let handle = Handle::try_current().ok();
+
+    lua.context(|lua_ctx| -> rlua::Result<String> {
+        let globals = lua_ctx.globals();
+        globals.set(IPADDRESS, context.ipaddress.unwrap_or_default())?;
+
+        globals.set(
+            "http_request",
+            lua_ctx.create_function(
+                move |_,
+                      (method, url, headers, body): (
+                    String,
+                    String,
+                    Option<HashMap<String, String>>,
+                    Option<String>,
+                )| {
+                    http_request(
+                        &handle,
+                        &method,
+                        &url,
+                        headers.unwrap_or_default().into_iter().collect(),
+                        body,
+                    )
+                    .map_err(|err| rlua::Error::RuntimeError(format!("{}", err)))
+                },
+            )?,
+        )?;
+        globals.set(
+            "parse_json",
+            lua_ctx.create_function(|lua_ctx, text: String| {
+                let value: serde_json::Value = serde_json::from_str(&text)
+                    .map_err(|err| rlua::Error::RuntimeError(format!("{}", err)))?;
+                json_to_lua(lua_ctx, &value)
+            })?,
+        )?;
+        globals.set(
+            "to_json",
+            lua_ctx.create_function(|_, value: rlua::Value| {
+                serde_json::to_string(&lua_to_json(value)?)
+                    .map_err(|err| rlua::Error::RuntimeError(format!("{}", err)))
+            })?,
+        )?;
+        let params = context.params;
+        globals.set(
+            "param",
+            lua_ctx.create_function(move |_, name: String| {
+                params
+                    .get(&name)
+                    .cloned()
+                    .ok_or_else(|| rlua::Error::RuntimeError(format!("param {} not found", name)))
+            })?,
+        )?;
+        let credentials = context.credentials;
+        globals.set(
+            "credential",
+            lua_ctx.create_function(move |_, name: String| {
+                credentials.get(&name).cloned().ok_or_else(|| {
+                    rlua::Error::RuntimeError(format!("credential {} not found", name))
+                })
+            })?,
+        )?;
+
+        match lua_ctx.load(script).eval::<rlua::Value>()? {
+            rlua::Value::String(result) => Ok(result.to_str()?.to_owned()),
+            other => serde_json::to_string(&lua_to_json(other)?)
+                .map_err(|err| rlua::Error::RuntimeError(format!("{}", err))),
+        }
+    })
+    .map_err(|e| AppError::ScriptError(format!("{}", e)))
+}
+
+fn http_request(
+    handle: &Option<Handle>,
+    method: &str,
+    url: &str,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+) -> Result<String, AppError> {
+    let handle = handle.as_ref().ok_or(AppError::ScriptError(
+        "No runtime for http requests".to_owned(),
+    ))?;
+
+    handle.block_on(crate::common::execute_http_request(
+        url,
+        method.to_lowercase().as_str(),
+        Some(headers),
+        body,
+    ))
+}
+
+fn json_to_lua<'lua>(
+    lua_ctx: Context<'lua>,
+    value: &serde_json::Value,
+) -> rlua::Result<rlua::Value<'lua>> {
+    Ok(match value {
+        serde_json::Value::Null => rlua::Value::Nil,
+        serde_json::Value::Bool(b) => rlua::Value::Boolean(*b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => rlua::Value::Integer(i),
+            None => rlua::Value::Number(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => rlua::Value::String(lua_ctx.create_string(s)?),
+        serde_json::Value::Array(list) => {
+            let table = lua_ctx.create_table()?;
+            for (index, entry) in list.iter().enumerate() {
+                table.set(index + 1, json_to_lua(lua_ctx, entry)?)?;
+            }
+            rlua::Value::Table(table)
+        }
+        serde_json::Value::Object(map) => {
+            let table = lua_ctx.create_table()?;
+            for (key, entry) in map {
+                table.set(key.as_str(), json_to_lua(lua_ctx, entry)?)?;
+            }
+            rlua::Value::Table(table)
+        }
+    })
+}
+
+fn lua_to_json(value: rlua::Value) -> rlua::Result<serde_json::Value> {
+    Ok(match value {
+        rlua::Value::Nil => serde_json::Value::Null,
+        rlua::Value::Boolean(b) => serde_json::Value::Bool(b),
+        rlua::Value::Integer(i) => serde_json::Value::from(i),
+        rlua::Value::Number(n) => serde_json::Value::from(n),
+        rlua::Value::String(s) => serde_json::Value::String(s.to_str()?.to_owned()),
+        rlua::Value::Table(table) => {
+            let length = table.raw_len();
+            let pairs: Vec<(rlua::Value, rlua::Value)> =
+                table.pairs().collect::<rlua::Result<_>>()?;
+
+            // a table with the keys 1..n is treated as an array, every other table as object
+            if length > 0 && pairs.len() == length as usize {
+                let mut list = Vec::new();
+                for index in 1..=length {
+                    let entry = pairs
+                        .iter()
+                        .find(|(key, _)| matches!(key, rlua::Value::Integer(k) if *k == index))
+                        .map(|(_, entry)| entry.clone())
+                        .unwrap_or(rlua::Value::Nil);
+                    list.push(lua_to_json(entry)?);
+                }
+                serde_json::Value::Array(list)
+            } else {
+                let mut map = serde_json::Map::new();
+                for (key, entry) in pairs {
+                    let key = match key {
+                        rlua::Value::String(s) => s.to_str()?.to_owned(),
+                        rlua::Value::Integer(i) => format!("{}", i),
+                        rlua::Value::Number(n) => format!("{}", n),
+                        _ => continue,
+                    };
+                    map.insert(key, lua_to_json(entry)?);
+                }
+                serde_json::Value::Object(map)
+            }
+        }
+        other => {
+            return Err(rlua::Error::RuntimeError(format!(
+                "cannot convert {:?} to JSON",
+                other
+            )))
+        }
+    })
+}
+
 fn wrap_in_brackets(input: &str) -> String {
     format!("[[{}]]", input)
 }
@@ -127,4 +450,55 @@ mod tests {
 
         assert!(match_with_rhai(input, script).expect("should not happen"));
     }
+
+    #[test]
+    fn test_script_execute() {
+        let context = ScriptContext {
+            ipaddress: Some("192.168.178.2".to_owned()),
+            params: HashMap::from([("port".to_owned(), "8080".to_owned())]),
+            credentials: HashMap::from([("token".to_owned(), "secret".to_owned())]),
+        };
+
+        let lua = Script {
+            script_type: "lua".to_owned(),
+            script: "local data = parse_json('{\"values\": [1, 2]}') return { sum = data.values[1] + data.values[2], url = ipaddress .. ':' .. param('port'), token = credential('token') }".to_owned(),
+        };
+        let rhai = Script {
+            script_type: "rhai".to_owned(),
+            script: "let data = parse_json(`{\"values\": [1, 2]}`); #{ sum: data.values[0] + data.values[1], url: ipaddress + \":\" + param(\"port\"), token: credential(\"token\") }".to_owned(),
+        };
+
+        for script in [lua, rhai] {
+            let result: serde_json::Value =
+                serde_json::from_str(&script_execute(&script, context.clone()).unwrap()).unwrap();
+
+            assert_eq!(
+                result,
+                serde_json::json!({"sum": 3, "url": "192.168.178.2:8080", "token": "secret"})
+            );
+        }
+    }
+
+    #[test]
+    fn test_endless_loop_is_stopped() {
+        let lua = Script {
+            script_type: "lua".to_owned(),
+            script: "while true do end".to_owned(),
+        };
+        let rhai = Script {
+            script_type: "rhai".to_owned(),
+            script: "loop {}".to_owned(),
+        };
+
+        for script in [lua, rhai] {
+            assert!(matches!(
+                script_execute(&script, ScriptContext::default()),
+                Err(AppError::ScriptError(_))
+            ));
+            assert!(matches!(
+                script_match(&script, "input"),
+                Err(AppError::ScriptError(_))
+            ));
+        }
+    }
 }
@@ -0,0 +1,450 @@
+use std::{net::IpAddr, str::FromStr, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use snmp2::{
+    v3::{Auth, AuthProtocol, Cipher, Security},
+    AsyncSession, Oid, Value,
+};
+
+use crate::models::error::AppError;
+
+const MAX_WALK_ENTRIES: usize = 10000;
+
+#[derive(Clone, Debug)]
+pub enum SnmpCredentials {
+    V2c {
+        community: String,
+    },
+    V3 {
+        username: String,
+        auth_password: Option<String>,
+        auth_protocol: Option<String>,
+        priv_password: Option<String>,
+        priv_protocol: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnmpOperation {
+    Get,
+    GetNext,
+    Walk,
+    Set { value_type: String, value: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SnmpVarbind {
+    pub oid: String,
+    #[serde(rename = "type")]
+    pub value_type: String,
+    pub value: serde_json::Value,
+}
+
+/// Executes an SNMP request and returns the resulting varbinds
+///
+/// # Arguments
+///
+/// * `ipaddress` the address of the SNMP agent
+/// * `port` the UDP port of the agent (normally 161)
+/// * `credentials` either a v2c community or a v3 user with optional authentication and privacy settings
+/// * `operation` get, getnext, walk or set
+/// * `oids` the OIDs in dotted notation. For walk and set only the first OID is used
+/// * `timeout` timeout for each single request to the agent
+///
+/// # Errors
+/// * `AppError::InvalidArgument` if an OID, a protocol or a value cannot be parsed
+/// * `AppError::CommunicationError` if the agent does not answer in time or returns an error
+pub async fn execute_snmp_request(
+    ipaddress: IpAddr,
+    port: u16,
+    credentials: SnmpCredentials,
+    operation: SnmpOperation,
+    oids: Vec<String>,
+    timeout: Duration,
+) -> Result<Vec<SnmpVarbind>, AppError> {
+    let oids = oids
+        .iter()
+        .map(|oid| parse_oid(oid))
+        .collect::<Result<Vec<Oid>, AppError>>()?;
+
+    let first_oid = oids
+        .first()
+        .ok_or_else(|| AppError::MissingArgument("oid".to_owned()))?;
+
+    let mut session = create_session(ipaddress, port, credentials, timeout).await?;
+
+    match operation {
+        SnmpOperation::Get => {
+            let oid_refs: Vec<&Oid> = oids.iter().collect();
+            let pdu = with_timeout(timeout, session.get_many(&oid_refs)).await?;
+            check_error_status(pdu.error_status)?;
+            Ok(pdu.varbinds.map(to_varbind).collect())
+        }
+        SnmpOperation::GetNext => {
+            let pdu = with_timeout(timeout, session.getnext(first_oid)).await?;
+            check_error_status(pdu.error_status)?;
+            Ok(pdu.varbinds.map(to_varbind).collect())
+        }
+        SnmpOperation::Walk => walk(&mut session, first_oid, timeout).await,
+        SnmpOperation::Set { value_type, value } => {
+            let value_holder = SetValue::parse(&value_type, &value)?;
+            let pdu = with_timeout(
+                timeout,
+                session.set(&[(first_oid, value_holder.as_value())]),
+            )
+            .await?;
+            check_error_status(pdu.error_status)?;
+            Ok(pdu.varbinds.map(to_varbind).collect())
+        }
+    }
+}
+
+impl FromStr for SnmpOperation {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "get" => Ok(SnmpOperation::Get),
+            "getnext" => Ok(SnmpOperation::GetNext),
+            "walk" => Ok(SnmpOperation::Walk),
+            _ => Err(AppError::InvalidArgument(
+                "operation".to_owned(),
+                Some(s.to_owned()),
+            )),
+        }
+    }
+}
+
+async fn create_session(
+    ipaddress: IpAddr,
+    port: u16,
+    credentials: SnmpCredentials,
+    timeout: Duration,
+) -> Result<AsyncSession, AppError> {
+    let destination = (ipaddress, port);
+
+    match credentials {
+        SnmpCredentials::V2c { community } => {
+            Ok(AsyncSession::new_v2c(destination, community.as_bytes(), 0).await?)
+        }
+        SnmpCredentials::V3 {
+            username,
+            auth_password,
+            auth_protocol,
+            priv_password,
+            priv_protocol,
+        } => {
+            let security = match auth_password.filter(|p| !p.is_empty()) {
+                Some(auth_password) => {
+                    let security = Security::new(username.as_bytes(), auth_password.as_bytes())
+                        .with_auth_protocol(parse_auth_protocol(auth_protocol.as_deref())?);
+
+                    match priv_password.filter(|p| !p.is_empty()) {
+                        Some(priv_password) => security.with_auth(Auth::AuthPriv {
+                            cipher: parse_cipher(priv_protocol.as_deref())?,
+                            privacy_password: priv_password.into_bytes(),
+                        }),
+                        None => security.with_auth(Auth::AuthNoPriv),
+                    }
+                }
+                None => Security::new(username.as_bytes(), &[]).with_auth(Auth::NoAuthNoPriv),
+            };
+
+            let mut session = AsyncSession::new_v3(destination, 0, security).await?;
+            with_timeout(timeout, session.init()).await?;
+            Ok(session)
+        }
+    }
+}
+
+async fn walk(
+    session: &mut AsyncSession,
+    base_oid: &Oid<'_>,
+    timeout: Duration,
+) -> Result<Vec<SnmpVarbind>, AppError> {
+    let mut result = Vec::new();
+    let mut current = base_oid.to_owned();
+
+    while result.len() < MAX_WALK_ENTRIES {
+        let pdu = with_timeout(timeout, session.getnext(&current)).await?;
+        check_error_status(pdu.error_status)?;
+
+        let mut next: Option<Oid<'static>> = None;
+        for (oid, value) in pdu.varbinds {
+            if !oid.starts_with(base_oid) || oid == current || is_end_marker(&value) {
+                break;
+            }
+            next = Some(oid.to_owned());
+            result.push(to_varbind((oid, value)));
+        }
+
+        match next {
+            Some(oid) => current = oid,
+            None => break,
+        }
+    }
+    Ok(result)
+}
+
+async fn with_timeout<T>(
+    timeout: Duration,
+    future: impl std::future::Future<Output = Result<T, snmp2::Error>>,
+) -> Result<T, AppError> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| AppError::CommunicationError("SNMP request timed out".to_owned()))?
+        .map_err(AppError::from)
+}
+
+fn check_error_status(error_status: u32) -> Result<(), AppError> {
+    match error_status {
+        0 => Ok(()),
+        status => Err(AppError::CommunicationError(format!(
+            "SNMP agent returned error status {}",
+            status
+        ))),
+    }
+}
+
+fn parse_oid(oid: &str) -> Result<Oid<'static>, AppError> {
+    Oid::from_str(oid.trim().trim_start_matches('.'))
+        .map_err(|_| AppError::InvalidArgument("oid".to_owned(), Some(oid.to_owned())))
+}
+
+fn parse_auth_protocol(protocol: Option<&str>) -> Result<AuthProtocol, AppError> {
+    match protocol.unwrap_or("sha1").to_lowercase().as_str() {
+        "md5" => Ok(AuthProtocol::Md5),
+        "sha" | "sha1" => Ok(AuthProtocol::Sha1),
+        "sha224" => Ok(AuthProtocol::Sha224),
+        "sha256" => Ok(AuthProtocol::Sha256),
+        "sha384" => Ok(AuthProtocol::Sha384),
+        "sha512" => Ok(AuthProtocol::Sha512),
+        other => Err(AppError::InvalidArgument(
+            "auth_protocol".to_owned(),
+            Some(other.to_owned()),
+        )),
+    }
+}
+
+fn parse_cipher(protocol: Option<&str>) -> Result<Cipher, AppError> {
+    match protocol.unwrap_or("aes").to_lowercase().as_str() {
+        "des" => Ok(Cipher::Des),
+        "aes" | "aes128" => Ok(Cipher::Aes128),
+        "aes192" => Ok(Cipher::Aes192),
+        "aes256" => Ok(Cipher::Aes256),
+        other => Err(AppError::InvalidArgument(
+            "priv_protocol".to_owned(),
+            Some(other.to_owned()),
+        )),
+    }
+}
+
+fn is_end_marker(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::EndOfMibView | Value::NoSuchObject | Value::NoSuchInstance
+    )
+}
+
+fn to_varbind((oid, value): (Oid, Value)) -> SnmpVarbind {
+    let (value_type, value) = match value {
+        Value::Boolean(v) => ("boolean", serde_json::Value::from(v)),
+        Value::Integer(v) => ("integer", serde_json::Value::from(v)),
+        Value::OctetString(v) => ("string", octets_to_json(v)),
+        Value::ObjectIdentifier(v) => ("oid", serde_json::Value::from(v.to_id_string())),
+        Value::IpAddress(v) => (
+            "ipaddress",
+            serde_json::Value::from(std::net::Ipv4Addr::from(v).to_string()),
+        ),
+        Value::Counter32(v) => ("counter32", serde_json::Value::from(v)),
+        Value::Unsigned32(v) => ("unsigned32", serde_json::Value::from(v)),
+        Value::Timeticks(v) => ("timeticks", serde_json::Value::from(v)),
+        Value::Counter64(v) => ("counter64", serde_json::Value::from(v)),
+        Value::Opaque(v) => ("opaque", serde_json::Value::from(hex::encode(v))),
+        Value::Null => ("null", serde_json::Value::Null),
+        Value::NoSuchObject => ("nosuchobject", serde_json::Value::Null),
+        Value::NoSuchInstance => ("nosuchinstance", serde_json::Value::Null),
+        Value::EndOfMibView => ("endofmibview", serde_json::Value::Null),
+        _ => ("unsupported", serde_json::Value::Null),
+    };
+
+    SnmpVarbind {
+        oid: oid.to_id_string(),
+        value_type: value_type.to_owned(),
+        value,
+    }
+}
+
+fn octets_to_json(octets: &[u8]) -> serde_json::Value {
+    match std::str::from_utf8(octets) {
+        Ok(str) if !str.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+            serde_json::Value::from(str.trim_end_matches('\0'))
+        }
+        _ => serde_json::Value::from(hex::encode(octets)),
+    }
+}
+
+enum SetValue {
+    Integer(i64),
+    OctetString(Vec<u8>),
+    ObjectIdentifier(Oid<'static>),
+    IpAddress([u8; 4]),
+    Counter32(u32),
+    Unsigned32(u32),
+    Timeticks(u32),
+    Counter64(u64),
+}
+
+impl SetValue {
+    fn parse(value_type: &str, value: &str) -> Result<Self, AppError> {
+        let invalid = || AppError::InvalidArgument("value".to_owned(), Some(value.to_owned()));
+
+        match value_type.to_lowercase().as_str() {
+            "i" | "integer" => Ok(SetValue::Integer(value.parse().map_err(|_| invalid())?)),
+            "s" | "string" => Ok(SetValue::OctetString(value.as_bytes().to_vec())),
+            "x" | "hex" => Ok(SetValue::OctetString(
+                hex::decode(value).map_err(|_| invalid())?,
+            )),
+            "o" | "oid" => Ok(SetValue::ObjectIdentifier(parse_oid(value)?)),
+            "a" | "ipaddress" => match value.parse::<IpAddr>() {
+                Ok(IpAddr::V4(ip)) => Ok(SetValue::IpAddress(ip.octets())),
+                _ => Err(invalid()),
+            },
+            "c" | "counter32" => Ok(SetValue::Counter32(value.parse().map_err(|_| invalid())?)),
+            "u" | "unsigned32" => Ok(SetValue::Unsigned32(value.parse().map_err(|_| invalid())?)),
+            "t" | "timeticks" => Ok(SetValue::Timeticks(value.parse().map_err(|_| invalid())?)),
+            "counter64" => Ok(SetValue::Counter64(value.parse().map_err(|_| invalid())?)),
+            other => Err(AppError::InvalidArgument(
+                "value_type".to_owned(),
+                Some(other.to_owned()),
+            )),
+        }
+    }
+
+    fn as_value(&self) -> Value<'_> {
+        match self {
+            SetValue::Integer(v) => Value::Integer(*v),
+            SetValue::OctetString(v) => Value::OctetString(v.as_slice()),
+            SetValue::ObjectIdentifier(v) => Value::ObjectIdentifier(v.clone()),
+            SetValue::IpAddress(v) => Value::IpAddress(*v),
+            SetValue::Counter32(v) => Value::Counter32(*v),
+            SetValue::Unsigned32(v) => Value::Unsigned32(*v),
+            SetValue::Timeticks(v) => Value::Timeticks(*v),
+            SetValue::Counter64(v) => Value::Counter64(*v),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::Duration;
+
+    use tokio::net::UdpSocket;
+
+    use super::*;
+
+    const SYS_NAME_OID: [u8; 8] = [0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x05, 0x00];
+
+    fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut result = vec![tag, content.len() as u8];
+        result.extend_from_slice(content);
+        result
+    }
+
+    fn find_request_id(request: &[u8]) -> Vec<u8> {
+        // message: SEQUENCE { version, community, PDU { request-id, ... } }
+        let mut pos = 2;
+        pos += 2 + request[pos + 1] as usize; // version
+        pos += 2 + request[pos + 1] as usize; // community
+        pos += 2; // pdu header
+        let len = request[pos + 1] as usize;
+        request[pos..pos + 2 + len].to_vec()
+    }
+
+    /// minimal snmpd stand-in which answers every request with sysName.0 = "switch01"
+    async fn start_agent() -> u16 {
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let port = socket.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1500];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                let request_id = find_request_id(&buf[..len]);
+
+                let varbind = [tlv(0x06, &SYS_NAME_OID), tlv(0x04, b"switch01")].concat();
+                let varbinds = tlv(0x30, &tlv(0x30, &varbind));
+                let pdu = tlv(
+                    0xa2,
+                    &[
+                        request_id,
+                        vec![0x02, 0x01, 0x00, 0x02, 0x01, 0x00],
+                        varbinds,
+                    ]
+                    .concat(),
+                );
+                let message = tlv(
+                    0x30,
+                    &[vec![0x02, 0x01, 0x01], tlv(0x04, b"public"), pdu].concat(),
+                );
+                socket.send_to(&message, peer).await.unwrap();
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn test_snmp_get_v2c() {
+        let port = start_agent().await;
+
+        let result = execute_snmp_request(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port,
+            SnmpCredentials::V2c {
+                community: "public".to_owned(),
+            },
+            SnmpOperation::Get,
+            vec!["1.3.6.1.2.1.1.5.0".to_owned()],
+            Duration::from_secs(2),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"[{"oid":"1.3.6.1.2.1.1.5.0","type":"string","value":"switch01"}]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_snmp_walk_stops_outside_of_subtree() {
+        let port = start_agent().await;
+
+        let result = execute_snmp_request(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port,
+            SnmpCredentials::V2c {
+                community: "public".to_owned(),
+            },
+            SnmpOperation::Walk,
+            vec!["1.3.6.1.2.1.1".to_owned()],
+            Duration::from_secs(2),
+        )
+        .await
+        .unwrap();
+
+        // the stand-in always answers with the same OID, so the walk has to stop after the first entry
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].value, serde_json::Value::from("switch01"));
+    }
+
+    #[test]
+    fn test_set_value_parse() {
+        assert!(matches!(
+            SetValue::parse("i", "42").unwrap(),
+            SetValue::Integer(42)
+        ));
+        assert!(SetValue::parse("integer", "abc").is_err());
+        assert!(SetValue::parse("unknown", "1").is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    io::{ErrorKind, Read},
+    net::{IpAddr, SocketAddr, TcpStream},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use ssh2::{Channel, HashType, Session};
+
+use crate::models::error::AppError;
+
+#[derive(Clone, Debug)]
+pub struct SshCredentials {
+    pub username: String,
+    pub password: Option<String>,
+    pub private_key: Option<String>,
+    pub passphrase: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SshResponse {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+    /// SHA-256 fingerprint of the host key, so that it can be pinned on first use
+    #[serde(skip)]
+    pub host_key_fingerprint: String,
+}
+
+/// how long to wait before polling the output of the command again
+const POLL_INTERVAL_MILLIS: u64 = 10;
+
+/// Executes a command on a remote host via SSH and returns stdout, stderr and the exit code of the command.
+///
+/// # Arguments
+///
+/// * `ipaddress` the address of the remote host
+/// * `port` the port of the SSH server (normally 22)
+/// * `credentials` the user name and either a password or a private key (PEM format) with an optional passphrase
+/// * `command` the command that should be executed on the remote host
+/// * `timeout` timeout for connect, authentication and execution
+/// * `pinned_host_key` the fingerprint of the host key that was seen on first use - None if nothing is pinned yet
+///
+/// # Errors
+/// * `AppError::CommunicationError` if the connection, the authentication or the execution fails
+/// * `AppError::HostKeyMismatch` if the host key doesn't match the pinned one - the credentials are not sent in this case
+///
+/// Note: the function is blocking and should be executed with `tokio::task::spawn_blocking`
+pub fn execute_ssh_command(
+    ipaddress: IpAddr,
+    port: u16,
+    credentials: SshCredentials,
+    command: &str,
+    timeout: Duration,
+    pinned_host_key: Option<&str>,
+) -> Result<SshResponse, AppError> {
+    let tcp = TcpStream::connect_timeout(&SocketAddr::new(ipaddress, port), timeout)?;
+
+    let mut session = Session::new()?;
+    session.set_timeout(timeout.as_millis() as u32);
+    session.set_tcp_stream(tcp);
+    session.handshake()?;
+
+    let host_key_fingerprint = session
+        .host_key_hash(HashType::Sha256)
+        .map(super::format_fingerprint)
+        .ok_or(AppError::CommunicationError(format!(
+            "No SSH host key received from {}",
+            ipaddress
+        )))?;
+
+    if let Some(pinned_host_key) = pinned_host_key {
+        if !super::is_same_fingerprint(pinned_host_key, &host_key_fingerprint) {
+            return Err(AppError::HostKeyMismatch(host_key_fingerprint));
+        }
+    }
+
+    authenticate(&session, &credentials)?;
+
+    if !session.authenticated() {
+        return Err(AppError::CommunicationError(format!(
+            "SSH authentication for user {} on {} failed",
+            credentials.username, ipaddress
+        )));
+    }
+
+    let mut channel = session.channel_session()?;
+    channel.exec(command)?;
+
+    let (stdout, stderr) = read_output(&session, &channel, timeout)?;
+
+    channel.wait_close()?;
+    let exit_code = channel.exit_status()?;
+
+    log::debug!(
+        "ssh command on {} finished with exit code {}",
+        ipaddress,
+        exit_code
+    );
+
+    Ok(SshResponse {
+        stdout,
+        stderr,
+        exit_code,
+        host_key_fingerprint,
+    })
+}
+
+/// Reads stdout and stderr in turns until the command closed both. Reading one stream to the end first would block
+/// forever, if the command writes more to the other stream than the window of the channel holds.
+/// The timeout applies to the time without any output
+fn read_output(
+    session: &Session,
+    channel: &Channel,
+    timeout: Duration,
+) -> Result<(String, String), AppError> {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+
+    session.set_blocking(false);
+    let result = (|| {
+        let mut last_output = Instant::now();
+        loop {
+            let read = read_available(&mut channel.stream(0), &mut stdout)?
+                + read_available(&mut channel.stderr(), &mut stderr)?;
+
+            if read > 0 {
+                last_output = Instant::now();
+            } else if channel.eof() {
+                return Ok(());
+            } else if last_output.elapsed() > timeout {
+                return Err(AppError::CommunicationError(
+                    "Timeout while waiting for the output of the ssh command".to_owned(),
+                ));
+            } else {
+                std::thread::sleep(Duration::from_millis(POLL_INTERVAL_MILLIS));
+            }
+        }
+    })();
+    session.set_blocking(true);
+    result?;
+
+    Ok((
+        String::from_utf8_lossy(&stdout).to_string(),
+        String::from_utf8_lossy(&stderr).to_string(),
+    ))
+}
+
+/// reads everything that is available without blocking - returns the number of bytes read
+fn read_available(reader: &mut impl Read, output: &mut Vec<u8>) -> Result<usize, AppError> {
+    let mut buffer = [0_u8; 8192];
+    let mut total = 0;
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(total),
+            Ok(read) => {
+                output.extend_from_slice(&buffer[..read]);
+                total += read;
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(total),
+            Err(err) => return Err(AppError::from(err)),
+        }
+    }
+}
+
+/// the way the user is authenticated - a private key is preferred over a password
+#[derive(Debug, PartialEq)]
+enum SshAuthentication<'a> {
+    PrivateKey {
+        private_key: &'a str,
+        passphrase: Option<&'a str>,
+    },
+    Password(&'a str),
+}
+
+fn get_authentication(credentials: &SshCredentials) -> Result<SshAuthentication<'_>, AppError> {
+    match (&credentials.private_key, &credentials.password) {
+        (Some(private_key), _) if !private_key.trim().is_empty() => {
+            Ok(SshAuthentication::PrivateKey {
+                private_key: private_key.as_str(),
+                passphrase: credentials.passphrase.as_deref().filter(|p| !p.is_empty()),
+            })
+        }
+        (_, Some(password)) => Ok(SshAuthentication::Password(password.as_str())),
+        _ => Err(AppError::MissingArgument(
+            "password or private_key".to_owned(),
+        )),
+    }
+}
+
+fn authenticate(session: &Session, credentials: &SshCredentials) -> Result<(), AppError> {
+    match get_authentication(credentials)? {
+        SshAuthentication::PrivateKey {
+            private_key,
+            passphrase,
+        } => session.userauth_pubkey_memory(
+            credentials.username.as_str(),
+            None,
+            private_key,
+            passphrase,
+        ),
+        SshAuthentication::Password(password) => {
+            session.userauth_password(credentials.username.as_str(), password)
+        }
+    }
+    .map_err(AppError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_credentials(
+        password: Option<&str>,
+        private_key: Option<&str>,
+        passphrase: Option<&str>,
+    ) -> SshCredentials {
+        SshCredentials {
+            username: "admin".to_owned(),
+            password: password.map(|p| p.to_owned()),
+            private_key: private_key.map(|p| p.to_owned()),
+            passphrase: passphrase.map(|p| p.to_owned()),
+        }
+    }
+
+    /// returns the chunks and then blocks, like a stream of a non-blocking session
+    struct ChunkReader {
+        chunks: Vec<&'static [u8]>,
+    }
+
+    impl Read for ChunkReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.chunks.is_empty() {
+                return Err(ErrorKind::WouldBlock.into());
+            }
+            let chunk = self.chunks.remove(0);
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn test_read_available() {
+        let mut reader = ChunkReader {
+            chunks: vec![b"Filesystem ", b"Size"],
+        };
+        let mut output = Vec::new();
+
+        assert_eq!(read_available(&mut reader, &mut output).unwrap(), 15);
+        assert_eq!(output, b"Filesystem Size");
+        // nothing available - returns instead of blocking
+        assert_eq!(read_available(&mut reader, &mut output).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_get_authentication() {
+        let credentials = make_credentials(Some("secret"), Some("PRIVATE KEY"), Some(""));
+        assert_eq!(
+            get_authentication(&credentials).unwrap(),
+            SshAuthentication::PrivateKey {
+                private_key: "PRIVATE KEY",
+                passphrase: None
+            }
+        );
+
+        // an empty private key (ie. an unset credential of the feature) falls back to the password
+        let credentials = make_credentials(Some("secret"), Some(" "), Some("phrase"));
+        assert_eq!(
+            get_authentication(&credentials).unwrap(),
+            SshAuthentication::Password("secret")
+        );
+
+        let credentials = make_credentials(None, None, None);
+        assert!(get_authentication(&credentials).is_err());
+    }
+}
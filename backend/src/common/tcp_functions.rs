@@ -0,0 +1,267 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{TcpStream, UdpSocket},
+};
+use tokio_native_tls::{native_tls, TlsConnector};
+
+use crate::models::error::AppError;
+
+const MAX_BANNER_SIZE: usize = 1024;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PortCheckResponse {
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub banner: Option<String>,
+    pub certificate: Option<CertificateInfo>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub not_before: String,
+    pub not_after: String,
+    pub days_to_expiry: i64,
+}
+
+/// Checks if a TCP port is reachable and measures the time needed to connect
+///
+/// # Arguments
+///
+/// * `ipaddress` the address of the host
+/// * `port` the port to check
+/// * `tls` if true, a TLS handshake is done and the certificate of the server is extracted. The certificate is not validated, so self signed or expired certificates are reported too
+/// * `server_name` optional name for SNI - if not given the IP address is used
+/// * `probe` optional data that is sent after connecting. The response is returned as banner
+/// * `read_banner` if true, the first data sent by the server (ie. for SSH, SMTP, FTP) is returned as banner
+/// * `timeout` timeout for connect, handshake and reading the banner
+///
+/// A port that cannot be reached is no error - the response contains `reachable: false` and the error message instead
+pub async fn check_tcp_port(
+    ipaddress: IpAddr,
+    port: u16,
+    tls: bool,
+    server_name: Option<&str>,
+    probe: Option<&str>,
+    read_banner: bool,
+    timeout: Duration,
+) -> Result<PortCheckResponse, AppError> {
+    let start = Instant::now();
+
+    let stream = match tokio::time::timeout(
+        timeout,
+        TcpStream::connect(SocketAddr::new(ipaddress, port)),
+    )
+    .await
+    {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(err)) => return Ok(unreachable(err.to_string())),
+        Err(_) => return Ok(unreachable("connect timed out".to_owned())),
+    };
+
+    let mut response = PortCheckResponse {
+        reachable: true,
+        latency_ms: Some(start.elapsed().as_millis() as u64),
+        ..Default::default()
+    };
+
+    if !tls {
+        if probe.is_some() || read_banner {
+            response.banner = get_banner(stream, probe, timeout).await;
+        }
+        return Ok(response);
+    }
+
+    let connector = TlsConnector::from(
+        native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true)
+            .build()
+            .map_err(|err| AppError::Unknown(format!("{}", err)))?,
+    );
+
+    let domain = server_name
+        .map(|name| name.to_owned())
+        .unwrap_or(format!("{}", ipaddress));
+
+    match tokio::time::timeout(timeout, connector.connect(domain.as_str(), stream)).await {
+        Ok(Ok(tls_stream)) => {
+            response.certificate = match tls_stream.get_ref().peer_certificate() {
+                Ok(Some(certificate)) => Some(parse_certificate(
+                    certificate
+                        .to_der()
+                        .map_err(|err| AppError::Unknown(format!("{}", err)))?
+                        .as_slice(),
+                )?),
+                _ => None,
+            };
+
+            if probe.is_some() || read_banner {
+                response.banner = get_banner(tls_stream, probe, timeout).await;
+            }
+        }
+        Ok(Err(err)) => response.error = Some(format!("TLS handshake failed: {}", err)),
+        Err(_) => response.error = Some("TLS handshake timed out".to_owned()),
+    }
+
+    Ok(response)
+}
+
+/// Checks if a UDP port answers to the given probe.
+/// Since UDP is connectionless, a port only counts as reachable if a response was received
+pub async fn check_udp_port(
+    ipaddress: IpAddr,
+    port: u16,
+    probe: Option<&str>,
+    timeout: Duration,
+) -> Result<PortCheckResponse, AppError> {
+    let local_address: SocketAddr = match ipaddress {
+        IpAddr::V4(_) => "0.0.0.0:0".parse()?,
+        IpAddr::V6(_) => "[::]:0".parse()?,
+    };
+
+    let socket = UdpSocket::bind(local_address).await?;
+    socket.connect(SocketAddr::new(ipaddress, port)).await?;
+
+    let start = Instant::now();
+    socket
+        .send(unescape(probe.unwrap_or_default()).as_bytes())
+        .await?;
+
+    let mut buf = [0u8; MAX_BANNER_SIZE];
+    match tokio::time::timeout(timeout, socket.recv(&mut buf)).await {
+        Ok(Ok(len)) => Ok(PortCheckResponse {
+            reachable: true,
+            latency_ms: Some(start.elapsed().as_millis() as u64),
+            banner: Some(String::from_utf8_lossy(&buf[..len]).to_string()),
+            ..Default::default()
+        }),
+        Ok(Err(err)) => Ok(unreachable(err.to_string())),
+        Err(_) => Ok(unreachable("no response within timeout".to_owned())),
+    }
+}
+
+async fn get_banner<S>(mut stream: S, probe: Option<&str>, timeout: Duration) -> Option<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    if let Some(probe) = probe {
+        if let Err(err) = stream.write_all(unescape(probe).as_bytes()).await {
+            log::debug!("Could not send probe: {}", err);
+            return None;
+        }
+    }
+
+    let mut buf = [0u8; MAX_BANNER_SIZE];
+    match tokio::time::timeout(timeout, stream.read(&mut buf)).await {
+        Ok(Ok(len)) if len > 0 => Some(String::from_utf8_lossy(&buf[..len]).trim().to_owned()),
+        _ => None,
+    }
+}
+
+fn parse_certificate(der: &[u8]) -> Result<CertificateInfo, AppError> {
+    let (_, certificate) = x509_parser::parse_x509_certificate(der)
+        .map_err(|err| AppError::ParseError(format!("{}", err)))?;
+
+    let not_before = certificate.validity().not_before.timestamp();
+    let not_after = certificate.validity().not_after.timestamp();
+
+    Ok(CertificateInfo {
+        subject: certificate.subject().to_string(),
+        issuer: certificate.issuer().to_string(),
+        not_before: timestamp_to_string(not_before),
+        not_after: timestamp_to_string(not_after),
+        days_to_expiry: (not_after - Utc::now().timestamp()).div_euclid(60 * 60 * 24),
+    })
+}
+
+fn timestamp_to_string(timestamp: i64) -> String {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .map(|datetime| datetime.to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// allows to define line breaks in probes like they are needed for HTTP or SMTP
+fn unescape(probe: &str) -> String {
+    probe.replace("\\r", "\r").replace("\\n", "\n")
+}
+
+fn unreachable(error: String) -> PortCheckResponse {
+    PortCheckResponse {
+        reachable: false,
+        error: Some(error),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_tcp_port_with_banner() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            socket.write_all(b"SSH-2.0-OpenSSH_9.3\r\n").await.unwrap();
+        });
+
+        let response = check_tcp_port(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port,
+            false,
+            None,
+            None,
+            true,
+            Duration::from_secs(2),
+        )
+        .await
+        .unwrap();
+
+        assert!(response.reachable);
+        assert!(response.latency_ms.is_some());
+        assert_eq!(response.banner, Some("SSH-2.0-OpenSSH_9.3".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_check_tcp_port_closed() {
+        // bind and drop to get a port that is most likely not in use
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let response = check_tcp_port(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port,
+            false,
+            None,
+            None,
+            false,
+            Duration::from_secs(2),
+        )
+        .await
+        .unwrap();
+
+        assert!(!response.reachable);
+        assert!(response.error.is_some());
+    }
+}
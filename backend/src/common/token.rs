@@ -1,16 +1,9 @@
-use std::collections::HashMap;
-
-use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 
-use tokio::sync::RwLock;
-
-use crate::models::error::AppError;
-
-lazy_static! {
-    static ref GENERATED_TOKENS: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
-}
+use crate::{datastore, models::error::AppError};
 
+/// Token for the event stream. It is part of the url, since the EventSource of the browser cannot send headers,
+/// so it is only valid for the event stream and not for the API
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct Token {
     token: String,
@@ -27,49 +20,17 @@ impl Token {
         Token { token }
     }
 
-    pub async fn generate() -> Result<Self, AppError> {
-        let token = generate_unique_token().await;
+    pub async fn generate(user_id: &str) -> Result<Self, AppError> {
+        let token = super::generate_long_random_string();
 
-        insert_token(&token).await;
+        datastore::insert_event_token(&token, user_id).await?;
 
         Ok(Token { token })
     }
 
-    pub async fn is_valid(&self, remove_if_exists: bool) -> bool {
-        let exists = token_exists(&self.token).await;
-
-        if exists && remove_if_exists {
-            remove_token(&self.token).await;
-        }
-        exists
-    }
-}
-
-async fn generate_unique_token() -> String {
-    let read_tokens = GENERATED_TOKENS.read().await;
-    let mut token;
-    'outer: loop {
-        token = super::generate_short_random_string();
-
-        if !read_tokens.contains_key(&token) {
-            break 'outer;
-        }
+    /// the user the token was generated for - None if the token is unknown, expired or already used.
+    /// The token can only be used once, a reconnect needs a new token
+    pub async fn take_user_id(&self) -> Result<Option<String>, AppError> {
+        datastore::take_event_token(&self.token).await
     }
-    token
-}
-
-async fn token_exists(token: &str) -> bool {
-    let read_tokens = GENERATED_TOKENS.read().await;
-    read_tokens.contains_key(token)
-}
-
-async fn insert_token(token: &str) {
-    let mut write_keys = GENERATED_TOKENS.write().await;
-    write_keys.insert(token.to_owned(), token.to_owned());
-}
-
-pub async fn remove_token(token: &str) {
-    let mut write_tokens = GENERATED_TOKENS.write().await;
-
-    write_tokens.remove(token);
 }
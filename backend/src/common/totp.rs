@@ -0,0 +1,137 @@
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use sha1::Sha1;
+
+use crate::models::error::AppError;
+
+use super::percent_encode;
+
+const ISSUER: &str = "Server-Manager";
+const DIGITS: u32 = 6;
+const STEP_SECONDS: u64 = 30;
+/// codes of the previous and next time step are accepted as well, in case the clocks differ a bit
+const ALLOWED_STEP_DRIFT: u64 = 1;
+const SECRET_LENGTH: usize = 20;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Random secret as base32 - the format that authenticator apps expect
+pub fn generate_totp_secret() -> String {
+    let mut buff = [0_u8; SECRET_LENGTH];
+    OsRng.fill_bytes(&mut buff);
+    BASE32_NOPAD.encode(&buff)
+}
+
+/// The uri that can be added to an authenticator app (ie. as QR code)
+pub fn make_otpauth_uri(secret: &str, user_id: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        ISSUER,
+        percent_encode(user_id),
+        secret,
+        ISSUER,
+        DIGITS,
+        STEP_SECONDS
+    )
+}
+
+/// Checks the code against the time steps around the given unix time (RFC 6238).
+/// Returns the time step of the code, if it is valid and newer than the last used step - so a code cannot be used twice
+pub fn verify_totp_code(
+    secret: &str,
+    code: &str,
+    unix_time: u64,
+    last_used_step: u64,
+) -> Result<Option<u64>, AppError> {
+    let key = BASE32_NOPAD
+        .decode(secret.as_bytes())
+        .map_err(|err| AppError::Unknown(format!("Invalid TOTP secret: {}", err)))?;
+
+    // parse would accept signs and additional leading zeros
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(None);
+    }
+
+    let current_step = unix_time / STEP_SECONDS;
+    let first_step = current_step.saturating_sub(ALLOWED_STEP_DRIFT);
+
+    for step in first_step..=current_step + ALLOWED_STEP_DRIFT {
+        if step > last_used_step && format_code(totp(&key, step)?) == code {
+            return Ok(Some(step));
+        }
+    }
+    Ok(None)
+}
+
+/// Random one-time codes that can be used instead of a TOTP code, if the authenticator is lost
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| super::generate_short_random_string())
+        .collect()
+}
+
+fn totp(key: &[u8], step: u64) -> Result<u32, AppError> {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key)
+        .map_err(|err| AppError::Unknown(format!("Invalid TOTP key: {}", err)))?;
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // dynamic truncation of RFC 4226
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    Ok(binary % 10_u32.pow(DIGITS))
+}
+
+/// the code with leading zeros, like it is shown by the authenticator apps
+fn format_code(code: u32) -> String {
+    format!("{:0width$}", code, width = DIGITS as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_totp_code() {
+        // test vectors of RFC 6238 (SHA1) - truncated to 6 digits
+        let secret = BASE32_NOPAD.encode(b"12345678901234567890");
+
+        assert_eq!(verify_totp_code(&secret, "287082", 59, 0).unwrap(), Some(1));
+        assert_eq!(
+            verify_totp_code(&secret, "081804", 1111111109, 0).unwrap(),
+            Some(37037036)
+        );
+        // already used
+        assert_eq!(
+            verify_totp_code(&secret, "081804", 1111111109, 37037036).unwrap(),
+            None
+        );
+        assert_eq!(
+            verify_totp_code(&secret, "123456", 1111111109, 0).unwrap(),
+            None
+        );
+        assert_eq!(verify_totp_code(&secret, "abc", 59, 0).unwrap(), None);
+        // only the code with exactly six digits is valid
+        assert_eq!(
+            verify_totp_code(&secret, "81804", 1111111109, 0).unwrap(),
+            None
+        );
+        assert_eq!(verify_totp_code(&secret, "0287082", 59, 0).unwrap(), None);
+        assert_eq!(verify_totp_code(&secret, "+287082", 59, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_make_otpauth_uri() {
+        assert_eq!(
+            make_otpauth_uri("JBSWY3DPEHPK3PXP", "max mustermann"),
+            "otpauth://totp/Server-Manager:max%20mustermann?secret=JBSWY3DPEHPK3PXP&issuer=Server-Manager&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use crate::models::{alert::AlertRule, error::AppError};
+
+use super::{persistence, Entry};
+
+const TABLE: &str = "alarms";
+
+fn entry_to_alert_rule(entry: &Entry) -> Result<AlertRule, AppError> {
+    serde_json::from_str::<AlertRule>(entry.value.as_str()).map_err(AppError::from)
+}
+
+fn alert_rule_to_entry(rule: &AlertRule) -> Result<Entry, AppError> {
+    Ok(Entry {
+        key: rule.id.clone(),
+        value: serde_json::to_string(rule)?,
+    })
+}
+
+pub async fn insert_or_update_alert_rule(rule: &AlertRule) -> Result<bool, AppError> {
+    let entry = alert_rule_to_entry(rule)?;
+
+    let count = match persistence::get(TABLE, &rule.id).await? {
+        Some(_) => persistence::update(TABLE, entry).await?,
+        None => persistence::insert(TABLE, entry).await?,
+    };
+    Ok(count > 0)
+}
+
+pub async fn delete_alert_rule(id: &str) -> Result<bool, AppError> {
+    let result = persistence::delete(TABLE, id).await?;
+
+    Ok(result > 0)
+}
+
+pub async fn get_all_alert_rules() -> Result<Vec<AlertRule>, AppError> {
+    let entries = persistence::get_all(TABLE, Some("key")).await?;
+
+    let mut list = Vec::new();
+    for entry in &entries {
+        list.push(entry_to_alert_rule(entry)?);
+    }
+    Ok(list)
+}
+
+pub async fn get_alert_rule(id: &str) -> Result<AlertRule, AppError> {
+    match persistence::get(TABLE, id).await? {
+        Some(entry) => entry_to_alert_rule(&entry),
+        None => Err(AppError::DataNotFound(id.to_owned())),
+    }
+}
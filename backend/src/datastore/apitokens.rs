@@ -0,0 +1,98 @@
+use chrono::{Duration, Utc};
+
+use crate::{
+    common,
+    models::{error::AppError, token::ApiToken},
+};
+
+use super::{persistence, Entry};
+
+/// the key is the hash of the token, so that a token can be found without knowing its id
+const TABLE: &str = "api_tokens";
+
+/// the last use is only written with this precision to avoid a database write on every request
+const LAST_USED_PRECISION_SECONDS: i64 = 60;
+
+fn entry_to_api_token(entry: &Entry) -> Result<ApiToken, AppError> {
+    serde_json::from_str::<ApiToken>(entry.value.as_str()).map_err(AppError::from)
+}
+
+fn api_token_to_entry(api_token: &ApiToken) -> Result<Entry, AppError> {
+    Ok(Entry {
+        key: api_token.get_token_hash(),
+        value: serde_json::to_string(api_token)?,
+    })
+}
+
+pub async fn insert_api_token(api_token: &ApiToken) -> Result<bool, AppError> {
+    let result = persistence::insert(TABLE, api_token_to_entry(api_token)?).await?;
+
+    Ok(result > 0)
+}
+
+/// the tokens of the user without their hashes
+pub async fn get_api_tokens_of_user(user_id: &str) -> Result<Vec<ApiToken>, AppError> {
+    Ok(get_all_api_tokens()
+        .await?
+        .iter()
+        .filter(|api_token| api_token.user_id == user_id)
+        .map(|api_token| api_token.copy_no_hash())
+        .collect())
+}
+
+pub async fn delete_api_token(user_id: &str, id: &str) -> Result<bool, AppError> {
+    let Some(api_token) = get_all_api_tokens()
+        .await?
+        .into_iter()
+        .find(|api_token| api_token.user_id == user_id && api_token.id == id)
+    else {
+        return Err(AppError::DataNotFound(id.to_owned()));
+    };
+
+    let result = persistence::delete(TABLE, &api_token.get_token_hash()).await?;
+
+    Ok(result > 0)
+}
+
+pub async fn delete_api_tokens_of_user(user_id: &str) -> Result<(), AppError> {
+    for api_token in get_all_api_tokens().await? {
+        if api_token.user_id == user_id {
+            persistence::delete(TABLE, &api_token.get_token_hash()).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the API token if it is known and not expired and updates its last use
+pub async fn use_api_token(token: &str) -> Result<Option<ApiToken>, AppError> {
+    let Some(entry) = persistence::get(TABLE, &common::hash_token(token)).await? else {
+        return Ok(None);
+    };
+    let mut api_token = entry_to_api_token(&entry)?;
+
+    let now = Utc::now();
+    if api_token.is_expired(now) {
+        return Ok(None);
+    }
+
+    let recently_used = api_token
+        .last_used
+        .is_some_and(|last_used| now - last_used < Duration::seconds(LAST_USED_PRECISION_SECONDS));
+
+    if !recently_used {
+        api_token.last_used = Some(now);
+        persistence::update(TABLE, api_token_to_entry(&api_token)?).await?;
+    }
+
+    Ok(Some(api_token))
+}
+
+async fn get_all_api_tokens() -> Result<Vec<ApiToken>, AppError> {
+    let entries = persistence::get_all(TABLE, Some("key")).await?;
+
+    let mut list = Vec::new();
+    for entry in &entries {
+        list.push(entry_to_api_token(entry)?);
+    }
+    Ok(list)
+}
@@ -5,14 +5,13 @@ use crate::{
         error::AppError,
         plugin::{monitoring::MonitioringDef, Plugin},
         server::Server,
+        timeseries::TimeSeriesData,
     },
 };
 use config::Config;
 use lazy_static::lazy_static;
 use std::{collections::HashMap, net::IpAddr, sync::RwLock};
 
-use crate::models::token::TokenInfo;
-
 struct ConfigHolder {
     config: Option<Config>,
     crypto_key: Option<String>,
@@ -28,7 +27,6 @@ impl ConfigHolder {
 }
 
 lazy_static! {
-    static ref TOKENS: RwLock<HashMap<String, TokenInfo>> = RwLock::new(HashMap::new());
     static ref CONFIG: RwLock<ConfigHolder> = RwLock::new(ConfigHolder::new());
     static ref PLUGIN_CACHE: RwLock<HashMap<String, Plugin>> = RwLock::new(HashMap::new());
     static ref SERVER_CACHE: RwLock<HashMap<IpAddr, Server>> = RwLock::new(HashMap::new());
@@ -37,6 +35,9 @@ lazy_static! {
         RwLock::new(HashMap::new());
     static ref SERIES_TO_MONITORING: RwLock<HashMap<String, MonitioringDef>> =
         RwLock::new(HashMap::new());
+    /// the latest value per series and identifiers - ie. for the metrics endpoint
+    static ref LATEST_TIMESERIES_DATA: RwLock<HashMap<String, (String, TimeSeriesData)>> =
+        RwLock::new(HashMap::new());
 }
 
 pub fn set_config(config: Config) -> Result<(), AppError> {
@@ -252,52 +253,40 @@ pub fn insert_condition_result(condition_result: ConditionCheckResult) -> Result
     Ok(())
 }
 
-pub fn insert_token(token: &str) -> Result<(), AppError> {
-    let mut store = TOKENS
-        .write()
-        .map_err(|err| AppError::Unknown(format!("Could not get write lock. Error: {}", err)))?;
-
-    store.insert(token.to_owned(), TokenInfo::new());
+pub fn get_monitoring_config_for_series(
+    series_id: &str,
+) -> Result<Option<MonitioringDef>, AppError> {
+    let cache = SERIES_TO_MONITORING
+        .read()
+        .map_err(|err| AppError::Unknown(format!("Could not get read lock. Error: {}", err)))?;
 
-    Ok(())
+    Ok(cache.get(series_id).cloned())
 }
 
-pub fn delete_expired_tokens() -> Result<(), AppError> {
-    let mut store = TOKENS
+pub fn cache_latest_timeseries_data(
+    series_id: &str,
+    data_vec: &[TimeSeriesData],
+) -> Result<(), AppError> {
+    let mut cache = LATEST_TIMESERIES_DATA
         .write()
         .map_err(|err| AppError::Unknown(format!("Could not get write lock. Error: {}", err)))?;
-    log::debug!(
-        "Number of tokens before cleanup of expired tokens {}",
-        store.len()
-    );
-    store.retain(|_k, v| !v.is_expired());
-    log::debug!(
-        "Number of tokens after cleanup of expired tokens {}",
-        store.len()
-    );
 
-    Ok(())
-}
-
-pub fn is_valid_token(token: &str) -> Result<bool, AppError> {
-    let store = TOKENS
-        .read()
-        .map_err(|err| AppError::Unknown(format!("Could not get read lock. Error: {}", err)))?;
-
-    let res = match store.get(token) {
-        Some(found) => !found.is_expired(),
-        None => false,
-    };
+    for data in data_vec {
+        let mut key = format!("{}|{}", series_id, data.identifier.value_as_string());
+        for sub_identifier in &data.sub_identifiers {
+            key.push('|');
+            key.push_str(&sub_identifier.value_as_string());
+        }
 
-    Ok(res)
+        cache.insert(key, (series_id.to_owned(), data.clone()));
+    }
+    Ok(())
 }
 
-pub fn get_monitoring_config_for_series(
-    series_id: &str,
-) -> Result<Option<MonitioringDef>, AppError> {
-    let cache = SERIES_TO_MONITORING
+pub fn get_latest_timeseries_data() -> Result<Vec<(String, TimeSeriesData)>, AppError> {
+    let cache = LATEST_TIMESERIES_DATA
         .read()
         .map_err(|err| AppError::Unknown(format!("Could not get read lock. Error: {}", err)))?;
 
-    Ok(cache.get(series_id).cloned())
+    Ok(cache.values().cloned().collect())
 }
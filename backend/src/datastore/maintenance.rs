@@ -0,0 +1,44 @@
+use crate::models::{error::AppError, maintenance::MaintenanceWindow};
+
+use super::{persistence, Entry};
+
+const TABLE: &str = "maintenance_windows";
+
+fn entry_to_maintenance_window(entry: &Entry) -> Result<MaintenanceWindow, AppError> {
+    serde_json::from_str::<MaintenanceWindow>(entry.value.as_str()).map_err(AppError::from)
+}
+
+fn maintenance_window_to_entry(window: &MaintenanceWindow) -> Result<Entry, AppError> {
+    Ok(Entry {
+        key: window.id.clone(),
+        value: serde_json::to_string(window)?,
+    })
+}
+
+pub async fn insert_or_update_maintenance_window(
+    window: &MaintenanceWindow,
+) -> Result<bool, AppError> {
+    let entry = maintenance_window_to_entry(window)?;
+
+    let count = match persistence::get(TABLE, &window.id).await? {
+        Some(_) => persistence::update(TABLE, entry).await?,
+        None => persistence::insert(TABLE, entry).await?,
+    };
+    Ok(count > 0)
+}
+
+pub async fn delete_maintenance_window(id: &str) -> Result<bool, AppError> {
+    let result = persistence::delete(TABLE, id).await?;
+
+    Ok(result > 0)
+}
+
+pub async fn get_all_maintenance_windows() -> Result<Vec<MaintenanceWindow>, AppError> {
+    let entries = persistence::get_all(TABLE, Some("key")).await?;
+
+    let mut list = Vec::new();
+    for entry in &entries {
+        list.push(entry_to_maintenance_window(entry)?);
+    }
+    Ok(list)
+}
@@ -1,11 +1,15 @@
+mod alerts;
+mod apitokens;
 mod config;
 mod inmemory;
+mod maintenance;
 mod model;
 mod notifications;
 mod persistence;
 mod plugins;
 mod servers;
 mod timeseries;
+mod tokens;
 mod users;
 
 pub use model::migration::Migration;
@@ -14,6 +18,17 @@ pub use timeseries::TimeSeriesPersistence;
 
 pub use std::sync::atomic::{AtomicBool, Ordering};
 
+pub use self::alerts::delete_alert_rule;
+pub use self::alerts::get_alert_rule;
+pub use self::alerts::get_all_alert_rules;
+pub use self::alerts::insert_or_update_alert_rule;
+
+pub use self::apitokens::delete_api_token;
+pub use self::apitokens::delete_api_tokens_of_user;
+pub use self::apitokens::get_api_tokens_of_user;
+pub use self::apitokens::insert_api_token;
+pub use self::apitokens::use_api_token;
+
 pub use self::config::delete_dnsserver;
 pub use self::config::get_all_dnsservers;
 pub use self::config::get_encryption_key;
@@ -46,16 +61,32 @@ pub use self::users::encrypt_users;
 pub use self::users::get_all_users;
 pub use self::users::get_user;
 pub use self::users::insert_user;
+pub use self::users::set_role_of_users_without_role;
 pub use self::users::update_user;
 
+pub use self::maintenance::delete_maintenance_window;
+pub use self::maintenance::get_all_maintenance_windows;
+pub use self::maintenance::insert_or_update_maintenance_window;
+
 pub use self::notifications::get_all_notifications;
+pub use self::notifications::get_notification;
+pub use self::notifications::get_notification_history;
+pub use self::notifications::insert_notification_history;
 pub use self::notifications::insert_notifications;
 pub use self::notifications::insert_or_update_notifications;
 
-pub use self::inmemory::delete_expired_tokens;
-pub use self::inmemory::insert_token;
-pub use self::inmemory::is_valid_token;
+pub use self::tokens::delete_expired_tokens;
+pub use self::tokens::delete_tokens_of_user;
+pub use self::tokens::delete_user_action_tokens_of_user;
+pub use self::tokens::get_token_info;
+pub use self::tokens::has_recent_user_action_token;
+pub use self::tokens::insert_event_token;
+pub use self::tokens::insert_token;
+pub use self::tokens::insert_user_action_token;
+pub use self::tokens::take_event_token;
+pub use self::tokens::take_user_action_token;
 
+pub use self::inmemory::cache_latest_timeseries_data;
 pub use self::inmemory::cache_plugins;
 pub use self::inmemory::cache_servers;
 pub use self::inmemory::cache_status;
@@ -65,6 +96,7 @@ pub use self::inmemory::get_all_plugins;
 pub use self::inmemory::get_all_plugins_map;
 pub use self::inmemory::get_config;
 pub use self::inmemory::get_crypto_key;
+pub use self::inmemory::get_latest_timeseries_data;
 pub use self::inmemory::get_monitoring_config_for_series;
 pub use self::inmemory::get_plugin;
 pub use self::inmemory::get_status;
@@ -73,12 +105,16 @@ pub use self::inmemory::set_config;
 pub use self::inmemory::set_crypto_key;
 
 pub use self::persistence::init_db;
+pub use self::persistence::is_migration_done;
 pub use self::persistence::save_migrations;
 
 pub use self::timeseries::get_timeseriesdb_config;
+pub use self::timeseries::init_timeseries_db;
+pub use self::timeseries::query_timeseries_data;
 pub use self::timeseries::save_timeseries_data;
 
 pub use crate::models::timeseries::TimeSeriesData;
+pub use crate::models::timeseries::TimeSeriesQuery;
 
 pub fn init_cache() {
     if let Ok(number) = plugins::init_cache() {
@@ -1,8 +1,15 @@
-use crate::models::{error::AppError, plugin::notification::Notifications};
+use crate::{
+    event_handling::{self, EventSource},
+    models::{
+        error::AppError,
+        plugin::notification::{NotificationHistoryPage, Notifications, ResolvedNotification},
+    },
+};
 
 use super::{persistence, Entry};
 
 const TABLE: &str = "notifications";
+const HISTORY_TABLE: &str = "notification_history";
 
 fn entry_to_notifications(entry: &Entry) -> Result<Notifications, AppError> {
     serde_json::from_str::<Notifications>(entry.value.as_str()).map_err(AppError::from)
@@ -33,10 +40,16 @@ pub async fn insert_or_update_notifications(
     let existing = get_existing(&notifications).await?;
 
     if existing.is_none() {
-        updated |= insert_notifications(notifications).await?;
+        updated |= insert_notifications(notifications.clone()).await?;
     } else {
-        updated |= update_notifications(notifications).await?;
+        updated |= update_notifications(notifications.clone()).await?;
     }
+
+    event_handling::handle_object_change(
+        Some(Box::new(notifications)),
+        existing.map(|e| Box::new(e) as Box<dyn EventSource>),
+    )?;
+
     Ok(updated)
 }
 
@@ -92,3 +105,53 @@ pub async fn get_notification(ipaddress: &str) -> Result<Notifications, AppError
         None => Err(AppError::DataNotFound(ipaddress.to_owned())),
     }
 }
+
+/// Keeps the resolved notification in the history. The key starts with the IP address so that the history can be filtered by server
+pub async fn insert_notification_history(
+    resolved_notification: &ResolvedNotification,
+) -> Result<bool, AppError> {
+    let entry = Entry {
+        key: format!(
+            "{}|{}",
+            resolved_notification.ipaddress,
+            uuid::Uuid::new_v4()
+        ),
+        value: serde_json::to_string(resolved_notification)?,
+    };
+
+    let count = persistence::insert(HISTORY_TABLE, entry).await?;
+    Ok(count > 0)
+}
+
+/// Returns a page of the resolved notifications - newest first
+pub async fn get_notification_history(
+    ipaddress: Option<&str>,
+    page: u32,
+    page_size: u32,
+) -> Result<NotificationHistoryPage, AppError> {
+    let key_prefix = ipaddress
+        .map(|ipaddress| format!("{}|", ipaddress))
+        .unwrap_or_default();
+
+    let total = persistence::count(HISTORY_TABLE, &key_prefix).await?;
+    let entries = persistence::get_page(
+        HISTORY_TABLE,
+        &key_prefix,
+        "rowid DESC",
+        page_size,
+        page.saturating_mul(page_size),
+    )
+    .await?;
+
+    let mut list = Vec::new();
+    for entry in entries {
+        list.push(serde_json::from_str::<ResolvedNotification>(&entry.value)?);
+    }
+
+    Ok(NotificationHistoryPage {
+        page,
+        page_size,
+        total,
+        list,
+    })
+}
@@ -55,6 +55,21 @@ pub async fn init_db() -> Result<(), AppError> {
         ("dns_servers", vec![("key", "TEXT"), ("value", "TEXT")]),
         ("notifications", vec![("key", "TEXT"), ("value", "TEXT")]),
         ("alarms", vec![("key", "TEXT"), ("value", "TEXT")]),
+        (
+            "notification_history",
+            vec![("key", "TEXT"), ("value", "TEXT")],
+        ),
+        (
+            "maintenance_windows",
+            vec![("key", "TEXT"), ("value", "TEXT")],
+        ),
+        ("api_tokens", vec![("key", "TEXT"), ("value", "TEXT")]),
+        ("tokens", vec![("key", "TEXT"), ("value", "TEXT")]),
+        ("event_tokens", vec![("key", "TEXT"), ("value", "TEXT")]),
+        (
+            "user_action_tokens",
+            vec![("key", "TEXT"), ("value", "TEXT")],
+        ),
     ])
     .await?;
 
@@ -64,6 +79,12 @@ pub async fn init_db() -> Result<(), AppError> {
     create_index(vec![("dns_servers", true, vec!["key"])]).await?;
     create_index(vec![("notifications", true, vec!["key"])]).await?;
     create_index(vec![("alarms", true, vec!["key"])]).await?;
+    create_index(vec![("notification_history", true, vec!["key"])]).await?;
+    create_index(vec![("maintenance_windows", true, vec!["key"])]).await?;
+    create_index(vec![("api_tokens", true, vec!["key"])]).await?;
+    create_index(vec![("tokens", true, vec!["key"])]).await?;
+    create_index(vec![("event_tokens", true, vec!["key"])]).await?;
+    create_index(vec![("user_action_tokens", true, vec!["key"])]).await?;
     Ok(())
 }
 
@@ -114,6 +135,19 @@ pub async fn get_migration(name: &str) -> Result<Migration, AppError> {
     Ok(result)
 }
 
+pub async fn is_migration_done(name: &str) -> Result<bool, AppError> {
+    let mut transaction = PERSISTENCE.lock().await.pool.begin().await?;
+
+    let select = "SELECT * from migration where name = ?";
+    let result: Option<Migration> = sqlx::query_as(select)
+        .bind(name)
+        .fetch_optional(&mut transaction)
+        .await?;
+
+    transaction.commit().await?;
+    Ok(result.is_some())
+}
+
 #[allow(dead_code)]
 pub async fn save_migration(migration: Migration) -> Result<u64, AppError> {
     let mut transaction = PERSISTENCE.lock().await.pool.begin().await?;
@@ -160,6 +194,41 @@ pub async fn get_all(table: &str, order_by: Option<&str>) -> Result<Vec<Entry>,
     Ok(result)
 }
 
+/// Returns the entries whose key starts with the given prefix. Limit and offset allow to page through big tables
+pub async fn get_page(
+    table: &str,
+    key_prefix: &str,
+    order_by: &str,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<Entry>, AppError> {
+    let mut transaction = PERSISTENCE.lock().await.pool.begin().await?;
+
+    let select = get_select_page_statement(table, order_by);
+
+    let result: Vec<Entry> = sqlx::query_as(select.as_str())
+        .bind(format!("{}%", key_prefix))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&mut transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(result)
+}
+
+pub async fn count(table: &str, key_prefix: &str) -> Result<i64, AppError> {
+    let mut transaction = PERSISTENCE.lock().await.pool.begin().await?;
+
+    let select = get_count_statement(table);
+
+    let result: i64 = sqlx::query_scalar(select.as_str())
+        .bind(format!("{}%", key_prefix))
+        .fetch_one(&mut transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(result)
+}
+
 pub async fn get(table: &str, key: &str) -> Result<Option<Entry>, AppError> {
     let mut transaction = PERSISTENCE.lock().await.pool.begin().await?;
 
@@ -245,6 +314,17 @@ fn get_select_all_statement(table: &str, order_by: Option<&str>) -> String {
     format!("SELECT key, value FROM {}{}", table, str_to_add)
 }
 
+fn get_select_page_statement(table: &str, order_by: &str) -> String {
+    format!(
+        "SELECT key, value FROM {} WHERE key LIKE ? ORDER BY {} LIMIT ? OFFSET ?",
+        table, order_by
+    )
+}
+
+fn get_count_statement(table: &str) -> String {
+    format!("SELECT count(*) FROM {} WHERE key LIKE ?", table)
+}
+
 fn get_select_statement(table: &str) -> String {
     format!("SELECT key, value FROM {} WHERE key = ?", table)
 }
@@ -222,6 +222,7 @@ mod tests {
             },
             credentials: vec![],
             params: vec![],
+            authentication: None,
             data: vec![],
             notifications: vec![],
             actions: vec![ActionDef {
@@ -297,6 +298,7 @@ mod tests {
             },
             credentials: vec![],
             params: vec![],
+            authentication: None,
             data: vec![],
             notifications: vec![],
             actions: vec![ActionDef {
@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use questdb::ingress::Buffer;
+
+use super::{SendError, SeriesValue, Sink};
+
+/// Writes the values in the influx line protocol to the v2 write API - ie. of InfluxDB or VictoriaMetrics
+pub struct InfluxSink {
+    url: String,
+    token: Option<String>,
+    org: String,
+    bucket: String,
+}
+
+impl InfluxSink {
+    pub fn new(url: &str, token: Option<String>, org: String, bucket: String) -> Self {
+        InfluxSink {
+            url: format!("{}/api/v2/write", url.trim_end_matches('/')),
+            token,
+            org,
+            bucket,
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for InfluxSink {
+    fn get_name(&self) -> &str {
+        "influx"
+    }
+
+    async fn send(&self, values: &[SeriesValue]) -> Result<(), SendError> {
+        let mut buffer = Buffer::new();
+        for (series_id, data) in values {
+            data.apply(series_id, &mut buffer)?;
+        }
+
+        let mut request = super::make_client()?
+            .post(&self.url)
+            .query(&[
+                ("org", self.org.as_str()),
+                ("bucket", self.bucket.as_str()),
+                ("precision", "ns"),
+            ])
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body(buffer.as_str().to_owned());
+
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Token {}", token));
+        }
+
+        super::execute(request).await
+    }
+}
@@ -0,0 +1,402 @@
+mod influx;
+mod remote_write;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use reqwest::StatusCode;
+use tokio::sync::Mutex;
+
+use crate::models::{error::AppError, timeseries::TimeSeriesData};
+
+const TIMEOUT_SECONDS: u64 = 5;
+/// values that are kept per sink while it is not reachable - the oldest values are dropped first
+const MAX_QUEUED_VALUES: usize = 100_000;
+/// the values are sent in chunks of this size
+const MAX_BATCH_SIZE: usize = 5_000;
+const MIN_RETRY_DELAY_SECONDS: i64 = 5;
+const MAX_RETRY_DELAY_SECONDS: i64 = 300;
+
+/// a value together with the id of its series
+type SeriesValue = (String, TimeSeriesData);
+
+lazy_static! {
+    /// the lock of the map is only held to get the queue of a sink, the queue itself is not locked while sending
+    static ref QUEUES: Mutex<HashMap<String, Arc<Mutex<SinkQueue>>>> = Mutex::new(HashMap::new());
+}
+
+#[async_trait]
+trait Sink {
+    fn get_name(&self) -> &str;
+
+    async fn send(&self, values: &[SeriesValue]) -> Result<(), SendError>;
+}
+
+#[derive(Debug)]
+enum SendError {
+    /// the sink rejected the values (4xx) or they could not be encoded - sending them again would fail again
+    Rejected(String),
+    /// the sink was not reachable, timed out or had an internal error - the values are sent again later
+    Unavailable(String),
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SendError::Rejected(err) => write!(f, "Rejected: {}", err),
+            SendError::Unavailable(err) => write!(f, "Unavailable: {}", err),
+        }
+    }
+}
+
+/// errors while building the request don't depend on the sink, so they would occur again
+impl From<AppError> for SendError {
+    fn from(err: AppError) -> Self {
+        SendError::Rejected(format!("{}", err))
+    }
+}
+
+/// the values that could not be sent yet
+#[derive(Default)]
+struct SinkQueue {
+    values: VecDeque<SeriesValue>,
+    failures: u32,
+    retry_after: Option<DateTime<Utc>>,
+    /// a chunk is currently sent - the values are only sent by one task at a time to keep their order
+    sending: bool,
+}
+
+impl SinkQueue {
+    fn push(&mut self, sink_name: &str, series_id: &str, data_vec: &[TimeSeriesData]) {
+        self.values.extend(
+            data_vec
+                .iter()
+                .map(|data| (series_id.to_owned(), data.clone())),
+        );
+        self.limit(sink_name);
+    }
+
+    fn limit(&mut self, sink_name: &str) {
+        if self.values.len() > MAX_QUEUED_VALUES {
+            let dropped = self.values.len() - MAX_QUEUED_VALUES;
+            self.values.drain(..dropped);
+
+            log::warn!(
+                "Dropped {} time series values for {} since it was not reachable for too long",
+                dropped,
+                sink_name
+            );
+        }
+    }
+
+    /// the next chunk to send - None if there is nothing to send, the retry delay is not over yet or the values are sent already
+    fn take_chunk(&mut self, now: DateTime<Utc>) -> Option<Vec<SeriesValue>> {
+        if self.sending
+            || self.values.is_empty()
+            || self
+                .retry_after
+                .is_some_and(|retry_after| now < retry_after)
+        {
+            return None;
+        }
+
+        let size = self.values.len().min(MAX_BATCH_SIZE);
+        self.sending = true;
+        Some(self.values.drain(..size).collect())
+    }
+
+    /// Handles the result of sending the chunk. Rejected chunks are dropped, if the sink is not available
+    /// the chunk is queued again and the next try is delayed with an increasing delay
+    fn complete(
+        &mut self,
+        sink_name: &str,
+        chunk: Vec<SeriesValue>,
+        result: Result<(), SendError>,
+        now: DateTime<Utc>,
+    ) {
+        self.sending = false;
+
+        match result {
+            Ok(()) => {
+                self.failures = 0;
+                self.retry_after = None;
+            }
+            Err(SendError::Rejected(err)) => {
+                self.failures = 0;
+                self.retry_after = None;
+
+                log::error!(
+                    "Dropped {} time series values since {} did not accept them. Error: {}",
+                    chunk.len(),
+                    sink_name,
+                    err
+                );
+            }
+            Err(SendError::Unavailable(err)) => {
+                self.failures += 1;
+
+                let delay = (MIN_RETRY_DELAY_SECONDS << (self.failures - 1).min(10))
+                    .min(MAX_RETRY_DELAY_SECONDS);
+                self.retry_after = Some(now + chrono::Duration::seconds(delay));
+
+                for value in chunk.into_iter().rev() {
+                    self.values.push_front(value);
+                }
+                self.limit(sink_name);
+
+                log::warn!(
+                    "Could not forward time series data to {} - retrying in {} seconds with {} queued values. Error: {}",
+                    sink_name,
+                    delay,
+                    self.values.len(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+/// Sends the queued values in chunks until the queue is empty or the sink is not available.
+/// The queue is not locked while a chunk is sent, so new values can be queued in the meantime
+async fn flush(queue: &Mutex<SinkQueue>, sink: &(dyn Sink + Send + Sync)) {
+    loop {
+        let Some(chunk) = queue.lock().await.take_chunk(Utc::now()) else {
+            return;
+        };
+
+        let result = sink.send(&chunk).await;
+
+        queue
+            .lock()
+            .await
+            .complete(sink.get_name(), chunk, result, Utc::now());
+    }
+}
+
+/// Forwards the values to the additionally configured sinks (Influx v2 and/or Prometheus remote write).
+/// The values are queued and sent in the background, so that a slow sink doesn't delay the monitoring. Errors are only logged
+pub async fn forward_timeseries_data(series_id: &str, data_vec: &[TimeSeriesData]) {
+    let sinks = match get_configured_sinks() {
+        Ok(sinks) => sinks,
+        Err(err) => {
+            log::error!(
+                "Could not read config of the time series forwarding: {}",
+                err
+            );
+            return;
+        }
+    };
+
+    if sinks.is_empty() || data_vec.is_empty() {
+        return;
+    }
+
+    for sink in sinks {
+        let queue = QUEUES
+            .lock()
+            .await
+            .entry(sink.get_name().to_owned())
+            .or_default()
+            .clone();
+
+        queue
+            .lock()
+            .await
+            .push(sink.get_name(), series_id, data_vec);
+
+        tokio::spawn(async move {
+            flush(&queue, sink.as_ref()).await;
+        });
+    }
+}
+
+/// Sends the request. Client errors are rejections of the values - except for rate limiting and timeouts,
+/// which are retried like server errors and connection problems
+async fn execute(request: reqwest::RequestBuilder) -> Result<(), SendError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|err| SendError::Unavailable(format!("{}", err)))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let message = format!("{} {}", status, response.text().await.unwrap_or_default());
+    if status.is_client_error()
+        && status != StatusCode::TOO_MANY_REQUESTS
+        && status != StatusCode::REQUEST_TIMEOUT
+    {
+        Err(SendError::Rejected(message))
+    } else {
+        Err(SendError::Unavailable(message))
+    }
+}
+
+fn get_configured_sinks() -> Result<Vec<Box<dyn Sink + Send + Sync>>, AppError> {
+    let config = crate::datastore::get_config()?;
+    let get = |key: &str| {
+        config
+            .get_string(key)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    };
+
+    let mut sinks: Vec<Box<dyn Sink + Send + Sync>> = Vec::new();
+
+    if let Some(url) = get("timeseries_forward_influx_url") {
+        sinks.push(Box::new(influx::InfluxSink::new(
+            &url,
+            get("timeseries_forward_influx_token"),
+            get("timeseries_forward_influx_org").unwrap_or_default(),
+            get("timeseries_forward_influx_bucket").unwrap_or_default(),
+        )));
+    }
+
+    if let Some(url) = get("timeseries_forward_remote_write_url") {
+        sinks.push(Box::new(remote_write::RemoteWriteSink::new(
+            &url,
+            get("timeseries_forward_remote_write_token"),
+        )));
+    }
+    Ok(sinks)
+}
+
+fn make_client() -> Result<reqwest::Client, AppError> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(TIMEOUT_SECONDS))
+        .build()
+        .map_err(AppError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+        time::SystemTime,
+    };
+
+    use crate::{
+        common,
+        models::timeseries::{TimeSeriesValue, Timestamp},
+    };
+
+    use super::*;
+
+    struct TestSink {
+        available: AtomicBool,
+        accepting: AtomicBool,
+        received: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Sink for TestSink {
+        fn get_name(&self) -> &str {
+            "test"
+        }
+
+        async fn send(&self, values: &[SeriesValue]) -> Result<(), SendError> {
+            if !self.available.load(Ordering::SeqCst) {
+                return Err(SendError::Unavailable("not available".to_owned()));
+            }
+            if !self.accepting.load(Ordering::SeqCst) {
+                return Err(SendError::Rejected("400 Bad Request".to_owned()));
+            }
+            self.received.fetch_add(values.len(), Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn test_data() -> TimeSeriesData {
+        TimeSeriesData {
+            identifier: TimeSeriesValue::Symbol(
+                common::IDENTIFIER.to_owned(),
+                "192.168.178.2".to_owned(),
+            ),
+            sub_identifiers: Vec::new(),
+            value: TimeSeriesValue::Int(common::VALUE.to_owned(), 1),
+            timestamp: Timestamp::SysTime(SystemTime::now()),
+        }
+    }
+
+    #[test]
+    fn test_queue_retry() {
+        let data = test_data();
+        let now = Utc::now();
+
+        let mut queue = SinkQueue::default();
+        queue.push("test", "server_status", &[data.clone(), data.clone()]);
+
+        let chunk = queue.take_chunk(now).expect("chunk expected");
+        // only one chunk is sent at a time
+        assert!(queue.take_chunk(now).is_none());
+        queue.complete(
+            "test",
+            chunk,
+            Err(SendError::Unavailable("not available".to_owned())),
+            now,
+        );
+
+        assert_eq!(queue.values.len(), 2);
+        assert_eq!(queue.failures, 1);
+        assert_eq!(queue.retry_after, Some(now + chrono::Duration::seconds(5)));
+
+        // not retried before the delay is over
+        queue.push("test", "server_status", &[data]);
+        assert!(queue
+            .take_chunk(now + chrono::Duration::seconds(1))
+            .is_none());
+        assert_eq!(queue.values.len(), 3);
+
+        let chunk = queue
+            .take_chunk(now + chrono::Duration::seconds(5))
+            .expect("chunk expected");
+        assert_eq!(chunk.len(), 3);
+        queue.complete("test", chunk, Ok(()), now + chrono::Duration::seconds(5));
+
+        assert!(queue.values.is_empty());
+        assert_eq!(queue.failures, 0);
+        assert_eq!(queue.retry_after, None);
+    }
+
+    #[tokio::test]
+    async fn test_flush() {
+        let sink = TestSink {
+            available: AtomicBool::new(false),
+            accepting: AtomicBool::new(false),
+            received: AtomicUsize::new(0),
+        };
+
+        let mut queue = SinkQueue::default();
+        queue.push("test", "server_status", &[test_data(), test_data()]);
+        let queue = Mutex::new(queue);
+
+        flush(&queue, &sink).await;
+        assert_eq!(queue.lock().await.values.len(), 2);
+        assert_eq!(queue.lock().await.failures, 1);
+
+        // rejected values are dropped instead of being retried
+        queue.lock().await.retry_after = None;
+        sink.available.store(true, Ordering::SeqCst);
+        flush(&queue, &sink).await;
+        assert!(queue.lock().await.values.is_empty());
+        assert_eq!(queue.lock().await.failures, 0);
+
+        sink.accepting.store(true, Ordering::SeqCst);
+        queue
+            .lock()
+            .await
+            .push("test", "server_status", &[test_data()]);
+        flush(&queue, &sink).await;
+        assert!(queue.lock().await.values.is_empty());
+        assert_eq!(sink.received.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+
+use super::{SendError, SeriesValue, Sink};
+
+/// all values are written as this metric with the series id as label - like on the metrics endpoint
+const METRIC_NAME: &str = "server_manager_monitoring_value";
+
+/// the sorted labels of a time series
+type Labels = Vec<(String, String)>;
+
+/// Sends the values with the Prometheus remote write protocol (snappy compressed protobuf)
+pub struct RemoteWriteSink {
+    url: String,
+    token: Option<String>,
+}
+
+impl RemoteWriteSink {
+    pub fn new(url: &str, token: Option<String>) -> Self {
+        RemoteWriteSink {
+            url: url.to_owned(),
+            token,
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for RemoteWriteSink {
+    fn get_name(&self) -> &str {
+        "remote_write"
+    }
+
+    async fn send(&self, values: &[SeriesValue]) -> Result<(), SendError> {
+        let body = snap::raw::Encoder::new()
+            .compress_vec(&encode_write_request(&group_by_labels(values)))
+            .map_err(|err| SendError::Rejected(format!("Could not compress data: {}", err)))?;
+
+        let mut request = super::make_client()?
+            .post(&self.url)
+            .header("Content-Type", "application/x-protobuf")
+            .header("Content-Encoding", "snappy")
+            .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+            .body(body);
+
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        super::execute(request).await
+    }
+}
+
+/// the samples (timestamp in milliseconds, value) per time series - string values cannot be sent and are skipped
+fn group_by_labels(values: &[SeriesValue]) -> BTreeMap<Labels, Vec<(i64, f64)>> {
+    let mut map: BTreeMap<Labels, Vec<(i64, f64)>> = BTreeMap::new();
+
+    for (series_id, data) in values {
+        let Some(value) = data.value.as_f64() else {
+            continue;
+        };
+
+        let mut labels: Labels = vec![
+            ("__name__".to_owned(), METRIC_NAME.to_owned()),
+            ("series_id".to_owned(), series_id.clone()),
+        ];
+        labels.extend(
+            data.get_labels()
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value)),
+        );
+        labels.sort();
+
+        map.entry(labels)
+            .or_default()
+            .push((data.timestamp.as_micros() / 1000, value));
+    }
+
+    for samples in map.values_mut() {
+        samples.sort_by_key(|(timestamp, _)| *timestamp);
+    }
+    map
+}
+
+/// WriteRequest { repeated TimeSeries timeseries = 1; }
+/// TimeSeries { repeated Label labels = 1; repeated Sample samples = 2; }
+/// Label { string name = 1; string value = 2; }
+/// Sample { double value = 1; int64 timestamp = 2; }
+fn encode_write_request(series: &BTreeMap<Labels, Vec<(i64, f64)>>) -> Vec<u8> {
+    let mut request = Vec::new();
+
+    for (labels, samples) in series {
+        let mut time_series = Vec::new();
+
+        for (name, value) in labels {
+            let mut label = Vec::new();
+            encode_bytes(&mut label, 1, name.as_bytes());
+            encode_bytes(&mut label, 2, value.as_bytes());
+
+            encode_bytes(&mut time_series, 1, &label);
+        }
+
+        for (timestamp, value) in samples {
+            let mut sample = Vec::new();
+            encode_key(&mut sample, 1, 1);
+            sample.extend_from_slice(&value.to_le_bytes());
+            encode_key(&mut sample, 2, 0);
+            encode_varint(&mut sample, *timestamp as u64);
+
+            encode_bytes(&mut time_series, 2, &sample);
+        }
+
+        encode_bytes(&mut request, 1, &time_series);
+    }
+    request
+}
+
+fn encode_key(buf: &mut Vec<u8>, field: u32, wire_type: u32) {
+    encode_varint(buf, ((field << 3) | wire_type) as u64);
+}
+
+fn encode_bytes(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    encode_key(buf, field, 2);
+    encode_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_write_request() {
+        let mut series = BTreeMap::new();
+        series.insert(
+            vec![("__name__".to_owned(), "a".to_owned())],
+            vec![(300, 1.0)],
+        );
+
+        let mut expected: Vec<u8> = vec![0x0a, 29, 0x0a, 13, 0x0a, 8];
+        expected.extend_from_slice(b"__name__");
+        expected.extend_from_slice(&[0x12, 1, b'a']);
+        expected.extend_from_slice(&[0x12, 12, 0x09]);
+        expected.extend_from_slice(&1.0f64.to_le_bytes());
+        expected.extend_from_slice(&[0x10, 0xac, 0x02]);
+
+        assert_eq!(encode_write_request(&series), expected);
+    }
+}
@@ -0,0 +1,116 @@
+mod forwarding;
+mod questdb;
+mod sqlite;
+
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+
+use crate::models::{
+    error::AppError,
+    plugin::monitoring::TimeSeriesResponseData,
+    timeseries::{TimeSeriesData, TimeSeriesQuery},
+};
+
+pub use questdb::get_timeseriesdb_config;
+pub use sqlite::init_timeseries_db;
+
+#[async_trait]
+trait TimeSeriesStore: Debug {
+    async fn save(
+        &mut self,
+        series_id: &str,
+        data_vec: Vec<TimeSeriesData>,
+    ) -> Result<(), AppError>;
+
+    async fn query(&self, query: &TimeSeriesQuery) -> Result<TimeSeriesResponseData, AppError>;
+
+    fn clone_box(&self) -> Box<dyn TimeSeriesStore + Send + Sync>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSeriesBackend {
+    QuestDB,
+    /// embedded store in a separate SQLite file - for small installations without a QuestDB container
+    SQLite,
+}
+
+/// The backend can be set with TIMESERIES_DB_TYPE (questdb or sqlite).
+/// If it is not set, QuestDB is used as long as a host is configured for it
+pub fn get_timeseries_backend() -> Result<TimeSeriesBackend, AppError> {
+    let config = super::get_config()?;
+
+    match config
+        .get_string("timeseries_db_type")
+        .unwrap_or_default()
+        .to_lowercase()
+        .as_str()
+    {
+        "questdb" => Ok(TimeSeriesBackend::QuestDB),
+        "sqlite" => Ok(TimeSeriesBackend::SQLite),
+        "" => match config.get_string("timeseries_db_host") {
+            Ok(host) if !host.trim().is_empty() => Ok(TimeSeriesBackend::QuestDB),
+            _ => Ok(TimeSeriesBackend::SQLite),
+        },
+        other => Err(AppError::InvalidArgument(
+            "timeseries_db_type".to_owned(),
+            Some(other.to_owned()),
+        )),
+    }
+}
+
+#[derive(Debug)]
+pub struct TimeSeriesPersistence {
+    store: Box<dyn TimeSeriesStore + Send + Sync>,
+}
+
+impl Clone for TimeSeriesPersistence {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone_box(),
+        }
+    }
+}
+
+pub async fn save_timeseries_data(
+    timeseries_persistence: &mut TimeSeriesPersistence,
+    series_id: &str,
+    data_vec: Vec<TimeSeriesData>,
+) -> Result<(), AppError> {
+    let result = timeseries_persistence
+        .save(series_id, data_vec.clone())
+        .await;
+
+    forwarding::forward_timeseries_data(series_id, &data_vec).await;
+
+    result
+}
+
+pub async fn query_timeseries_data(
+    query: &TimeSeriesQuery,
+) -> Result<TimeSeriesResponseData, AppError> {
+    TimeSeriesPersistence::new().await?.query(query).await
+}
+
+impl TimeSeriesPersistence {
+    pub async fn new() -> Result<TimeSeriesPersistence, AppError> {
+        let store: Box<dyn TimeSeriesStore + Send + Sync> = match get_timeseries_backend()? {
+            TimeSeriesBackend::QuestDB => Box::new(questdb::QuestDBStore::new()?),
+            TimeSeriesBackend::SQLite => Box::new(sqlite::SQLiteStore::new()),
+        };
+
+        Ok(TimeSeriesPersistence { store })
+    }
+
+    pub async fn save(
+        &mut self,
+        series_id: &str,
+        data_vec: Vec<TimeSeriesData>,
+    ) -> Result<(), AppError> {
+        self.store.save(series_id, data_vec).await
+    }
+
+    pub async fn query(&self, query: &TimeSeriesQuery) -> Result<TimeSeriesResponseData, AppError> {
+        self.store.query(query).await
+    }
+}
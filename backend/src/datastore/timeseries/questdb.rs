@@ -0,0 +1,222 @@
+use async_trait::async_trait;
+use questdb::ingress::{Buffer, Sender, SenderBuilder};
+
+use crate::{
+    common,
+    models::{
+        error::AppError,
+        plugin::monitoring::TimeSeriesResponseData,
+        timeseries::{TimeSeriesData, TimeSeriesQuery},
+    },
+};
+
+use super::TimeSeriesStore;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.6fZ";
+
+#[derive(Clone, Debug)]
+pub struct QuestDBConfig {
+    host: String,
+    influx_port: u16,
+    http_port: u16,
+}
+
+impl QuestDBConfig {
+    pub fn get_host(&self) -> String {
+        self.host.clone()
+    }
+    pub fn get_http_port(&self) -> u16 {
+        self.http_port
+    }
+}
+
+impl QuestDBConfig {
+    fn new() -> Result<Self, AppError> {
+        let config = crate::datastore::get_config()?;
+
+        let timeseries_db_host = config.get_string("timeseries_db_host")?;
+
+        let timeseries_influx_port = config.get_int("timeseries_db_influx_port")?;
+
+        let timeseries_http_port = config.get_int("timeseries_db_http_port")?;
+
+        Ok(QuestDBConfig {
+            host: timeseries_db_host,
+            influx_port: timeseries_influx_port as u16,
+            http_port: timeseries_http_port as u16,
+        })
+    }
+}
+
+pub fn get_timeseriesdb_config() -> Result<QuestDBConfig, AppError> {
+    QuestDBConfig::new()
+}
+
+/// Writes the data with the influx line protocol and queries it with the REST API of QuestDB
+#[derive(Debug)]
+pub struct QuestDBStore {
+    host: String,
+    port: u16,
+    sender: Option<Sender>,
+}
+
+impl QuestDBStore {
+    pub fn new() -> Result<QuestDBStore, AppError> {
+        let config = QuestDBConfig::new()?;
+
+        Ok(QuestDBStore {
+            host: config.host,
+            port: config.influx_port,
+            sender: None,
+        })
+    }
+
+    fn create_sender(&mut self) -> Result<(), AppError> {
+        self.sender = Some(
+            SenderBuilder::new(self.host.clone(), self.port)
+                .connect()
+                .map_err(|err| AppError::DatabaseError(format!("{}", err)))?,
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TimeSeriesStore for QuestDBStore {
+    async fn save(
+        &mut self,
+        series_id: &str,
+        data_vec: Vec<TimeSeriesData>,
+    ) -> Result<(), AppError> {
+        if data_vec.is_empty() {
+            return Ok(());
+        }
+
+        if self.sender.is_none() {
+            self.create_sender()?;
+        }
+
+        if let Some(sender) = self.sender.as_mut() {
+            for data in data_vec {
+                let mut buffer = Buffer::new();
+                data.apply(series_id, &mut buffer)?;
+
+                sender
+                    .flush(&mut buffer)
+                    .map_err(|err| AppError::DatabaseError(format!("{}", err)))?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn query(&self, query: &TimeSeriesQuery) -> Result<TimeSeriesResponseData, AppError> {
+        let select = create_select(query);
+
+        log::trace!("querying QuestDB: {}", select);
+
+        let result = common::execute_timeseries_db_query(&[("query", select.as_str())]).await?;
+
+        log::trace!("response from db: {}", result);
+
+        serde_json::from_str::<TimeSeriesResponseData>(&result).map_err(AppError::from)
+    }
+
+    fn clone_box(&self) -> Box<dyn TimeSeriesStore + Send + Sync> {
+        Box::new(QuestDBStore {
+            host: self.host.clone(),
+            port: self.port,
+            sender: None,
+        })
+    }
+}
+
+fn create_select(query: &TimeSeriesQuery) -> String {
+    let mut cols: Vec<String> = query.group_by.clone();
+
+    for aggregation in &query.aggregations {
+        cols.push(format!("{}({})", aggregation.as_str(), common::VALUE));
+    }
+
+    let mut conditions: Vec<String> = vec![match query.to {
+        Some(to) => format!(
+            "{} BETWEEN '{}' AND '{}'",
+            common::TIMESTAMP,
+            query.from.format(TIMESTAMP_FORMAT),
+            to.format(TIMESTAMP_FORMAT)
+        ),
+        None => format!(
+            "{} > '{}'",
+            common::TIMESTAMP,
+            query.from.format(TIMESTAMP_FORMAT)
+        ),
+    }];
+
+    for (column, value) in &query.filters {
+        conditions.push(format!("{} = '{}'", column, value.replace('\'', "''")));
+    }
+
+    // the columns that are not aggregated are grouped implicitly
+    match query.bucket_seconds {
+        Some(bucket_seconds) => {
+            cols.push(common::TIMESTAMP.to_owned()); // always the last column
+
+            format!(
+                "select {} from {} where {} SAMPLE BY {}s FILL(NONE) ORDER BY {} asc",
+                cols.join(","),
+                query.series_id,
+                conditions.join(" AND "),
+                bucket_seconds,
+                common::TIMESTAMP
+            )
+        }
+        None => format!(
+            "select {} from {} where {}",
+            cols.join(","),
+            query.series_id,
+            conditions.join(" AND ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::models::plugin::monitoring::Aggregation;
+
+    use super::*;
+
+    #[test]
+    fn test_create_select() {
+        let from = Utc.with_ymd_and_hms(2023, 9, 1, 2, 0, 0).unwrap();
+
+        let mut query = TimeSeriesQuery {
+            series_id: "disk_usage".to_owned(),
+            group_by: vec![
+                common::IDENTIFIER.to_owned(),
+                common::SUB_IDENTIFIER_2.to_owned(),
+            ],
+            filters: vec![(common::SUB_IDENTIFIER_2.to_owned(), "free".to_owned())],
+            from,
+            to: None,
+            aggregations: vec![Aggregation::Min, Aggregation::Last],
+            bucket_seconds: None,
+        };
+
+        assert_eq!(
+            create_select(&query),
+            "select Identifier,Sub_Identifier2,min(Value),last(Value) from disk_usage \
+            where timestamp > '2023-09-01T02:00:00.000000Z' AND Sub_Identifier2 = 'free'"
+        );
+
+        query.to = Some(from + chrono::Duration::days(1));
+        query.bucket_seconds = Some(300);
+
+        assert_eq!(
+            create_select(&query),
+            "select Identifier,Sub_Identifier2,min(Value),last(Value),timestamp from disk_usage \
+            where timestamp BETWEEN '2023-09-01T02:00:00.000000Z' AND '2023-09-02T02:00:00.000000Z' AND Sub_Identifier2 = 'free' \
+            SAMPLE BY 300s FILL(NONE) ORDER BY timestamp asc"
+        );
+    }
+}
@@ -0,0 +1,448 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use lazy_static::lazy_static;
+use serde_json::Value;
+use sqlx::{sqlite::SqliteConnectOptions, ConnectOptions, Pool, Sqlite, SqlitePool};
+
+use crate::{
+    common,
+    models::{
+        error::AppError,
+        plugin::monitoring::{
+            Aggregation, TimeSeriesResponseColumnMetaData, TimeSeriesResponseData,
+        },
+        timeseries::{TimeSeriesData, TimeSeriesQuery, TimeSeriesValue},
+    },
+};
+
+use super::TimeSeriesStore;
+
+const TABLE: &str = "timeseries";
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.6fZ";
+
+/// the identifier columns of the table - named like the columns of the QuestDB tables so that the queries work for both stores
+const IDENTIFIER_COLUMNS: [&str; 3] = [
+    common::IDENTIFIER,
+    common::SUB_IDENTIFIER,
+    common::SUB_IDENTIFIER_2,
+];
+
+lazy_static! {
+    static ref TIMESERIES_POOL: Pool<Sqlite> = {
+        let db_url = format!("sqlite:{}?mode=rwc", common::TIMESERIES_DB_FILENAME);
+
+        let mut options = SqliteConnectOptions::from_str(&db_url)
+            .expect("Could not get time series db url")
+            .create_if_missing(true);
+
+        SqlitePool::connect_lazy_with(options.disable_statement_logging().to_owned())
+    };
+}
+
+type Row = (Option<String>, Option<String>, Option<String>, f64, i64);
+
+/// Creates the table of the embedded store - only if it is used instead of QuestDB
+pub async fn init_timeseries_db() -> Result<(), AppError> {
+    if super::get_timeseries_backend()? != super::TimeSeriesBackend::SQLite {
+        return Ok(());
+    }
+    log::info!(
+        "No time series db configured - using the embedded store {}",
+        common::TIMESERIES_DB_FILENAME
+    );
+
+    create_table(&TIMESERIES_POOL).await
+}
+
+async fn create_table(pool: &Pool<Sqlite>) -> Result<(), AppError> {
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (series_id TEXT, {} TEXT, {} TEXT, {} TEXT, {} REAL, {} INTEGER)",
+        TABLE,
+        common::IDENTIFIER,
+        common::SUB_IDENTIFIER,
+        common::SUB_IDENTIFIER_2,
+        common::VALUE,
+        common::TIMESTAMP
+    ))
+    .execute(pool)
+    .await?;
+
+    sqlx::query(&format!(
+        "CREATE INDEX IF NOT EXISTS IDX_{} ON {} (series_id, {})",
+        TABLE,
+        TABLE,
+        common::TIMESTAMP
+    ))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Stores the values in a single table of a SQLite file and aggregates them on query.
+/// Only numeric values are stored, since only these can be aggregated
+#[derive(Debug, Clone)]
+pub struct SQLiteStore {
+    pool: Pool<Sqlite>,
+}
+
+impl SQLiteStore {
+    pub fn new() -> Self {
+        SQLiteStore {
+            pool: TIMESERIES_POOL.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl TimeSeriesStore for SQLiteStore {
+    async fn save(
+        &mut self,
+        series_id: &str,
+        data_vec: Vec<TimeSeriesData>,
+    ) -> Result<(), AppError> {
+        if data_vec.is_empty() {
+            return Ok(());
+        }
+
+        let insert = format!(
+            "INSERT INTO {} (series_id, {}, {}, {}, {}, {}) VALUES (?, ?, ?, ?, ?, ?)",
+            TABLE,
+            common::IDENTIFIER,
+            common::SUB_IDENTIFIER,
+            common::SUB_IDENTIFIER_2,
+            common::VALUE,
+            common::TIMESTAMP
+        );
+
+        let mut transaction = self.pool.begin().await?;
+
+        for data in data_vec {
+            let Some(value) = data.value.as_f64() else {
+                log::trace!(
+                    "skipping non numeric value {:?} of {}",
+                    data.value,
+                    series_id
+                );
+                continue;
+            };
+
+            sqlx::query(&insert)
+                .bind(series_id)
+                .bind(data.identifier.value_as_string())
+                .bind(find_sub_identifier(&data, common::SUB_IDENTIFIER))
+                .bind(find_sub_identifier(&data, common::SUB_IDENTIFIER_2))
+                .bind(value)
+                .bind(data.timestamp.as_micros())
+                .execute(&mut transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn query(&self, query: &TimeSeriesQuery) -> Result<TimeSeriesResponseData, AppError> {
+        let mut conditions = vec!["series_id = ?".to_owned()];
+
+        conditions.push(match query.to {
+            Some(_) => format!("{} BETWEEN ? AND ?", common::TIMESTAMP),
+            None => format!("{} > ?", common::TIMESTAMP),
+        });
+
+        for (column, _) in &query.filters {
+            conditions.push(format!("{} = ?", check_column(column)?));
+        }
+
+        let select = format!(
+            "SELECT {}, {}, {} FROM {} WHERE {} ORDER BY {} asc",
+            IDENTIFIER_COLUMNS.join(", "),
+            common::VALUE,
+            common::TIMESTAMP,
+            TABLE,
+            conditions.join(" AND "),
+            common::TIMESTAMP
+        );
+
+        log::trace!("querying time series: {}", select);
+
+        let mut statement = sqlx::query_as::<_, Row>(&select)
+            .bind(&query.series_id)
+            .bind(query.from.timestamp_micros());
+
+        if let Some(to) = query.to {
+            statement = statement.bind(to.timestamp_micros());
+        }
+        for (_, value) in &query.filters {
+            statement = statement.bind(value);
+        }
+
+        let rows = statement.fetch_all(&self.pool).await?;
+
+        aggregate(select, &rows, query)
+    }
+
+    fn clone_box(&self) -> Box<dyn TimeSeriesStore + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Accumulator {
+    count: u32,
+    sum: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+    last: Option<f64>,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+        self.last = Some(value);
+    }
+
+    fn get(&self, aggregation: &Aggregation) -> Option<f64> {
+        match aggregation {
+            Aggregation::Avg => (self.count > 0).then(|| self.sum / self.count as f64),
+            Aggregation::Min => self.min,
+            Aggregation::Max => self.max,
+            Aggregation::Last => self.last,
+            Aggregation::Sum => Some(self.sum),
+        }
+    }
+}
+
+/// Builds the same result as QuestDB does for a select with aggregations and SAMPLE BY. The rows have to be ordered by timestamp
+fn aggregate(
+    select: String,
+    rows: &[Row],
+    query: &TimeSeriesQuery,
+) -> Result<TimeSeriesResponseData, AppError> {
+    let group_indices = query
+        .group_by
+        .iter()
+        .map(|column| {
+            let column = check_column(column)?;
+            Ok(IDENTIFIER_COLUMNS
+                .iter()
+                .position(|c| *c == column)
+                .unwrap_or_default())
+        })
+        .collect::<Result<Vec<usize>, AppError>>()?;
+
+    let bucket_micros = query
+        .bucket_seconds
+        .map(|bucket_seconds| (bucket_seconds.max(1) * 1_000_000) as i64);
+
+    // buckets are aligned to the epoch - without a bucket size there is a single bucket
+    let mut map: BTreeMap<(i64, Vec<Option<String>>), Accumulator> = BTreeMap::new();
+
+    for (identifier, sub_identifier, sub_identifier2, value, timestamp) in rows {
+        let identifiers = [identifier, sub_identifier, sub_identifier2];
+
+        let bucket = bucket_micros
+            .map(|micros| timestamp - timestamp.rem_euclid(micros))
+            .unwrap_or_default();
+        let group = group_indices
+            .iter()
+            .map(|index| identifiers[*index].clone())
+            .collect();
+
+        map.entry((bucket, group)).or_default().add(*value);
+    }
+
+    let mut columns: Vec<TimeSeriesResponseColumnMetaData> = query
+        .group_by
+        .iter()
+        .map(|column| make_column(column, "SYMBOL"))
+        .collect();
+    columns.extend(
+        query
+            .aggregations
+            .iter()
+            .map(|aggregation| make_column(aggregation.as_str(), "DOUBLE")),
+    );
+    if bucket_micros.is_some() {
+        columns.push(make_column(common::TIMESTAMP, "TIMESTAMP"));
+    }
+
+    let dataset: Vec<Vec<Value>> = map
+        .into_iter()
+        .map(|((bucket, group), accumulator)| {
+            let mut row: Vec<Value> = group
+                .into_iter()
+                .map(|value| value.map(Value::String).unwrap_or(Value::Null))
+                .collect();
+
+            row.extend(query.aggregations.iter().map(|aggregation| {
+                accumulator
+                    .get(aggregation)
+                    .map(Value::from)
+                    .unwrap_or(Value::Null)
+            }));
+
+            if bucket_micros.is_some() {
+                row.push(Value::String(
+                    Utc.timestamp_micros(bucket)
+                        .single()
+                        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+                        .format(TIMESTAMP_FORMAT)
+                        .to_string(),
+                ));
+            }
+            row
+        })
+        .collect();
+
+    Ok(TimeSeriesResponseData {
+        query: select,
+        columns,
+        count: dataset.len() as u32,
+        dataset,
+    })
+}
+
+fn make_column(name: &str, column_type: &str) -> TimeSeriesResponseColumnMetaData {
+    TimeSeriesResponseColumnMetaData {
+        name: name.to_owned(),
+        column_type: column_type.to_owned(),
+    }
+}
+
+/// the column names are part of the statement, so only the known columns are accepted
+fn check_column(column: &str) -> Result<&str, AppError> {
+    IDENTIFIER_COLUMNS
+        .iter()
+        .find(|c| **c == column)
+        .copied()
+        .ok_or(AppError::InvalidArgument(
+            "column".to_owned(),
+            Some(column.to_owned()),
+        ))
+}
+
+fn find_sub_identifier(data: &TimeSeriesData, column: &str) -> Option<String> {
+    data.sub_identifiers
+        .iter()
+        .find(|value| value.get_name() == column)
+        .map(TimeSeriesValue::value_as_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use serde_json::json;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::models::timeseries::Timestamp;
+
+    use super::*;
+
+    fn make_data(ipaddress: &str, value_name: &str, value: f64, seconds: u64) -> TimeSeriesData {
+        TimeSeriesData {
+            identifier: TimeSeriesValue::Symbol(
+                common::IDENTIFIER.to_owned(),
+                ipaddress.to_owned(),
+            ),
+            sub_identifiers: vec![TimeSeriesValue::Symbol(
+                common::SUB_IDENTIFIER_2.to_owned(),
+                value_name.to_owned(),
+            )],
+            value: TimeSeriesValue::Float(common::VALUE.to_owned(), value),
+            timestamp: Timestamp::SysTime(UNIX_EPOCH + Duration::from_secs(seconds)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_query() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        create_table(&pool).await.unwrap();
+
+        let mut store = SQLiteStore { pool };
+        store
+            .save(
+                "power",
+                vec![
+                    make_data("192.168.178.2", "watt", 10.0, 0),
+                    make_data("192.168.178.2", "watt", 20.0, 30),
+                    make_data("192.168.178.2", "volt", 230.0, 30),
+                    make_data("192.168.178.2", "watt", 5.0, 70),
+                    make_data("192.168.178.3", "watt", 100.0, 70),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let mut query = TimeSeriesQuery {
+            series_id: "power".to_owned(),
+            group_by: vec![
+                common::IDENTIFIER.to_owned(),
+                common::SUB_IDENTIFIER_2.to_owned(),
+            ],
+            filters: vec![
+                (common::IDENTIFIER.to_owned(), "192.168.178.2".to_owned()),
+                (common::SUB_IDENTIFIER_2.to_owned(), "watt".to_owned()),
+            ],
+            from: Utc.timestamp_opt(0, 0).unwrap(),
+            to: Some(Utc.timestamp_opt(120, 0).unwrap()),
+            aggregations: vec![Aggregation::Avg, Aggregation::Last],
+            bucket_seconds: Some(60),
+        };
+
+        let data = store.query(&query).await.unwrap();
+        assert_eq!(
+            data.dataset,
+            vec![
+                vec![
+                    json!("192.168.178.2"),
+                    json!("watt"),
+                    json!(15.0),
+                    json!(20.0),
+                    json!("1970-01-01T00:00:00.000000Z")
+                ],
+                vec![
+                    json!("192.168.178.2"),
+                    json!("watt"),
+                    json!(5.0),
+                    json!(5.0),
+                    json!("1970-01-01T00:01:00.000000Z")
+                ],
+            ]
+        );
+
+        // the whole range without the first value, since it is not after the start
+        query.filters.truncate(1);
+        query.to = None;
+        query.bucket_seconds = None;
+        query.aggregations = vec![Aggregation::Min, Aggregation::Max];
+
+        let data = store.query(&query).await.unwrap();
+        assert_eq!(
+            data.dataset,
+            vec![
+                vec![
+                    json!("192.168.178.2"),
+                    json!("volt"),
+                    json!(230.0),
+                    json!(230.0)
+                ],
+                vec![
+                    json!("192.168.178.2"),
+                    json!("watt"),
+                    json!(5.0),
+                    json!(20.0)
+                ],
+            ]
+        );
+    }
+}
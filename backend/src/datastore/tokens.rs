@@ -0,0 +1,232 @@
+use chrono::{Duration, Utc};
+
+use crate::{
+    common,
+    models::{
+        error::AppError,
+        token::{TokenInfo, UserAction, UserActionToken},
+        users::role::Role,
+    },
+};
+
+use super::{persistence, Entry};
+
+/// the tokens of the sessions - the key is the hash of the token
+const TABLE: &str = "tokens";
+/// the tokens of the event streams are kept separately, since they are part of the url and cannot be used for the API
+const EVENT_TABLE: &str = "event_tokens";
+/// the single-use tokens of the invitation and password reset links
+const ACTION_TABLE: &str = "user_action_tokens";
+
+/// a session token expires after an hour without a request
+const TOKEN_MAX_IDLE_SECONDS: i64 = 3600;
+/// the token of the event stream is used right after it was requested, an unused token expires after a minute
+const EVENT_TOKEN_MAX_IDLE_SECONDS: i64 = 60;
+
+fn entry_to_token_info(entry: &Entry) -> Result<TokenInfo, AppError> {
+    serde_json::from_str::<TokenInfo>(entry.value.as_str()).map_err(AppError::from)
+}
+
+fn token_info_to_entry(token_hash: &str, token_info: &TokenInfo) -> Result<Entry, AppError> {
+    Ok(Entry {
+        key: token_hash.to_owned(),
+        value: serde_json::to_string(token_info)?,
+    })
+}
+
+fn entry_to_user_action_token(entry: &Entry) -> Result<UserActionToken, AppError> {
+    serde_json::from_str::<UserActionToken>(entry.value.as_str()).map_err(AppError::from)
+}
+
+pub async fn insert_token(
+    token: &str,
+    user_id: &str,
+    max_role: Option<Role>,
+) -> Result<(), AppError> {
+    insert(TABLE, token, TokenInfo::new(user_id, max_role)).await
+}
+
+/// the user and restrictions of the token - None if the token is unknown or expired
+pub async fn get_token_info(token: &str) -> Result<Option<TokenInfo>, AppError> {
+    get(TABLE, token, TOKEN_MAX_IDLE_SECONDS).await
+}
+
+pub async fn insert_event_token(token: &str, user_id: &str) -> Result<(), AppError> {
+    insert(EVENT_TABLE, token, TokenInfo::new(user_id, None)).await
+}
+
+/// the user of the event token - the token is deleted, so it can only be used once
+pub async fn take_event_token(token: &str) -> Result<Option<String>, AppError> {
+    let token_hash = common::hash_token(token);
+
+    let Some(entry) = persistence::get(EVENT_TABLE, &token_hash).await? else {
+        return Ok(None);
+    };
+    let token_info = entry_to_token_info(&entry)?;
+
+    // only the request that really deleted the token can use it
+    if persistence::delete(EVENT_TABLE, &token_hash).await? != 1
+        || token_info.is_expired(Utc::now(), EVENT_TOKEN_MAX_IDLE_SECONDS)
+    {
+        return Ok(None);
+    }
+    Ok(Some(token_info.get_user_id()))
+}
+
+/// a new link replaces the older links of the user for the same action
+pub async fn insert_user_action_token(
+    token: &str,
+    user_id: &str,
+    action: UserAction,
+) -> Result<(), AppError> {
+    for (key, existing) in get_all_user_action_tokens().await? {
+        if existing.get_user_id() == user_id && existing.get_action() == action {
+            persistence::delete(ACTION_TABLE, &key).await?;
+        }
+    }
+
+    let entry = Entry {
+        key: common::hash_token(token),
+        value: serde_json::to_string(&UserActionToken::new(user_id, action))?,
+    };
+    persistence::insert(ACTION_TABLE, entry).await?;
+    Ok(())
+}
+
+/// Removes the token of the user, so that it can only be used once - None if the token is unknown, of another user,
+/// expired or was taken by a concurrent request
+pub async fn take_user_action_token(
+    token: &str,
+    user_id: &str,
+) -> Result<Option<UserActionToken>, AppError> {
+    let token_hash = common::hash_token(token);
+
+    let Some(entry) = persistence::get(ACTION_TABLE, &token_hash).await? else {
+        return Ok(None);
+    };
+    let user_action_token = entry_to_user_action_token(&entry)?;
+    if user_action_token.get_user_id() != user_id {
+        return Ok(None);
+    }
+
+    // only the request that really deleted the token can use it
+    if persistence::delete(ACTION_TABLE, &token_hash).await? != 1 {
+        return Ok(None);
+    }
+
+    if user_action_token.is_expired(Utc::now()) {
+        return Ok(None);
+    }
+    Ok(Some(user_action_token))
+}
+
+/// true if a link for the action was sent to the user within the duration - used to throttle the unauthenticated requests
+pub async fn has_recent_user_action_token(
+    user_id: &str,
+    action: UserAction,
+    duration: Duration,
+) -> Result<bool, AppError> {
+    let now = Utc::now();
+
+    Ok(get_all_user_action_tokens()
+        .await?
+        .iter()
+        .any(|(_, existing)| {
+            existing.get_user_id() == user_id
+                && existing.get_action() == action
+                && existing.is_created_within(now, duration)
+        }))
+}
+
+pub async fn delete_user_action_tokens_of_user(user_id: &str) -> Result<(), AppError> {
+    for (key, existing) in get_all_user_action_tokens().await? {
+        if existing.get_user_id() == user_id {
+            persistence::delete(ACTION_TABLE, &key).await?;
+        }
+    }
+    Ok(())
+}
+
+/// ends all sessions and event streams of the user
+pub async fn delete_tokens_of_user(user_id: &str) -> Result<(), AppError> {
+    for table in [TABLE, EVENT_TABLE] {
+        for entry in persistence::get_all(table, None).await? {
+            if entry_to_token_info(&entry)?.get_user_id() == user_id {
+                persistence::delete(table, &entry.key).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn delete_expired_tokens() -> Result<(), AppError> {
+    delete_expired(TABLE, TOKEN_MAX_IDLE_SECONDS).await?;
+    delete_expired(EVENT_TABLE, EVENT_TOKEN_MAX_IDLE_SECONDS).await?;
+
+    let now = Utc::now();
+    for (key, existing) in get_all_user_action_tokens().await? {
+        if existing.is_expired(now) {
+            persistence::delete(ACTION_TABLE, &key).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn get_all_user_action_tokens() -> Result<Vec<(String, UserActionToken)>, AppError> {
+    let mut list = Vec::new();
+
+    for entry in persistence::get_all(ACTION_TABLE, None).await? {
+        list.push((entry.key.clone(), entry_to_user_action_token(&entry)?));
+    }
+    Ok(list)
+}
+
+async fn insert(table: &str, token: &str, token_info: TokenInfo) -> Result<(), AppError> {
+    let entry = token_info_to_entry(&common::hash_token(token), &token_info)?;
+
+    persistence::insert(table, entry).await?;
+    Ok(())
+}
+
+async fn get(
+    table: &str,
+    token: &str,
+    max_idle_seconds: i64,
+) -> Result<Option<TokenInfo>, AppError> {
+    let token_hash = common::hash_token(token);
+
+    let Some(entry) = persistence::get(table, &token_hash).await? else {
+        return Ok(None);
+    };
+    let mut token_info = entry_to_token_info(&entry)?;
+
+    let now = Utc::now();
+    if token_info.is_expired(now, max_idle_seconds) {
+        return Ok(None);
+    }
+
+    if token_info.touch(now) {
+        persistence::update(table, token_info_to_entry(&token_hash, &token_info)?).await?;
+    }
+    Ok(Some(token_info))
+}
+
+async fn delete_expired(table: &str, max_idle_seconds: i64) -> Result<(), AppError> {
+    let entries = persistence::get_all(table, None).await?;
+    let now = Utc::now();
+
+    let mut deleted = 0;
+    for entry in &entries {
+        if entry_to_token_info(entry)?.is_expired(now, max_idle_seconds) {
+            deleted += persistence::delete(table, &entry.key).await?;
+        }
+    }
+
+    log::debug!(
+        "Deleted {} of {} tokens from {} during the cleanup of expired tokens",
+        deleted,
+        entries.len(),
+        table
+    );
+    Ok(())
+}
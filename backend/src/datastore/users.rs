@@ -1,6 +1,9 @@
 use crate::{
     common, event_handling,
-    models::{error::AppError, users::User},
+    models::{
+        error::AppError,
+        users::{role::Role, User},
+    },
 };
 
 use super::{persistence, Entry};
@@ -81,6 +84,31 @@ pub async fn get_user(user_id: &str) -> Result<User, AppError> {
     }
 }
 
+/// Users that were stored before the roles existed don't have a role - they keep the rights they had before
+pub async fn set_role_of_users_without_role(role: Role) -> Result<(), AppError> {
+    for entry in persistence::get_all(TABLE, Some("key")).await? {
+        let mut value: serde_json::Value = serde_json::from_str(entry.value.as_str())?;
+
+        let Some(object) = value.as_object_mut() else {
+            continue;
+        };
+        if object.contains_key("role") {
+            continue;
+        }
+        object.insert("role".to_owned(), serde_json::to_value(role)?);
+
+        persistence::update(
+            TABLE,
+            Entry {
+                key: entry.key,
+                value: value.to_string(),
+            },
+        )
+        .await?;
+    }
+    Ok(())
+}
+
 pub fn encrypt_users(
     users: Vec<User>,
     password_for_encryption: &str,
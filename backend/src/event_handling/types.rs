@@ -1,4 +1,4 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::{collections::HashMap, fmt::Debug, net::IpAddr};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -105,6 +105,26 @@ impl Event {
         })
     }
 
+    /// the server the event belongs to - None for events that are not about a single server
+    pub fn get_ipaddress(&self) -> Option<IpAddr> {
+        match self.object_type {
+            ObjectType::Status | ObjectType::Server | ObjectType::Notification => {
+                self.key.parse().ok()
+            }
+            // the key also contains the data id
+            ObjectType::ConditionCheckResult => self.key.split('_').next()?.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// the user the event belongs to - None for events that are not about a user
+    pub fn get_user_id(&self) -> Option<&str> {
+        match self.object_type {
+            ObjectType::User => Some(&self.key),
+            _ => None,
+        }
+    }
+
     pub fn new_from_listevent_source(
         occurrence_datetime: DateTime<Utc>,
         event_type: EventType,
@@ -226,3 +246,43 @@ impl EventSource for ListSource {
         kv
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_event(object_type: ObjectType, key: &str) -> Event {
+        Event {
+            occurrence_datetime: Utc::now(),
+            object_type,
+            event_type: EventType::Update,
+            key_name: "".to_owned(),
+            key: key.to_owned(),
+            value: "".to_owned(),
+            version: 0,
+        }
+    }
+
+    #[test]
+    fn test_get_ipaddress() {
+        let ipaddress: IpAddr = "192.168.178.2".parse().unwrap();
+
+        assert_eq!(
+            make_event(ObjectType::Status, "192.168.178.2").get_ipaddress(),
+            Some(ipaddress)
+        );
+        assert_eq!(
+            make_event(ObjectType::ConditionCheckResult, "192.168.178.2_\"data\"").get_ipaddress(),
+            Some(ipaddress)
+        );
+        assert_eq!(make_event(ObjectType::User, "admin").get_ipaddress(), None);
+        assert_eq!(
+            make_event(ObjectType::User, "admin").get_user_id(),
+            Some("admin")
+        );
+        assert_eq!(
+            make_event(ObjectType::Plugin, "proxmox").get_user_id(),
+            None
+        );
+    }
+}
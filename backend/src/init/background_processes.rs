@@ -30,6 +30,25 @@ enum ProcessType {
     MessagePolling,
 }
 
+impl ProcessType {
+    fn get_name(&self) -> &str {
+        match self {
+            ProcessType::Monitoring => "monitoring",
+            ProcessType::StatusCheck => "status_check",
+            ProcessType::ConditionCheck => "condition_check",
+            ProcessType::MessagePolling => "message_polling",
+        }
+    }
+}
+
+/// the last finished run of a background process
+#[derive(Debug, Clone)]
+pub struct ProcessTiming {
+    pub process: String,
+    pub duration_seconds: f64,
+    pub end: DateTime<Utc>,
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 struct ProcessExecution {
     process_type: ProcessType,
@@ -81,6 +100,13 @@ impl ProcessExecution {
         self.prev_end = self.end;
     }
 
+    fn get_last_finished(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => Some((start, end)),
+            _ => self.prev_start.zip(self.prev_end),
+        }
+    }
+
     fn time_taken(&self) -> i64 {
         let Some(s) = self.start else {
             return 0;
@@ -124,6 +150,28 @@ impl ProcessExecutions {
     }
 }
 
+pub async fn get_process_timings() -> Vec<ProcessTiming> {
+    let lock = EXECUTIONS.read().await;
+
+    let mut timings: Vec<ProcessTiming> = lock
+        .executions
+        .values()
+        .filter_map(|execution| {
+            let (start, end) = execution.get_last_finished()?;
+
+            Some(ProcessTiming {
+                process: execution.process_type.get_name().to_owned(),
+                duration_seconds: end.signed_duration_since(start).num_milliseconds() as f64
+                    / 1000.0,
+                end,
+            })
+        })
+        .collect();
+
+    timings.sort_by(|a, b| a.process.cmp(&b.process));
+    timings
+}
+
 pub async fn start_background_prcesses() -> Result<(), AppError> {
     start_condition_checks().await;
     start_status_checks().await;
@@ -131,9 +179,38 @@ pub async fn start_background_prcesses() -> Result<(), AppError> {
 
     start_message_polling().await;
 
+    if crate::common::is_mqtt_configured() {
+        start_mqtt().await;
+    }
+
     Ok(())
 }
 
+async fn start_mqtt() {
+    thread::spawn(|| {
+        futures::executor::block_on(async {
+            let rt = new_tokio_runtime("mqtt", 2);
+            // Spawn a future onto the runtime
+
+            rt.spawn(async {
+                if let Err(err) = crate::common::run_mqtt_event_loop().await {
+                    log::error!("Could not start MQTT connection. Error was {}", err)
+                }
+            });
+
+            rt.spawn(async {
+                loop {
+                    if let Err(err) = crate::plugin_execution::process_mqtt_subscriptions().await {
+                        log::error!("Could not process MQTT subscriptions. Error was {}", err);
+                    }
+                    sleep(Duration::from_secs(10)).await;
+                }
+            })
+            .await
+        })
+    });
+}
+
 async fn start_message_polling() {
     thread::spawn(|| {
         futures::executor::block_on(async {
@@ -12,7 +12,9 @@ use crate::models::error::AppError;
 use crate::webserver;
 use crate::webserver::AppData;
 
+pub use background_processes::get_process_timings;
 pub use background_processes::register_poll_message_callback;
+pub use background_processes::ProcessTiming;
 
 pub static ENV_FILENAME: &str = "./external_files/.env";
 
@@ -25,13 +27,15 @@ pub async fn start() -> Result<(), AppError> {
 
     let bind_address = datastore::get_config()?.get_string("bind_address")?;
 
-    let neccessary_migrations = migrations::check_necessary_migration(); // needs to be checked before db connection is done
+    let mut neccessary_migrations = migrations::check_necessary_migration(); // needs to be checked before db connection is done
     migrations::execute_pre_db_startup_migrations(&neccessary_migrations)?;
 
     let app_data = create_common_app_data()?;
     datastore::init_db().await?;
+    datastore::init_timeseries_db().await?;
     one_time_post_db_startup().await?;
 
+    neccessary_migrations.extend(migrations::check_necessary_post_db_migrations().await?);
     migrations::execute_post_db_startup_migrations(&neccessary_migrations).await?;
     migrations::save_migration(&neccessary_migrations).await?;
 
@@ -10,8 +10,11 @@ use crate::{
     common,
     datastore::{self},
     event_handling::{self, EventSource},
-    models::{error::AppError, response::system_information::SystemInformation},
+    models::{
+        error::AppError, report::ReportPeriod, response::system_information::SystemInformation,
+    },
     other_functions::systeminfo,
+    plugin_execution,
 };
 
 lazy_static! {
@@ -32,6 +35,7 @@ pub async fn start_scheduled_jobs() -> Result<(), AppError> {
     schedule_cache_update(&scheduler).await?;
     schedule_token_cleanup(&scheduler).await?;
     schedule_one_time_crypt_key_cleanup(&scheduler).await?;
+    schedule_availability_digest(&scheduler).await?;
 
     scheduler.start().await?;
 
@@ -144,18 +148,19 @@ async fn schedule_cache_update(scheduler: &JobScheduler) -> Result<(), AppError>
 
 async fn schedule_token_cleanup(scheduler: &JobScheduler) -> Result<(), AppError> {
     scheduler
-        .add(Job::new(
-            "0 0 * * * *",
-            |_uuid, _l| match crate::datastore::delete_expired_tokens() {
-                Ok(_) => {}
-                Err(err) => {
-                    log::error!(
-                        "Could not execute job schedule_token_cleanup. Error was {}",
-                        err
-                    )
+        .add(Job::new_async("0 0 * * * *", |_uuid, _l| {
+            Box::pin(async {
+                match datastore::delete_expired_tokens().await {
+                    Ok(_) => {}
+                    Err(err) => {
+                        log::error!(
+                            "Could not execute job schedule_token_cleanup. Error was {}",
+                            err
+                        )
+                    }
                 }
-            },
-        )?)
+            })
+        })?)
         .await?;
 
     Ok(())
@@ -180,3 +185,37 @@ async fn schedule_one_time_crypt_key_cleanup(scheduler: &JobScheduler) -> Result
 
     Ok(())
 }
+
+/// the digest is only sent if a cron expression (in UTC) is configured
+async fn schedule_availability_digest(scheduler: &JobScheduler) -> Result<(), AppError> {
+    let config = datastore::get_config()?;
+
+    let Ok(cron) = config.get_string("availability_report_cron") else {
+        return Ok(());
+    };
+    if cron.trim().is_empty() {
+        return Ok(());
+    }
+
+    let period: ReportPeriod = match config.get_string("availability_report_period") {
+        Ok(period) => period.parse()?,
+        Err(_) => ReportPeriod::default(),
+    };
+
+    scheduler
+        .add(Job::new_async(cron.as_str(), move |_uuid, _l| {
+            let period = period.clone();
+
+            Box::pin(async move {
+                if let Err(err) = plugin_execution::send_availability_digest(period).await {
+                    log::error!(
+                        "Could not execute job schedule_availability_digest. Error was {}",
+                        err
+                    )
+                }
+            })
+        })?)
+        .await?;
+
+    Ok(())
+}
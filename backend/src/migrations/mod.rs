@@ -8,6 +8,7 @@ use crate::{
         error::AppError,
         plugin::Plugin,
         server::{Credential, Feature, Server},
+        users::{notification_channel::NotificationChannel, role::Role},
     },
 };
 
@@ -15,6 +16,8 @@ use crate::{
 pub enum MigrationTypes {
     DbLocation,
     Encryption,
+    NotificationChannelEncryption,
+    UserRoles,
 }
 
 impl fmt::Display for MigrationTypes {
@@ -22,6 +25,10 @@ impl fmt::Display for MigrationTypes {
         match self {
             MigrationTypes::DbLocation => write!(f, "DB_LOCATION"),
             MigrationTypes::Encryption => write!(f, "ENCRYPTION"),
+            MigrationTypes::NotificationChannelEncryption => {
+                write!(f, "NOTIFICATION_CHANNEL_ENCRYPTION")
+            }
+            MigrationTypes::UserRoles => write!(f, "USER_ROLES"),
         }
     }
 }
@@ -39,6 +46,22 @@ pub fn check_necessary_migration() -> Vec<MigrationTypes> {
     migrations
 }
 
+/// the migrations of the data in the database are checked against the migrations that were already done
+pub async fn check_necessary_post_db_migrations() -> Result<Vec<MigrationTypes>, AppError> {
+    let mut migrations: Vec<MigrationTypes> = Vec::new();
+
+    for migration in [
+        MigrationTypes::NotificationChannelEncryption,
+        MigrationTypes::UserRoles,
+    ] {
+        if !datastore::is_migration_done(migration.to_string().as_str()).await? {
+            migrations.push(migration);
+        }
+    }
+
+    Ok(migrations)
+}
+
 pub fn do_db_location_migration() -> std::result::Result<u64, AppError> {
     let old_path = Path::new("./server-manager.db");
     let new_path = Path::new(common::DB_FILENAME);
@@ -110,6 +133,27 @@ pub async fn do_encryption_migration() -> std::result::Result<(), AppError> {
     Ok(())
 }
 
+/// the secrets of the notification channels were stored unencrypted before
+pub async fn do_notification_channel_encryption_migration() -> Result<(), AppError> {
+    let crypto_key_entry = get_default_encryption_key().await?;
+
+    for mut user in datastore::get_all_users(true).await? {
+        if user.get_notification_channels().is_empty() {
+            continue;
+        }
+
+        let encrypted_channels = user
+            .get_notification_channels()
+            .iter()
+            .map(|channel| channel.encrypt_secrets(&crypto_key_entry.value))
+            .collect::<Result<Vec<NotificationChannel>, AppError>>()?;
+
+        user.set_notification_channels(encrypted_channels);
+        datastore::update_user(&user).await?;
+    }
+    Ok(())
+}
+
 async fn get_default_encryption_key() -> Result<datastore::Entry, AppError> {
     let crypto_key_entry = datastore::get_encryption_key()
         .await?
@@ -182,6 +226,14 @@ pub async fn execute_post_db_startup_migrations(
     if neccessary_migrations.contains(&MigrationTypes::Encryption) {
         do_encryption_migration().await?;
     }
+    // has to be done before the users are saved again by other migrations - they would be saved with the default role
+    if neccessary_migrations.contains(&MigrationTypes::UserRoles) {
+        // users created before the roles existed could manage everything
+        datastore::set_role_of_users_without_role(Role::Admin).await?;
+    }
+    if neccessary_migrations.contains(&MigrationTypes::NotificationChannelEncryption) {
+        do_notification_channel_encryption_migration().await?;
+    }
 
     Ok(())
 }
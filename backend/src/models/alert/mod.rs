@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{error::AppError, plugin::notification::Level};
+
+/// A rule on a monitoring series, ie. CPU > 90 for 5 minutes. If it is breached, a notification is raised for the server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertRule {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// id of the monitoring series, ie. the one defined in the monitoring section of a plugin
+    pub series_id: String,
+    /// only check the values of this server - all servers if not set
+    #[serde(default)]
+    pub ipaddress: Option<String>,
+    /// only check the values with this sub identifier, ie. the mount point of a disk - all if not set
+    #[serde(default)]
+    pub sub_identifier: Option<String>,
+    /// name of the value to check, if the series has more than one value
+    #[serde(default)]
+    pub value_name: Option<String>,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// the alert is only resolved once the value doesn't breach this threshold anymore - defaults to the threshold.
+    /// Prevents that an alert flaps for values close to the threshold
+    #[serde(default)]
+    pub clear_threshold: Option<f64>,
+    /// the threshold has to be breached by all values within this duration before the alert is raised
+    #[serde(default)]
+    pub duration_seconds: u64,
+    #[serde(default)]
+    pub notification_level: Level,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Comparison {
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+}
+
+impl Comparison {
+    pub fn compare(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterOrEqual => value >= threshold,
+            Comparison::Less => value < threshold,
+            Comparison::LessOrEqual => value <= threshold,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+        }
+    }
+}
+
+/// the aggregated values of a series within the duration of a rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowValues {
+    pub min: f64,
+    pub max: f64,
+    pub last: f64,
+    /// there are values from before the start of the duration, so the values cover the whole duration.
+    /// Not the case for a new series or a series that was interrupted
+    pub covers_duration: bool,
+}
+
+impl AlertRule {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.id.is_empty()
+            || !self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(AppError::InvalidArgument(
+                "id".to_owned(),
+                Some(self.id.clone()),
+            ));
+        }
+        if self.series_id.is_empty() {
+            return Err(AppError::MissingArgument("series_id".to_owned()));
+        }
+        // with a clear threshold beyond the threshold the alert would be resolved while the threshold is still breached
+        if let Some(clear_threshold) = self.clear_threshold {
+            let is_beyond_threshold = match self.comparison {
+                Comparison::Greater | Comparison::GreaterOrEqual => {
+                    clear_threshold > self.threshold
+                }
+                Comparison::Less | Comparison::LessOrEqual => clear_threshold < self.threshold,
+            };
+            if is_beyond_threshold {
+                return Err(AppError::InvalidArgument(
+                    "clear_threshold".to_owned(),
+                    Some(format!(
+                        "{} has to be on the other side of the threshold {}",
+                        clear_threshold, self.threshold
+                    )),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// true if all values within the duration breach the threshold - and the values cover the whole duration
+    pub fn is_breached(&self, values: &WindowValues) -> bool {
+        let value = if self.duration_seconds == 0 {
+            values.last
+        } else if !values.covers_duration {
+            return false;
+        } else {
+            // the value closest to the threshold decides
+            match self.comparison {
+                Comparison::Greater | Comparison::GreaterOrEqual => values.min,
+                Comparison::Less | Comparison::LessOrEqual => values.max,
+            }
+        };
+        self.comparison.compare(value, self.threshold)
+    }
+
+    /// true if the latest value doesn't breach the clear threshold anymore
+    pub fn is_cleared(&self, values: &WindowValues) -> bool {
+        !self
+            .comparison
+            .compare(values.last, self.clear_threshold.unwrap_or(self.threshold))
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_rule(comparison: Comparison, clear_threshold: Option<f64>) -> AlertRule {
+        AlertRule {
+            id: "cpu".to_owned(),
+            name: "CPU".to_owned(),
+            enabled: true,
+            series_id: "cpu_load".to_owned(),
+            ipaddress: None,
+            sub_identifier: None,
+            value_name: None,
+            comparison,
+            threshold: 90.0,
+            clear_threshold,
+            duration_seconds: 300,
+            notification_level: Level::Warn,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_is_breached_for_duration() {
+        let rule = make_rule(Comparison::Greater, None);
+
+        assert!(rule.is_breached(&WindowValues {
+            min: 91.0,
+            max: 99.0,
+            last: 95.0,
+            covers_duration: true
+        }));
+        // a single value below the threshold within the duration is enough to not raise the alert
+        assert!(!rule.is_breached(&WindowValues {
+            min: 50.0,
+            max: 99.0,
+            last: 95.0,
+            covers_duration: true
+        }));
+        // the values of a new series don't cover the duration yet
+        assert!(!rule.is_breached(&WindowValues {
+            min: 91.0,
+            max: 99.0,
+            last: 95.0,
+            covers_duration: false
+        }));
+
+        let rule = AlertRule {
+            duration_seconds: 0,
+            ..rule
+        };
+        assert!(rule.is_breached(&WindowValues {
+            min: 50.0,
+            max: 99.0,
+            last: 95.0,
+            covers_duration: false
+        }));
+    }
+
+    #[test]
+    fn test_validate_clear_threshold() {
+        assert!(make_rule(Comparison::Greater, Some(80.0))
+            .validate()
+            .is_ok());
+        assert!(make_rule(Comparison::GreaterOrEqual, Some(90.0))
+            .validate()
+            .is_ok());
+        assert!(make_rule(Comparison::Greater, Some(95.0))
+            .validate()
+            .is_err());
+
+        assert!(make_rule(Comparison::Less, Some(95.0)).validate().is_ok());
+        assert!(make_rule(Comparison::LessOrEqual, Some(80.0))
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_is_cleared_with_hysteresis() {
+        let rule = make_rule(Comparison::Greater, Some(80.0));
+
+        let values = |last| WindowValues {
+            min: last,
+            max: last,
+            last,
+            covers_duration: true,
+        };
+
+        assert!(!rule.is_cleared(&values(85.0)));
+        assert!(rule.is_cleared(&values(80.0)));
+
+        let rule = make_rule(Comparison::Less, Some(95.0));
+        assert!(!rule.is_cleared(&values(92.0)));
+        assert!(rule.is_cleared(&values(95.0)));
+    }
+}
@@ -25,6 +25,10 @@ enum Level {
 #[derive(Debug)]
 pub enum AppError {
     InvalidPassword,
+    TwoFactorRequired,
+    InvalidTwoFactorCode,
+    /// the user is locked after too many failed logins - contains the seconds until the next login is possible
+    TooManyLoginAttempts(i64),
     DNSServersNotConfigured(),
     ServerNotFound(String),
     FeatureNotFound(String, String),
@@ -34,6 +38,8 @@ pub enum AppError {
     CredentialNotFound(String),
     CommandNotFound(String),
     CommunicationError(String),
+    /// the SSH host key doesn't match the pinned fingerprint - contains the fingerprint of the host key
+    HostKeyMismatch(String),
     UnknownPlugin(String),
     UnknownPluginAction(String, String),
     UnknownPluginData(String, String),
@@ -46,6 +52,7 @@ pub enum AppError {
     UnsupportedURLParameter(String, Option<String>),
     CouldNotRenderData(String),
     UnAuthorized,
+    Forbidden(String),
     DecryptionError,
     ParseError(String),
     EmailConfigError(String),
@@ -67,6 +74,22 @@ impl Display for AppError {
             AppError::InvalidPassword => {
                 write!(f, "The password was invalid")
             }
+            AppError::TwoFactorRequired => {
+                write!(f, "A code of the two-factor authentication is required")
+            }
+            AppError::InvalidTwoFactorCode => {
+                write!(f, "The code of the two-factor authentication was invalid")
+            }
+            AppError::TooManyLoginAttempts(seconds) => write!(
+                f,
+                "Too many failed logins. The next login is possible in {} seconds",
+                seconds
+            ),
+            AppError::HostKeyMismatch(fingerprint) => write!(
+                f,
+                "The SSH host key with the fingerprint {} doesn't match the pinned fingerprint",
+                fingerprint
+            ),
             AppError::CommunicationError(err) => {
                 write!(
                     f,
@@ -141,6 +164,7 @@ impl Display for AppError {
             },
             AppError::CouldNotRenderData(data) => write!(f, "Could not render data '{}'", data),
             AppError::UnAuthorized => write!(f, "User is not authorized"),
+            AppError::Forbidden(err) => write!(f, "Not allowed: {}", err),
             AppError::DecryptionError => write!(f, "Data could not be decrypted"),
             AppError::ParseError(err) => write!(f, "Could not parse given data '{}'", err),
             AppError::EmailConfigError(name) => write!(
@@ -338,12 +362,52 @@ impl From<kafka::Error> for AppError {
     }
 }
 
+impl From<ssh2::Error> for AppError {
+    fn from(err: ssh2::Error) -> Self {
+        AppError::CommunicationError(format!("{}", err))
+    }
+}
+
+impl From<snmp2::Error> for AppError {
+    fn from(err: snmp2::Error) -> Self {
+        AppError::CommunicationError(format!("{}", err))
+    }
+}
+
+impl From<rumqttc::ClientError> for AppError {
+    fn from(err: rumqttc::ClientError) -> Self {
+        AppError::CommunicationError(format!("{}", err))
+    }
+}
+
+impl From<tokio_modbus::Error> for AppError {
+    fn from(err: tokio_modbus::Error) -> Self {
+        AppError::CommunicationError(format!("{}", err))
+    }
+}
+
+impl From<tokio_modbus::ExceptionCode> for AppError {
+    fn from(err: tokio_modbus::ExceptionCode) -> Self {
+        AppError::CommunicationError(format!("Modbus exception: {}", err))
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        AppError::Unknown(format!("{}", err))
+    }
+}
+
 impl ResponseError for AppError {
     fn status_code(&self) -> StatusCode {
         match self {
-            Self::UnAuthorized | Self::InvalidPassword | Self::UserNotFound(_) => {
-                StatusCode::UNAUTHORIZED
-            }
+            Self::UnAuthorized
+            | Self::InvalidPassword
+            | Self::UserNotFound(_)
+            | Self::TwoFactorRequired
+            | Self::InvalidTwoFactorCode => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::TooManyLoginAttempts(_) => StatusCode::TOO_MANY_REQUESTS,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -0,0 +1,141 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::error::AppError;
+
+/// During a maintenance window notifications of the server are still recorded, but they are not sent to the notification channels
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub ipaddress: String,
+    /// only the notifications of this feature are silenced - all notifications of the server if not set
+    #[serde(default)]
+    pub plugin_id: Option<String>,
+    #[serde(flatten)]
+    pub schedule: MaintenanceSchedule,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "schedule_type", rename_all = "lowercase")]
+pub enum MaintenanceSchedule {
+    /// recurring window. The cron expression (sec min hour day_of_month month day_of_week) defines the start in local time
+    Cron { cron: String, duration_minutes: u64 },
+    /// one-off window
+    Range {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+}
+
+impl MaintenanceWindow {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.id.is_empty()
+            || !self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(AppError::InvalidArgument(
+                "id".to_owned(),
+                Some(self.id.clone()),
+            ));
+        }
+
+        match &self.schedule {
+            MaintenanceSchedule::Cron { cron, .. } => {
+                cron::Schedule::from_str(cron).map_err(|_| {
+                    AppError::InvalidArgument("cron".to_owned(), Some(cron.clone()))
+                })?;
+            }
+            MaintenanceSchedule::Range { start, end } => {
+                if start >= end {
+                    return Err(AppError::InvalidArgument(
+                        "end".to_owned(),
+                        Some(format!("{}", end)),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn applies_to(&self, ipaddress: &str, plugin_id: Option<&str>) -> bool {
+        self.ipaddress == ipaddress
+            && (self.plugin_id.is_none() || self.plugin_id.as_deref() == plugin_id)
+    }
+
+    /// Returns the end of the window, if it is active at the given time
+    pub fn get_active_end(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let (start, end) = match &self.schedule {
+            MaintenanceSchedule::Cron {
+                cron,
+                duration_minutes,
+            } => {
+                let schedule = cron::Schedule::from_str(cron).ok()?;
+                let start = schedule
+                    .after(&now.with_timezone(&Local))
+                    .next_back()?
+                    .with_timezone(&Utc);
+
+                (start, start + Duration::minutes(*duration_minutes as i64))
+            }
+            MaintenanceSchedule::Range { start, end } => (*start, *end),
+        };
+
+        (start <= now && now < end).then_some(end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_get_active_end() {
+        let start = Utc.with_ymd_and_hms(2023, 9, 1, 2, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2023, 9, 1, 3, 0, 0).unwrap();
+
+        let range = MaintenanceWindow {
+            id: "backup".to_owned(),
+            name: "Backup".to_owned(),
+            ipaddress: "192.168.178.2".to_owned(),
+            plugin_id: None,
+            schedule: MaintenanceSchedule::Range { start, end },
+        };
+
+        assert_eq!(range.get_active_end(start), Some(end));
+        assert_eq!(range.get_active_end(end), None);
+        assert_eq!(range.get_active_end(start - Duration::minutes(1)), None);
+
+        // every day at 02:00 local time for an hour
+        let cron = MaintenanceWindow {
+            schedule: MaintenanceSchedule::Cron {
+                cron: "0 0 2 * * *".to_owned(),
+                duration_minutes: 60,
+            },
+            ..range
+        };
+        let local_start = Local
+            .with_ymd_and_hms(2023, 9, 1, 2, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            cron.get_active_end(local_start + Duration::minutes(30)),
+            Some(local_start + Duration::minutes(60))
+        );
+        assert_eq!(
+            cron.get_active_end(local_start + Duration::minutes(61)),
+            None
+        );
+        assert!(cron.validate().is_ok());
+        assert!(cron.applies_to("192.168.178.2", Some("proxmox")));
+        assert!(!cron.applies_to("192.168.178.3", None));
+    }
+}
@@ -4,9 +4,12 @@ use crate::common;
 
 use self::response::status::Status;
 
+pub mod alert;
 pub mod config;
 pub mod error;
+pub mod maintenance;
 pub mod plugin;
+pub mod report;
 pub mod request;
 pub mod response;
 pub mod server;
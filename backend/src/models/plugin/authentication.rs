@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use super::common::ArgDef;
+
+/// Login that has to be done before the actual http requests of a plugin can be executed -
+/// ie. for Proxmox tickets or the Synology SID.
+/// The extracted values can be used with `${session.<name>}` in the args of http data queries and actions
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticationDef {
+    /// args of the login request - same as for http data queries (method, url, header, body)
+    pub args: Vec<ArgDef>,
+    pub extract: Vec<SessionValueDef>,
+    /// time after which a new login is done, even if the API didn't respond with 401 before
+    #[serde(default = "default_expiry")]
+    pub expiry_seconds: u64,
+}
+
+/// A value of the login response that is stored in the session.
+/// Either extracted from the JSON response with a JSONPath or taken from a response header.
+/// For a set-cookie header only the cookie itself without attributes (path, expires...) is stored
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SessionValueDef {
+    pub name: String,
+    #[serde(default)]
+    pub json_path: Option<String>,
+    #[serde(default)]
+    pub header: Option<String>,
+}
+
+fn default_expiry() -> u64 {
+    300
+}
@@ -1,4 +1,5 @@
 pub mod action;
+pub mod authentication;
 pub mod common;
 pub mod data;
 pub mod detection;
@@ -15,7 +16,8 @@ use crate::event_handling::{EventSource, ObjectType, Value};
 use std::hash::{Hash, Hasher};
 
 use self::{
-    action::ActionDef, data::DataDef, detection::DetectionDef, notification::NotificationDef,
+    action::ActionDef, authentication::AuthenticationDef, data::DataDef, detection::DetectionDef,
+    notification::NotificationDef,
 };
 
 use super::error::AppError;
@@ -32,6 +34,8 @@ pub struct Plugin {
     pub credentials: Vec<CredentialDef>,
     #[serde(default)]
     pub params: Vec<ParamDef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authentication: Option<AuthenticationDef>,
     #[serde(default)]
     pub data: Vec<DataDef>,
     #[serde(default)]
@@ -1,8 +1,20 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::models::error::AppError;
+
 use super::common::Script;
 
+/// range that is returned if no range is requested
+const DEFAULT_RANGE_HOURS: i64 = 10;
+/// the automatically chosen bucket size results in at most this number of values per series
+const MAX_BUCKETS: i64 = 600;
+/// bucket sizes that are chosen automatically - from 1 minute to 1 day
+const BUCKET_SIZES_SECONDS: [u64; 9] = [60, 300, 900, 1800, 3600, 10800, 21600, 43200, 86400];
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum SeriesType {
@@ -67,6 +79,105 @@ pub struct TimeSeriesResponseMetaData {
     pub series_id: String,
     pub series_type: String,
     pub chart_type: String,
+    pub range: MonitoringRange,
+}
+
+/// the function that aggregates the values within a bucket
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregation {
+    Avg,
+    Min,
+    Max,
+    #[default]
+    Last,
+    Sum,
+}
+
+impl Aggregation {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Aggregation::Avg => "avg",
+            Aggregation::Min => "min",
+            Aggregation::Max => "max",
+            Aggregation::Last => "last",
+            Aggregation::Sum => "sum",
+        }
+    }
+}
+
+impl FromStr for Aggregation {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "avg" => Ok(Aggregation::Avg),
+            "min" => Ok(Aggregation::Min),
+            "max" => Ok(Aggregation::Max),
+            "last" => Ok(Aggregation::Last),
+            "sum" => Ok(Aggregation::Sum),
+            _ => Err(AppError::InvalidArgument(
+                "aggregation".to_owned(),
+                Some(s.to_owned()),
+            )),
+        }
+    }
+}
+
+/// time range and downsampling of the requested monitoring data
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MonitoringRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub bucket_seconds: u64,
+    pub aggregation: Aggregation,
+}
+
+impl MonitoringRange {
+    /// Without from the last 10 hours before to (default now) are returned.
+    /// Without a bucket size the smallest one is chosen that results in not more than 600 values per series
+    pub fn new(
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        bucket_seconds: Option<u64>,
+        aggregation: Aggregation,
+    ) -> Result<Self, AppError> {
+        let to = to.unwrap_or_else(Utc::now);
+        let from = from.unwrap_or(to - Duration::hours(DEFAULT_RANGE_HOURS));
+
+        if from >= to {
+            return Err(AppError::InvalidArgument(
+                "from".to_owned(),
+                Some(format!("{}", from)),
+            ));
+        }
+
+        let bucket_seconds = match bucket_seconds {
+            Some(0) => {
+                return Err(AppError::InvalidArgument(
+                    "bucket_seconds".to_owned(),
+                    Some("0".to_owned()),
+                ))
+            }
+            Some(bucket_seconds) => bucket_seconds,
+            None => {
+                let range_seconds = (to - from).num_seconds();
+
+                BUCKET_SIZES_SECONDS
+                    .iter()
+                    .find(|size| range_seconds / (**size as i64) <= MAX_BUCKETS)
+                    .copied()
+                    .unwrap_or(BUCKET_SIZES_SECONDS[BUCKET_SIZES_SECONDS.len() - 1])
+            }
+        };
+
+        Ok(MonitoringRange {
+            from,
+            to,
+            bucket_seconds,
+            aggregation,
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -92,3 +203,46 @@ pub struct TimeSeriesResponseColumnMetaData {
     #[serde(rename = "type")]
     pub column_type: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitoring_range_bucket_selection() {
+        let to = Utc::now();
+
+        let range = MonitoringRange::new(None, Some(to), None, Aggregation::default()).unwrap();
+        assert_eq!(range.from, to - Duration::hours(10));
+        assert_eq!(range.bucket_seconds, 60);
+
+        let week = MonitoringRange::new(
+            Some(to - Duration::days(7)),
+            Some(to),
+            None,
+            Aggregation::Avg,
+        )
+        .unwrap();
+        assert_eq!(week.bucket_seconds, 1800);
+
+        let month = MonitoringRange::new(
+            Some(to - Duration::days(30)),
+            Some(to),
+            None,
+            Aggregation::Avg,
+        )
+        .unwrap();
+        assert_eq!(month.bucket_seconds, 10800);
+
+        let explicit = MonitoringRange::new(
+            Some(to - Duration::days(30)),
+            Some(to),
+            Some(3600),
+            Aggregation::Max,
+        )
+        .unwrap();
+        assert_eq!(explicit.bucket_seconds, 3600);
+
+        assert!(MonitoringRange::new(Some(to), Some(to), None, Aggregation::Avg).is_err());
+    }
+}
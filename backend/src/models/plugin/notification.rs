@@ -1,8 +1,18 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    common,
+    event_handling::{EventSource, ObjectType, Value},
+    models::error::AppError,
+};
+
 use super::common::Script;
 
-#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+/// ordered by severity, so that levels can be compared
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Level {
     #[default]
     Info,
@@ -18,6 +28,7 @@ pub struct NotificationDef {
     pub data_id: String,
     #[serde(default)]
     pub message: String,
+    /// if true, the notification is acknowledged as soon as it is raised - it is shown but needs no reaction
     #[serde(default)]
     pub auto_ack: bool,
     #[serde(default)]
@@ -26,18 +37,148 @@ pub struct NotificationDef {
     pub notification_level: Level,
 }
 
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub enum NotificationState {
+    #[default]
+    Open,
+    Acknowledged,
+    Resolved,
+}
+
+/// the notifications of a server that are currently open or acknowledged
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Notifications {
     pub ipaddress: String,
     pub list: Vec<Notification>,
 }
 
+impl Notifications {
+    pub fn new(ipaddress: &str) -> Self {
+        Notifications {
+            ipaddress: ipaddress.to_owned(),
+            list: Vec::new(),
+        }
+    }
+}
+
+impl EventSource for Notifications {
+    fn get_object_type(&self) -> ObjectType {
+        ObjectType::Notification
+    }
+
+    fn get_event_key_name(&self) -> String {
+        common::IPADDRESS.to_owned()
+    }
+
+    fn get_event_key(&self) -> String {
+        self.ipaddress.clone()
+    }
+
+    fn get_event_value(&self) -> Result<String, AppError> {
+        serde_json::to_string(self).map_err(AppError::from)
+    }
+
+    fn get_version(&self) -> i64 {
+        -1
+    }
+
+    fn get_key_values(&self) -> HashMap<String, Value> {
+        let mut kv = HashMap::new();
+        kv.insert("list".to_owned(), Value::String(format!("{:?}", self.list)));
+        kv
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Notification {
+    /// the id of the notification definition - there is only one open notification per id and server
     pub id: String,
     pub name: String,
     #[serde(default)]
     pub message: String,
     #[serde(default)]
     pub notification_level: Level,
+    #[serde(default)]
+    pub state: NotificationState,
+    /// when the notification was raised
+    #[serde(default = "Utc::now")]
+    pub first_seen: DateTime<Utc>,
+    /// when the condition of the notification was matched for the last time
+    #[serde(default = "Utc::now")]
+    pub last_seen: DateTime<Utc>,
+    #[serde(default)]
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// the feature that raised the notification - not set for notifications that are not based on a plugin
+    #[serde(default)]
+    pub plugin_id: Option<String>,
+    /// no messages are sent to the notification channels until then
+    #[serde(default)]
+    pub silenced_until: Option<DateTime<Utc>>,
+    /// false as long as the notification was not sent to the notification channels, ie. since it was raised during a maintenance window
+    #[serde(default = "default_true")]
+    pub delivered: bool,
+}
+
+impl Notification {
+    pub fn new(id: &str, name: &str, message: &str, notification_level: Level) -> Self {
+        let now = Utc::now();
+
+        Notification {
+            id: id.to_owned(),
+            name: name.to_owned(),
+            message: message.to_owned(),
+            notification_level,
+            state: NotificationState::Open,
+            first_seen: now,
+            last_seen: now,
+            acknowledged_at: None,
+            resolved_at: None,
+            plugin_id: None,
+            silenced_until: None,
+            delivered: false,
+        }
+    }
+
+    pub fn silence(&mut self, until: DateTime<Utc>) {
+        self.silenced_until = Some(until);
+    }
+
+    pub fn is_silenced(&self, now: DateTime<Utc>) -> bool {
+        self.silenced_until.is_some_and(|until| until > now)
+    }
+
+    pub fn acknowledge(&mut self, now: DateTime<Utc>) {
+        if self.state == NotificationState::Open {
+            self.state = NotificationState::Acknowledged;
+            self.acknowledged_at = Some(now);
+        }
+    }
+
+    pub fn resolve(&mut self, now: DateTime<Utc>) {
+        self.state = NotificationState::Resolved;
+        self.resolved_at = Some(now);
+    }
+}
+
+/// a resolved notification as it is kept in the history
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedNotification {
+    pub ipaddress: String,
+    #[serde(flatten)]
+    pub notification: Notification,
+}
+
+/// a page of the notification history, newest first
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NotificationHistoryPage {
+    pub page: u32,
+    pub page_size: u32,
+    pub total: i64,
+    pub list: Vec<ResolvedNotification>,
+}
+
+fn default_true() -> bool {
+    true
 }
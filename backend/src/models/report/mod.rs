@@ -0,0 +1,61 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::error::AppError;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    Day,
+    #[default]
+    Week,
+    /// the last 30 days
+    Month,
+}
+
+impl ReportPeriod {
+    pub fn get_duration(&self) -> Duration {
+        match self {
+            ReportPeriod::Day => Duration::days(1),
+            ReportPeriod::Week => Duration::days(7),
+            ReportPeriod::Month => Duration::days(30),
+        }
+    }
+}
+
+impl FromStr for ReportPeriod {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" => Ok(ReportPeriod::Day),
+            "week" => Ok(ReportPeriod::Week),
+            "month" => Ok(ReportPeriod::Month),
+            _ => Err(AppError::InvalidArgument(
+                "period".to_owned(),
+                Some(s.to_owned()),
+            )),
+        }
+    }
+}
+
+/// availability of a server - or of a feature for series with a sub identifier - within a period
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AvailabilityReport {
+    pub ipaddress: String,
+    #[serde(default)]
+    pub sub_identifier: Option<String>,
+    pub series_id: String,
+    pub period: ReportPeriod,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// not set if there is no data for the period
+    pub uptime_percentage: Option<f64>,
+    /// an outage that is still ongoing at the end of the period is counted as well
+    pub outages: u32,
+    /// mean time to recovery
+    pub mttr_seconds: Option<i64>,
+    pub longest_outage_seconds: Option<i64>,
+}
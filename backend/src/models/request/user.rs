@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+
+use crate::models::token::TokenScope;
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct PasswordChange {
@@ -6,3 +9,30 @@ pub struct PasswordChange {
     pub old_password: String,
     pub new_password: String,
 }
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ApiTokenCreation {
+    pub name: String,
+    pub scope: TokenScope,
+    /// the token doesn't expire if not set
+    #[serde(default)]
+    pub expires: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct TwoFactorCode {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct PasswordResetRequest {
+    pub user_id: String,
+}
+
+/// the password is encrypted like the password of the login
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct PasswordSetting {
+    pub user_id: String,
+    pub token: String,
+    pub password: String,
+}
@@ -60,6 +60,10 @@ impl Server {
         self.ipaddress
     }
 
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
     pub fn get_features(&self) -> Vec<Feature> {
         self.features.clone()
     }
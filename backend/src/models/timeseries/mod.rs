@@ -1,6 +1,11 @@
+use std::time::UNIX_EPOCH;
+
+use chrono::{DateTime, Utc};
 use questdb::ingress::Buffer;
 
-use super::error::AppError;
+use crate::common;
+
+use super::{error::AppError, plugin::monitoring::Aggregation};
 
 #[derive(Clone, Debug)]
 pub struct TimeSeriesData {
@@ -10,6 +15,51 @@ pub struct TimeSeriesData {
     pub timestamp: Timestamp,
 }
 
+impl TimeSeriesData {
+    /// Adds the data as one line of the influx line protocol
+    pub fn apply(&self, series_id: &str, buffer: &mut Buffer) -> Result<(), AppError> {
+        buffer
+            .table(series_id)
+            .map_err(|err| AppError::DatabaseError(format!("{}", err)))?;
+
+        let mut all_columns: Vec<&TimeSeriesValue> = vec![&self.identifier];
+        all_columns.extend(self.sub_identifiers.iter());
+        all_columns.push(&self.value);
+
+        // first add all symbols
+        for value in &all_columns {
+            if !value.is_symbol() {
+                continue;
+            }
+            value.apply(buffer)?;
+        }
+
+        for value in &all_columns {
+            if value.is_symbol() {
+                continue;
+            }
+            value.apply(buffer)?;
+        }
+
+        self.timestamp.apply(buffer)
+    }
+
+    /// the identifiers as labels, ie. for Prometheus
+    pub fn get_labels(&self) -> Vec<(&'static str, String)> {
+        let mut labels = vec![("identifier", self.identifier.value_as_string())];
+
+        for sub_identifier in &self.sub_identifiers {
+            let label = match sub_identifier.get_name() {
+                common::SUB_IDENTIFIER => "sub_identifier",
+                common::SUB_IDENTIFIER_2 => "value_name",
+                _ => continue,
+            };
+            labels.push((label, sub_identifier.value_as_string()));
+        }
+        labels
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum TimeSeriesValue {
     Bool(String, bool),
@@ -41,6 +91,35 @@ impl TimeSeriesValue {
         Ok(())
     }
 
+    pub fn get_name(&self) -> &str {
+        match self {
+            TimeSeriesValue::Bool(name, _)
+            | TimeSeriesValue::Int(name, _)
+            | TimeSeriesValue::Float(name, _)
+            | TimeSeriesValue::String(name, _)
+            | TimeSeriesValue::Symbol(name, _) => name,
+        }
+    }
+
+    /// only numeric values - a bool is converted to 1 or 0
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            TimeSeriesValue::Bool(_, value) => Some(if *value { 1.0 } else { 0.0 }),
+            TimeSeriesValue::Int(_, value) => Some(*value as f64),
+            TimeSeriesValue::Float(_, value) => Some(*value),
+            TimeSeriesValue::String(_, _) | TimeSeriesValue::Symbol(_, _) => None,
+        }
+    }
+
+    pub fn value_as_string(&self) -> String {
+        match self {
+            TimeSeriesValue::Bool(_, value) => format!("{}", value),
+            TimeSeriesValue::Int(_, value) => format!("{}", value),
+            TimeSeriesValue::Float(_, value) => format!("{}", value),
+            TimeSeriesValue::String(_, value) | TimeSeriesValue::Symbol(_, value) => value.clone(),
+        }
+    }
+
     pub fn is_symbol(&self) -> bool {
         matches!(self, TimeSeriesValue::Symbol(_, _))
     }
@@ -60,4 +139,31 @@ impl Timestamp {
         };
         Ok(())
     }
+
+    /// microseconds since the epoch
+    pub fn as_micros(&self) -> i64 {
+        match self {
+            Timestamp::SysTime(timestamp) => timestamp
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_micros() as i64)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A query for the values of one series that is independent of the time series db.
+/// The result has the columns of group_by, one column per aggregation and - if there is a bucket size - the timestamp of the bucket
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeSeriesQuery {
+    pub series_id: String,
+    /// the identifier columns that are returned. The values are aggregated per combination of these columns
+    pub group_by: Vec<String>,
+    /// only the values where the column has the given value
+    pub filters: Vec<(String, String)>,
+    pub from: DateTime<Utc>,
+    /// the values up to now if not set
+    pub to: Option<DateTime<Utc>>,
+    pub aggregations: Vec<Aggregation>,
+    /// without a bucket size there is one row per group for the whole range
+    pub bucket_seconds: Option<u64>,
 }
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+
+use crate::models::users::role::Role;
+
+/// The rights of an API token - the token never has more rights than its user
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScope {
+    /// read-only access like a viewer
+    Read,
+    /// can additionally execute actions like an operator
+    Actions,
+    Admin,
+}
+
+impl TokenScope {
+    pub fn get_role(&self) -> Role {
+        match self {
+            TokenScope::Read => Role::Viewer,
+            TokenScope::Actions => Role::Operator,
+            TokenScope::Admin => Role::Admin,
+        }
+    }
+}
+
+/// A named personal token for scripts and other integrations. Only the hash of the token is stored
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApiToken {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub scope: TokenScope,
+    #[serde(default)]
+    token_hash: String,
+    pub created: DateTime<Utc>,
+    #[serde(default)]
+    pub expires: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl ApiToken {
+    pub fn new(
+        id: String,
+        user_id: String,
+        name: String,
+        scope: TokenScope,
+        token_hash: String,
+        expires: Option<DateTime<Utc>>,
+    ) -> Self {
+        ApiToken {
+            id,
+            user_id,
+            name,
+            scope,
+            token_hash,
+            created: Utc::now(),
+            expires,
+            last_used: None,
+        }
+    }
+
+    pub fn get_token_hash(&self) -> String {
+        self.token_hash.clone()
+    }
+
+    pub fn copy_no_hash(&self) -> Self {
+        ApiToken {
+            token_hash: "".to_owned(),
+            ..self.clone()
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+/// Returned once on creation - the token itself cannot be read again afterwards
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CreatedApiToken {
+    #[serde(flatten)]
+    pub api_token: ApiToken,
+    pub token: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::models::users::{role::Permissions, User};
+
+    use super::*;
+
+    #[test]
+    fn test_expiry_and_scope() {
+        let now = Utc::now();
+        let mut api_token = ApiToken::new(
+            "1".to_owned(),
+            "admin".to_owned(),
+            "home assistant".to_owned(),
+            TokenScope::Read,
+            "hash".to_owned(),
+            None,
+        );
+        assert!(!api_token.is_expired(now));
+
+        api_token.expires = Some(now - Duration::seconds(1));
+        assert!(api_token.is_expired(now));
+
+        assert_eq!(api_token.copy_no_hash().get_token_hash(), "");
+
+        // the scope restricts the role, but never raises it
+        let mut user = User::new(
+            "admin".to_owned(),
+            "Admin".to_owned(),
+            "admin@example.com".to_owned(),
+            "".to_owned(),
+        );
+        user.update_role_and_permissions(Role::Admin, Permissions::default());
+        user.restrict_role(api_token.scope.get_role());
+        assert_eq!(user.get_role(), Role::Viewer);
+
+        user.restrict_role(TokenScope::Admin.get_role());
+        assert_eq!(user.get_role(), Role::Viewer);
+    }
+}
@@ -1,5 +1,9 @@
+mod apitoken;
 mod tokeninfo;
+mod useractiontoken;
 mod usertoken;
 
+pub use apitoken::{ApiToken, CreatedApiToken, TokenScope};
 pub use tokeninfo::TokenInfo;
+pub use useractiontoken::{UserAction, UserActionToken};
 pub use usertoken::UserToken;
@@ -1,21 +1,70 @@
-use sqlx::types::chrono::{NaiveDateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+use crate::models::users::role::Role;
+
+/// the last use is only updated with this precision to avoid a database write on every request
+const LAST_USED_PRECISION_SECONDS: i64 = 60;
+
+/// A token of a session or of the event stream. It expires, if it is not used for a while
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TokenInfo {
-    creation_date: NaiveDateTime,
+    user_id: String,
+    creation_date: DateTime<Utc>,
+    last_used: DateTime<Utc>,
+    /// restricts the rights of the session, ie. for an admin that still has to set up the two-factor authentication
+    #[serde(default)]
+    max_role: Option<Role>,
 }
 
-const TOKEN_EXPIRY_DURATION_SECS: i64 = 3600; // 1 hour
-
 impl TokenInfo {
-    pub fn new() -> Self {
+    pub fn new(user_id: &str, max_role: Option<Role>) -> Self {
+        let now = Utc::now();
+
         TokenInfo {
-            creation_date: Utc::now().naive_utc(),
+            user_id: user_id.to_owned(),
+            creation_date: now,
+            last_used: now,
+            max_role,
         }
     }
 
-    pub fn is_expired(&self) -> bool {
-        let now = Utc::now().naive_utc().timestamp();
-        self.creation_date.timestamp() - now > TOKEN_EXPIRY_DURATION_SECS
+    pub fn get_user_id(&self) -> String {
+        self.user_id.clone()
+    }
+
+    pub fn get_max_role(&self) -> Option<Role> {
+        self.max_role
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>, max_idle_seconds: i64) -> bool {
+        now - self.last_used > Duration::seconds(max_idle_seconds)
+    }
+
+    /// Updates the last use - returns false if the last use is recent enough and doesn't need to be saved
+    pub fn touch(&mut self, now: DateTime<Utc>) -> bool {
+        if now - self.last_used < Duration::seconds(LAST_USED_PRECISION_SECONDS) {
+            return false;
+        }
+        self.last_used = now;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry() {
+        let mut token_info = TokenInfo::new("admin", None);
+        let now = token_info.creation_date;
+
+        assert!(!token_info.is_expired(now + Duration::seconds(3600), 3600));
+        assert!(token_info.is_expired(now + Duration::seconds(3601), 3600));
+
+        assert!(!token_info.touch(now + Duration::seconds(30)));
+        assert!(token_info.touch(now + Duration::seconds(3000)));
+        assert!(!token_info.is_expired(now + Duration::seconds(3601), 3600));
     }
 }
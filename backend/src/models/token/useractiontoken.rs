@@ -0,0 +1,81 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+
+/// The purpose of a link that is sent by mail
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UserAction {
+    /// an invited user sets the first password
+    Invitation,
+    PasswordReset,
+}
+
+impl UserAction {
+    /// an invitation is not always read immediately, a password reset is requested by the user themself
+    pub fn get_validity(&self) -> Duration {
+        match self {
+            UserAction::Invitation => Duration::days(3),
+            UserAction::PasswordReset => Duration::hours(1),
+        }
+    }
+}
+
+/// A single-use token of a link that allows a user to set the password without logging in
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UserActionToken {
+    user_id: String,
+    action: UserAction,
+    created: DateTime<Utc>,
+    expires: DateTime<Utc>,
+}
+
+impl UserActionToken {
+    pub fn new(user_id: &str, action: UserAction) -> Self {
+        let now = Utc::now();
+
+        UserActionToken {
+            user_id: user_id.to_owned(),
+            action,
+            created: now,
+            expires: now + action.get_validity(),
+        }
+    }
+
+    pub fn get_user_id(&self) -> String {
+        self.user_id.clone()
+    }
+
+    pub fn get_action(&self) -> UserAction {
+        self.action
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires <= now
+    }
+
+    pub fn is_created_within(&self, now: DateTime<Utc>, duration: Duration) -> bool {
+        now - self.created < duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_expired() {
+        let token = UserActionToken::new("user", UserAction::PasswordReset);
+        let now = Utc::now();
+
+        assert!(!token.is_expired(now));
+        assert!(!token.is_expired(now + Duration::minutes(59)));
+        assert!(token.is_expired(now + Duration::hours(1)));
+
+        let token = UserActionToken::new("user", UserAction::Invitation);
+        assert!(!token.is_expired(now + Duration::days(2)));
+
+        assert!(token.is_created_within(now, Duration::minutes(15)));
+        assert!(!token.is_created_within(now + Duration::minutes(16), Duration::minutes(15)));
+    }
+}
@@ -5,4 +5,7 @@ pub struct UserToken {
     pub user_id: String,
     pub token: String,
     pub client_key: String,
+    /// the session has only the rights of a viewer until the two-factor authentication is set up
+    #[serde(default)]
+    pub two_factor_setup_required: bool,
 }
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, hash::Hash};
+pub mod notification_channel;
+pub mod role;
+pub mod two_factor;
+
+use std::{collections::HashMap, hash::Hash, net::IpAddr};
 
 use serde::{Deserialize, Serialize};
 
@@ -7,6 +11,9 @@ use crate::{
     event_handling::{EventSource, ObjectType, Value},
 };
 
+use self::notification_channel::NotificationChannel;
+use self::role::{Permissions, Role};
+use self::two_factor::TwoFactor;
 use super::error::AppError;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -18,6 +25,14 @@ pub struct User {
     password_hash: String,
     #[serde(default)]
     version: i64,
+    #[serde(default)]
+    notification_channels: Vec<NotificationChannel>,
+    #[serde(default)]
+    role: Role,
+    #[serde(default)]
+    permissions: Permissions,
+    #[serde(default)]
+    two_factor: TwoFactor,
 }
 
 impl User {
@@ -29,12 +44,19 @@ impl User {
             email,
             password_hash,
             version: 0,
+            notification_channels: Vec::new(),
+            role: Role::default(),
+            permissions: Permissions::default(),
+            two_factor: TwoFactor::default(),
         }
     }
 
+    /// the notification channels contain tokens - they are only available through their own route
     pub fn copy_no_passwd(&self) -> Self {
         User {
             password_hash: "".to_owned(),
+            notification_channels: Vec::new(),
+            two_factor: self.two_factor.copy_no_secrets(),
             ..self.clone()
         }
     }
@@ -59,6 +81,116 @@ impl User {
         self.full_name.clone()
     }
 
+    pub fn get_notification_channels(&self) -> Vec<NotificationChannel> {
+        self.notification_channels.clone()
+    }
+
+    pub fn set_notification_channels(&mut self, notification_channels: Vec<NotificationChannel>) {
+        self.notification_channels = notification_channels;
+    }
+
+    pub fn get_two_factor(&self) -> TwoFactor {
+        self.two_factor.clone()
+    }
+
+    pub fn set_two_factor(&mut self, two_factor: TwoFactor) {
+        self.two_factor = two_factor;
+    }
+
+    pub fn is_two_factor_enabled(&self) -> bool {
+        self.two_factor.enabled
+    }
+
+    pub fn get_role(&self) -> Role {
+        self.role
+    }
+
+    pub fn get_permissions(&self) -> Permissions {
+        self.permissions.clone()
+    }
+
+    /// takes over the values that can be changed by an admin
+    pub fn update_role_and_permissions(&mut self, role: Role, permissions: Permissions) {
+        self.role = role;
+        self.permissions = permissions;
+    }
+
+    /// the role is never raised - used for tokens with a limited scope
+    pub fn restrict_role(&mut self, max_role: Role) {
+        self.role = self.role.min(max_role);
+    }
+
+    pub fn update_name_and_email(&mut self, full_name: String, email: String) {
+        self.full_name = full_name;
+        self.email = email;
+    }
+
+    pub fn check_role(&self, required: Role) -> Result<(), AppError> {
+        if self.role >= required {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(format!(
+                "the role {} is required but user '{}' has the role {}",
+                required, self.user_id, self.role
+            )))
+        }
+    }
+
+    pub fn can_access_server(&self, ipaddress: &IpAddr) -> bool {
+        self.role == Role::Admin
+            || self.permissions.servers.is_empty()
+            || self.permissions.servers.contains(ipaddress)
+    }
+
+    pub fn check_server_access(&self, ipaddress: &IpAddr) -> Result<(), AppError> {
+        if self.can_access_server(ipaddress) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(format!(
+                "user '{}' has no access to server {}",
+                self.user_id, ipaddress
+            )))
+        }
+    }
+
+    /// ip addresses that are given as string, like in the notifications and reports
+    pub fn can_access_server_str(&self, ipaddress: &str) -> bool {
+        match ipaddress.parse::<IpAddr>() {
+            Ok(ipaddress) => self.can_access_server(&ipaddress),
+            Err(_) => !self.is_restricted_to_servers(),
+        }
+    }
+
+    pub fn is_restricted_to_servers(&self) -> bool {
+        self.role != Role::Admin && !self.permissions.servers.is_empty()
+    }
+
+    pub fn can_access_plugin(&self, plugin_id: &str) -> bool {
+        self.role == Role::Admin
+            || self.permissions.plugins.is_empty()
+            || self.permissions.plugins.iter().any(|id| id == plugin_id)
+    }
+
+    pub fn check_plugin_access(&self, plugin_id: &str) -> Result<(), AppError> {
+        if self.can_access_plugin(plugin_id) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(format!(
+                "user '{}' cannot execute actions of plugin {}",
+                self.user_id, plugin_id
+            )))
+        }
+    }
+
+    /// the user itself or an admin
+    pub fn check_self_or_admin(&self, user_id: &str) -> Result<(), AppError> {
+        if self.user_id == user_id {
+            Ok(())
+        } else {
+            self.check_role(Role::Admin)
+        }
+    }
+
     pub fn check_password(&self, password_to_check: &str) -> Result<bool, AppError> {
         common::verify_password(password_to_check, self.password_hash.as_str())
     }
@@ -99,3 +231,56 @@ impl EventSource for User {
         kv
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access_checks() {
+        let mut user = User::new(
+            "viewer".to_owned(),
+            "Viewer".to_owned(),
+            "viewer@example.com".to_owned(),
+            "".to_owned(),
+        );
+        let allowed: IpAddr = "192.168.178.2".parse().unwrap();
+        let other: IpAddr = "192.168.178.3".parse().unwrap();
+
+        user.update_role_and_permissions(
+            Role::Viewer,
+            Permissions {
+                servers: vec![allowed],
+                plugins: vec!["proxmox".to_owned()],
+            },
+        );
+
+        assert!(user.check_role(Role::Viewer).is_ok());
+        assert!(user.check_role(Role::Operator).is_err());
+        assert!(user.check_server_access(&allowed).is_ok());
+        assert!(user.check_server_access(&other).is_err());
+        assert!(!user.can_access_server_str("192.168.178.3"));
+        assert!(user.check_plugin_access("proxmox").is_ok());
+        assert!(user.check_plugin_access("tasmota").is_err());
+        assert!(user.check_self_or_admin("viewer").is_ok());
+        assert!(user.check_self_or_admin("admin").is_err());
+
+        // admins are not restricted by the permissions
+        user.update_role_and_permissions(Role::Admin, user.get_permissions());
+
+        assert!(user.check_server_access(&other).is_ok());
+        assert!(user.check_plugin_access("tasmota").is_ok());
+        assert!(user.check_self_or_admin("admin").is_ok());
+    }
+
+    #[test]
+    fn test_users_without_role_are_viewers() {
+        let user: User = serde_json::from_str(
+            r#"{"user_id":"viewer","full_name":"Viewer","email":"viewer@example.com"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(user.get_role(), Role::Viewer);
+        assert_eq!(user.get_permissions(), Permissions::default());
+    }
+}
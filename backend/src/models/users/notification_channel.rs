@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common,
+    models::{error::AppError, plugin::notification::Level},
+};
+
+/// A channel on which a user receives the notifications raised for the servers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct NotificationChannel {
+    pub id: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// only notifications with this level or a higher one are sent
+    #[serde(default)]
+    pub min_level: Level,
+    #[serde(flatten)]
+    pub channel_type: NotificationChannelType,
+}
+
+impl NotificationChannel {
+    pub fn accepts(&self, level: &Level) -> bool {
+        self.enabled && *level >= self.min_level
+    }
+
+    /// the url of a channel that sends requests to a configurable host - None for mails and the default Telegram API
+    pub fn get_url(&self) -> Option<&str> {
+        match &self.channel_type {
+            NotificationChannelType::Email { .. } => None,
+            NotificationChannelType::Webhook { url, .. }
+            | NotificationChannelType::Ntfy { url, .. }
+            | NotificationChannelType::Gotify { url, .. } => Some(url),
+            NotificationChannelType::Telegram { api_url, .. } => api_url.as_deref(),
+        }
+    }
+
+    /// the tokens and headers are stored encrypted with the crypto key like the credentials of the features
+    pub fn encrypt_secrets(&self, crypto_key: &str) -> Result<Self, AppError> {
+        self.map_secrets(|value| common::default_encrypt(value, crypto_key))
+    }
+
+    pub fn decrypt_secrets(&self, crypto_key: &str) -> Result<Self, AppError> {
+        self.map_secrets(|value| common::default_decrypt(value, crypto_key))
+    }
+
+    fn map_secrets(
+        &self,
+        map: impl Fn(&str) -> Result<String, AppError>,
+    ) -> Result<Self, AppError> {
+        let channel_type = match &self.channel_type {
+            NotificationChannelType::Email { address } => NotificationChannelType::Email {
+                address: address.clone(),
+            },
+            NotificationChannelType::Webhook { url, headers } => NotificationChannelType::Webhook {
+                url: url.clone(),
+                headers: headers
+                    .iter()
+                    .map(|header| map(header))
+                    .collect::<Result<Vec<String>, AppError>>()?,
+            },
+            NotificationChannelType::Ntfy { url, token } => NotificationChannelType::Ntfy {
+                url: url.clone(),
+                token: token.as_deref().map(&map).transpose()?,
+            },
+            NotificationChannelType::Gotify { url, token } => NotificationChannelType::Gotify {
+                url: url.clone(),
+                token: map(token)?,
+            },
+            NotificationChannelType::Telegram {
+                bot_token,
+                chat_id,
+                api_url,
+            } => NotificationChannelType::Telegram {
+                bot_token: map(bot_token)?,
+                chat_id: chat_id.clone(),
+                api_url: api_url.clone(),
+            },
+        };
+
+        Ok(NotificationChannel {
+            channel_type,
+            ..self.clone()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "channel_type", rename_all = "lowercase")]
+pub enum NotificationChannelType {
+    /// sends a mail using the SMTP config of the .env file. Without an address the mail address of the user is used
+    Email {
+        #[serde(default)]
+        address: Option<String>,
+    },
+    /// posts the notification as JSON to the url. Headers are given as name=value
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: Vec<String>,
+    },
+    /// url of the ntfy server including the topic, ie. https://ntfy.sh/my_topic
+    Ntfy {
+        url: String,
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// url of the Gotify server and the token of the application
+    Gotify { url: String, token: String },
+    /// the api url only needs to be set for a self hosted Bot API server
+    Telegram {
+        bot_token: String,
+        chat_id: String,
+        #[serde(default)]
+        api_url: Option<String>,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_secrets() {
+        let crypto_key = "this is a key";
+        let channel = NotificationChannel {
+            id: "telegram".to_owned(),
+            enabled: true,
+            min_level: Level::Error,
+            channel_type: NotificationChannelType::Telegram {
+                bot_token: "bot token".to_owned(),
+                chat_id: "chat".to_owned(),
+                api_url: None,
+            },
+        };
+
+        let encrypted = channel.encrypt_secrets(crypto_key).unwrap();
+        let NotificationChannelType::Telegram {
+            bot_token, chat_id, ..
+        } = &encrypted.channel_type
+        else {
+            panic!("unexpected channel type");
+        };
+        assert_ne!(bot_token, "bot token");
+        assert_eq!(chat_id, "chat");
+        assert_eq!(encrypted.get_url(), None);
+
+        assert_eq!(encrypted.decrypt_secrets(crypto_key).unwrap(), channel);
+    }
+}
@@ -0,0 +1,40 @@
+use std::{fmt::Display, net::IpAddr};
+
+use serde::{Deserialize, Serialize};
+
+/// The role of a user - each role includes the rights of the roles before
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// can look at the servers, dashboards, monitoring data and notifications.
+    /// Default, so that a request without a role never creates an admin - users stored before the roles existed are migrated to admins
+    #[default]
+    Viewer,
+    /// can additionally execute actions of features, scan for features and handle notifications and maintenance windows
+    Operator,
+    /// can additionally change servers, plugins, users and the configuration
+    Admin,
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Viewer => write!(f, "viewer"),
+            Role::Operator => write!(f, "operator"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+/// Restricts a user to some servers and plugins - an empty list means no restriction. Admins are never restricted
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Permissions {
+    /// the servers the user can see and use
+    #[serde(default)]
+    pub servers: Vec<IpAddr>,
+    /// the plugins whose actions the user can execute
+    #[serde(default)]
+    pub plugins: Vec<String>,
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{common, models::error::AppError};
+
+/// The TOTP configuration of a user
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct TwoFactor {
+    /// true after the first code was verified - the secret of an unfinished enrollment is not used for the login
+    #[serde(default)]
+    pub enabled: bool,
+    /// encrypted with the crypto key
+    #[serde(default)]
+    secret: String,
+    /// SHA-256 hashes of the recovery codes that were not used yet
+    #[serde(default)]
+    recovery_codes: Vec<String>,
+    /// a code cannot be used again for the same or an older time step
+    #[serde(default)]
+    last_used_step: u64,
+}
+
+impl TwoFactor {
+    /// starts an enrollment with a new secret
+    pub fn new(secret: &str, crypto_key: &str) -> Result<Self, AppError> {
+        Ok(TwoFactor {
+            enabled: false,
+            secret: common::default_encrypt(secret, crypto_key)?,
+            recovery_codes: Vec::new(),
+            last_used_step: 0,
+        })
+    }
+
+    pub fn is_enrollment_started(&self) -> bool {
+        !self.secret.is_empty()
+    }
+
+    /// Finishes the enrollment and returns the recovery codes - they are only stored as hashes
+    pub fn enable(&mut self) -> Vec<String> {
+        let recovery_codes = common::generate_recovery_codes();
+
+        self.enabled = true;
+        self.recovery_codes = recovery_codes
+            .iter()
+            .map(|code| common::hash_token(code))
+            .collect();
+
+        recovery_codes
+    }
+
+    /// Checks a TOTP code or a recovery code. A used recovery code is removed
+    pub fn verify(
+        &mut self,
+        code: &str,
+        crypto_key: &str,
+        unix_time: u64,
+    ) -> Result<bool, AppError> {
+        let secret = common::default_decrypt(&self.secret, crypto_key)?;
+
+        if let Some(step) = common::verify_totp_code(&secret, code, unix_time, self.last_used_step)?
+        {
+            self.last_used_step = step;
+            return Ok(true);
+        }
+
+        let code_hash = common::hash_token(code.trim());
+        match self
+            .recovery_codes
+            .iter()
+            .position(|hash| *hash == code_hash)
+        {
+            Some(index) => {
+                self.recovery_codes.remove(index);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// only the information whether it is enabled is sent to the client
+    pub fn copy_no_secrets(&self) -> Self {
+        TwoFactor {
+            enabled: self.enabled,
+            ..TwoFactor::default()
+        }
+    }
+}
+
+/// The data that is needed to add the account to an authenticator app
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TwoFactorEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_codes() {
+        let crypto_key = "this is a key";
+        let mut two_factor = TwoFactor::new(&common::generate_totp_secret(), crypto_key).unwrap();
+        assert!(two_factor.is_enrollment_started());
+
+        let recovery_codes = two_factor.enable();
+        assert_eq!(two_factor.recovery_codes.len(), recovery_codes.len());
+
+        assert!(two_factor
+            .verify(&recovery_codes[0], crypto_key, 59)
+            .unwrap());
+        // can only be used once
+        assert!(!two_factor
+            .verify(&recovery_codes[0], crypto_key, 59)
+            .unwrap());
+        assert_eq!(two_factor.recovery_codes.len(), recovery_codes.len() - 1);
+
+        assert_eq!(two_factor.copy_no_secrets().recovery_codes.len(), 0);
+        assert!(two_factor.copy_no_secrets().enabled);
+    }
+}
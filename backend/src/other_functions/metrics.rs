@@ -0,0 +1,208 @@
+use std::{
+    fmt::Write,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    datastore,
+    init::{self, ProcessTiming},
+    models::{
+        error::AppError,
+        timeseries::{TimeSeriesData, Timestamp},
+    },
+};
+
+const PREFIX: &str = "server_manager";
+
+/// values that were not updated within this time are not exported anymore - ie. of removed servers or features
+const MAX_VALUE_AGE: Duration = Duration::from_secs(15 * 60);
+
+/// the running state of a server
+struct ServerState {
+    ipaddress: String,
+    name: String,
+    is_running: bool,
+}
+
+/// Renders the server status, the latest monitoring values and the timings of the background processes in the Prometheus text format
+pub async fn render_metrics() -> Result<String, AppError> {
+    let servers: Vec<ServerState> = datastore::get_all_servers_from_cache()?
+        .iter()
+        .filter_map(|server| {
+            let status = datastore::get_status(&server.get_ipaddress()).ok()??;
+
+            Some(ServerState {
+                ipaddress: format!("{}", server.get_ipaddress()),
+                name: server.get_name(),
+                is_running: status.is_running,
+            })
+        })
+        .collect();
+
+    let now = SystemTime::now();
+    let values: Vec<(String, TimeSeriesData)> = datastore::get_latest_timeseries_data()?
+        .into_iter()
+        .filter(|(_, data)| match data.timestamp {
+            Timestamp::SysTime(timestamp) => now
+                .duration_since(timestamp)
+                .is_ok_and(|age| age <= MAX_VALUE_AGE),
+        })
+        .collect();
+
+    let timings = init::get_process_timings().await;
+
+    Ok(render(&servers, &values, &timings))
+}
+
+fn render(
+    servers: &[ServerState],
+    values: &[(String, TimeSeriesData)],
+    timings: &[ProcessTiming],
+) -> String {
+    let mut out = String::new();
+
+    write_header(
+        &mut out,
+        "server_up",
+        "Whether the server was reachable at the last status check (1) or not (0)",
+    );
+    for server in servers {
+        write_sample(
+            &mut out,
+            "server_up",
+            &[("ipaddress", &server.ipaddress), ("name", &server.name)],
+            if server.is_running { 1.0 } else { 0.0 },
+        );
+    }
+
+    write_header(
+        &mut out,
+        "monitoring_value",
+        "Latest value of a monitoring series",
+    );
+    let mut samples: Vec<(Vec<(&str, String)>, f64)> = values
+        .iter()
+        .filter_map(|(series_id, data)| {
+            let value = data.value.as_f64()?;
+
+            let mut labels = vec![("series_id", series_id.clone())];
+            labels.extend(data.get_labels());
+
+            Some((labels, value))
+        })
+        .collect();
+    samples.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (labels, value) in &samples {
+        let labels: Vec<(&str, &str)> = labels
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        write_sample(&mut out, "monitoring_value", &labels, *value);
+    }
+
+    write_header(
+        &mut out,
+        "process_duration_seconds",
+        "Duration of the last run of a background process",
+    );
+    for timing in timings {
+        write_sample(
+            &mut out,
+            "process_duration_seconds",
+            &[("process", &timing.process)],
+            timing.duration_seconds,
+        );
+    }
+
+    write_header(
+        &mut out,
+        "process_last_run_timestamp_seconds",
+        "End of the last run of a background process",
+    );
+    for timing in timings {
+        write_sample(
+            &mut out,
+            "process_last_run_timestamp_seconds",
+            &[("process", &timing.process)],
+            timing.end.timestamp_millis() as f64 / 1000.0,
+        );
+    }
+
+    out
+}
+
+fn write_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {}_{} {}", PREFIX, name, help);
+    let _ = writeln!(out, "# TYPE {}_{} gauge", PREFIX, name);
+}
+
+fn write_sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: f64) {
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+        .collect();
+
+    let _ = writeln!(out, "{}_{}{{{}}} {}", PREFIX, name, labels.join(","), value);
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{common, models::timeseries::TimeSeriesValue};
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let servers = vec![ServerState {
+            ipaddress: "192.168.178.2".to_owned(),
+            name: "NAS \"main\"".to_owned(),
+            is_running: true,
+        }];
+        let values = vec![(
+            "disk_usage".to_owned(),
+            TimeSeriesData {
+                identifier: TimeSeriesValue::Symbol(
+                    common::IDENTIFIER.to_owned(),
+                    "192.168.178.2".to_owned(),
+                ),
+                sub_identifiers: vec![
+                    TimeSeriesValue::Symbol(common::SUB_IDENTIFIER.to_owned(), "/".to_owned()),
+                    TimeSeriesValue::Symbol(common::SUB_IDENTIFIER_2.to_owned(), "free".to_owned()),
+                ],
+                value: TimeSeriesValue::Float(common::VALUE.to_owned(), 12.5),
+                timestamp: Timestamp::SysTime(SystemTime::now()),
+            },
+        )];
+        let timings = vec![ProcessTiming {
+            process: "monitoring".to_owned(),
+            duration_seconds: 1.5,
+            end: Utc.with_ymd_and_hms(2023, 9, 1, 2, 0, 0).unwrap(),
+        }];
+
+        let out = render(&servers, &values, &timings);
+
+        assert!(out.contains("# TYPE server_manager_server_up gauge\n"));
+        assert!(out.contains(
+            "server_manager_server_up{ipaddress=\"192.168.178.2\",name=\"NAS \\\"main\\\"\"} 1\n"
+        ));
+        assert!(out.contains(
+            "server_manager_monitoring_value{series_id=\"disk_usage\",identifier=\"192.168.178.2\",sub_identifier=\"/\",value_name=\"free\"} 12.5\n"
+        ));
+        assert!(
+            out.contains("server_manager_process_duration_seconds{process=\"monitoring\"} 1.5\n")
+        );
+        assert!(out.contains(
+            "server_manager_process_last_run_timestamp_seconds{process=\"monitoring\"} 1693533600\n"
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod metrics;
 pub mod statuscheck;
 pub mod systeminfo;
 pub mod upnp;
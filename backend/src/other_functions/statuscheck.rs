@@ -1,17 +1,46 @@
-use std::net::IpAddr;
+use std::{collections::HashMap, net::IpAddr};
 
+use chrono::{DateTime, Utc};
 use futures::future::join_all;
 use lazy_static::lazy_static;
-use tokio::sync::Semaphore;
+use tokio::sync::{RwLock, Semaphore};
 
 use crate::{
     commands::{self, ping::PingCommandResult},
+    common,
     datastore::{self, TimeSeriesPersistence},
-    models::{self, error::AppError, response::status::Status},
+    models::{
+        self,
+        error::AppError,
+        plugin::notification::{Level, Notification},
+        response::status::Status,
+    },
+    plugin_execution,
 };
 
+const SERVER_DOWN_NOTIFICATION_ID: &str = "server_down";
+const DEFAULT_FAILED_CHECKS_FOR_ALERT: i64 = 3;
+
 lazy_static! {
     static ref SEMAPHORE_STATUS_CHECK: Semaphore = Semaphore::new(1);
+    static ref AVAILABILITY: RwLock<HashMap<IpAddr, Availability>> = RwLock::new(HashMap::new());
+}
+
+/// the result of the last status checks of a server
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Availability {
+    failed_checks: u32,
+    /// time of the first failed check in a row
+    down_since: Option<DateTime<Utc>>,
+    /// true while the server down notification is raised
+    alerted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Transition {
+    Down(DateTime<Utc>),
+    /// contains the time since when the server was down - if known
+    Up(Option<DateTime<Utc>>),
 }
 
 pub async fn status_check_all(silent: &bool) -> Result<(), AppError> {
@@ -57,6 +86,13 @@ pub async fn status_check_all(silent: &bool) -> Result<(), AppError> {
 
     log::debug!("inserting {} status into cache", results_from_query.len());
 
+    if let Err(err) = process_availability(&results_from_query).await {
+        log::error!(
+            "Could not process server up/down alerts. Error was: {}",
+            err
+        );
+    }
+
     let data = models::status_list_to_timeseries_data_list(results_from_query);
 
     datastore::save_timeseries_data(
@@ -70,6 +106,150 @@ pub async fn status_check_all(silent: &bool) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Raises a notification once a server failed the configured number of status checks in a row and resolves it with the downtime when it is reachable again
+async fn process_availability(status_list: &[Status]) -> Result<(), AppError> {
+    let failed_checks_for_alert = datastore::get_config()?
+        .get_int("status_alert_failed_checks")
+        .unwrap_or(DEFAULT_FAILED_CHECKS_FOR_ALERT);
+
+    if failed_checks_for_alert <= 0 {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let mut availability_map = AVAILABILITY.write().await;
+
+    for status in status_list {
+        let ipaddress = format!("{}", status.ipaddress);
+
+        let availability = match availability_map.get_mut(&status.ipaddress) {
+            Some(availability) => availability,
+            None => {
+                // a notification could still be open from before a restart
+                let alerted = match plugin_execution::is_notification_open(
+                    &ipaddress,
+                    SERVER_DOWN_NOTIFICATION_ID,
+                )
+                .await
+                {
+                    Ok(alerted) => alerted,
+                    Err(err) => {
+                        log::error!(
+                            "Could not check the open notifications of server {}. Error was: {}",
+                            ipaddress,
+                            err
+                        );
+                        continue;
+                    }
+                };
+
+                availability_map
+                    .entry(status.ipaddress)
+                    .or_insert(Availability {
+                        alerted,
+                        ..Availability::default()
+                    })
+            }
+        };
+
+        if let Err(err) = process_server_availability(
+            availability,
+            &ipaddress,
+            status.is_running,
+            failed_checks_for_alert as u32,
+            now,
+        )
+        .await
+        {
+            log::error!(
+                "Could not process the availability of server {}. Error was: {}",
+                ipaddress,
+                err
+            );
+        }
+    }
+    Ok(())
+}
+
+/// the availability is only changed once the notification was raised or resolved, so that a failed attempt is repeated with the next check
+async fn process_server_availability(
+    availability: &mut Availability,
+    ipaddress: &str,
+    is_running: bool,
+    failed_checks_for_alert: u32,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    match check_transition(availability, is_running, failed_checks_for_alert, now) {
+        Some(Transition::Down(down_since)) => {
+            log::info!("Server {} is down since {}", ipaddress, down_since);
+
+            plugin_execution::raise_notification(
+                ipaddress,
+                Notification::new(
+                    SERVER_DOWN_NOTIFICATION_ID,
+                    "Server down",
+                    &format!(
+                        "The server is not reachable since {}",
+                        down_since.format("%Y-%m-%d %H:%M:%S UTC")
+                    ),
+                    Level::Critical,
+                ),
+            )
+            .await?;
+
+            availability.alerted = true;
+        }
+        Some(Transition::Up(down_since)) => {
+            let message = match down_since {
+                Some(down_since) => format!(
+                    "The server is reachable again after a downtime of {}",
+                    common::format_duration(now - down_since)
+                ),
+                None => "The server is reachable again".to_owned(),
+            };
+
+            log::info!("Server {} is up: {}", ipaddress, message);
+
+            plugin_execution::resolve_notification_with_message(
+                ipaddress,
+                SERVER_DOWN_NOTIFICATION_ID,
+                &message,
+            )
+            .await?;
+
+            *availability = Availability::default();
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+fn check_transition(
+    availability: &mut Availability,
+    is_running: bool,
+    failed_checks_for_alert: u32,
+    now: DateTime<Utc>,
+) -> Option<Transition> {
+    if is_running {
+        // reset by the caller once the notification is resolved
+        if availability.alerted {
+            return Some(Transition::Up(availability.down_since));
+        }
+
+        *availability = Availability::default();
+        return None;
+    }
+
+    availability.failed_checks += 1;
+    let down_since = *availability.down_since.get_or_insert(now);
+
+    // marked as alerted by the caller once the notification is raised
+    if !availability.alerted && availability.failed_checks >= failed_checks_for_alert {
+        return Some(Transition::Down(down_since));
+    }
+    None
+}
+
 pub async fn status_check(
     ips_to_check: Vec<IpAddr>,
     use_cache: bool,
@@ -97,3 +277,47 @@ pub async fn status_check(
     };
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_check_transition() {
+        let start = Utc::now();
+        let mut availability = Availability::default();
+
+        assert_eq!(check_transition(&mut availability, true, 3, start), None);
+        assert_eq!(check_transition(&mut availability, false, 3, start), None);
+        assert_eq!(
+            check_transition(&mut availability, false, 3, start + Duration::minutes(1)),
+            None
+        );
+        assert_eq!(
+            check_transition(&mut availability, false, 3, start + Duration::minutes(2)),
+            Some(Transition::Down(start))
+        );
+        // repeated as long as the notification could not be raised
+        assert_eq!(
+            check_transition(&mut availability, false, 3, start + Duration::minutes(3)),
+            Some(Transition::Down(start))
+        );
+        // only raised once
+        availability.alerted = true;
+        assert_eq!(
+            check_transition(&mut availability, false, 3, start + Duration::minutes(4)),
+            None
+        );
+        assert_eq!(
+            check_transition(&mut availability, true, 3, start + Duration::minutes(5)),
+            Some(Transition::Up(Some(start)))
+        );
+        // repeated as long as the notification could not be resolved
+        assert_eq!(
+            check_transition(&mut availability, true, 3, start + Duration::minutes(6)),
+            Some(Transition::Up(Some(start)))
+        );
+    }
+}
@@ -7,8 +7,10 @@ use futures::future::join_all;
 
 use crate::{
     commands::{
-        self, http::HttpCommandResult, kafka::KafkaCommandResult, ping::PingCommandResult,
-        socket::SocketCommandResult, wol::WolCommandResult,
+        self, dns::DnsCommandResult, http::HttpCommandResult, kafka::KafkaCommandResult,
+        modbus::ModbusCommandResult, mqtt::MqttCommandResult, ping::PingCommandResult,
+        script::ScriptCommandResult, snmp::SnmpCommandResult, socket::SocketCommandResult,
+        ssh::SshCommandResult, tcp::TcpCommandResult, wol::WolCommandResult,
     },
     datastore,
     models::response::data_result::ConditionCheckResult,
@@ -116,6 +118,139 @@ pub async fn execute_action(
 
                 Ok(results.iter().any(|r| !r.get_response().is_empty()))
             }
+            commands::ssh::SSH => {
+                let inputs = commands::ssh::make_command_input_from_subaction(
+                    server,
+                    &crypto_key,
+                    plugin_action,
+                    action_params,
+                    feature,
+                    &plugin,
+                    silent,
+                )
+                .await?;
+
+                let mut results: Vec<SshCommandResult> = Vec::new();
+                for input in inputs {
+                    results.push(commands::execute(input, silent).await?);
+                }
+
+                Ok(!results.is_empty() && results.iter().all(|r| r.get_exit_code() == 0))
+            }
+            commands::snmp::SNMP => {
+                let inputs = commands::snmp::make_command_input_from_subaction(
+                    server,
+                    &crypto_key,
+                    plugin_action,
+                    action_params,
+                    feature,
+                    &plugin,
+                    silent,
+                )
+                .await?;
+
+                let mut results: Vec<SnmpCommandResult> = Vec::new();
+                for input in inputs {
+                    results.push(commands::execute(input, silent).await?);
+                }
+
+                Ok(results.iter().any(|r| !r.get_response().is_empty()))
+            }
+            commands::mqtt::MQTT => {
+                let inputs = commands::mqtt::make_command_input_from_subaction(
+                    server,
+                    &crypto_key,
+                    plugin_action,
+                    action_params,
+                    feature,
+                    &plugin,
+                    silent,
+                )
+                .await?;
+
+                let mut results: Vec<MqttCommandResult> = Vec::new();
+                for input in inputs {
+                    results.push(commands::execute(input, silent).await?);
+                }
+
+                Ok(!results.is_empty()) // a response is optional - publishing without error is enough
+            }
+            commands::tcp::TCP => {
+                let inputs = commands::tcp::make_command_input_from_subaction(
+                    server,
+                    &crypto_key,
+                    plugin_action,
+                    action_params,
+                    feature,
+                    &plugin,
+                    silent,
+                )
+                .await?;
+
+                let mut results: Vec<TcpCommandResult> = Vec::new();
+                for input in inputs {
+                    results.push(commands::execute(input, silent).await?);
+                }
+
+                Ok(!results.is_empty() && results.iter().all(|r| r.is_reachable()))
+            }
+            commands::dns::DNS => {
+                let inputs = commands::dns::make_command_input_from_subaction(
+                    server,
+                    &crypto_key,
+                    plugin_action,
+                    action_params,
+                    feature,
+                    &plugin,
+                    silent,
+                )
+                .await?;
+
+                let mut results: Vec<DnsCommandResult> = Vec::new();
+                for input in inputs {
+                    results.push(commands::execute(input, silent).await?);
+                }
+
+                Ok(!results.is_empty() && results.iter().all(|r| r.is_resolved()))
+            }
+            commands::modbus::MODBUS => {
+                let inputs = commands::modbus::make_command_input_from_subaction(
+                    server,
+                    &crypto_key,
+                    plugin_action,
+                    action_params,
+                    feature,
+                    &plugin,
+                    silent,
+                )
+                .await?;
+
+                let mut results: Vec<ModbusCommandResult> = Vec::new();
+                for input in inputs {
+                    results.push(commands::execute(input, silent).await?);
+                }
+
+                Ok(results.iter().any(|r| !r.get_response().is_empty()))
+            }
+            commands::script::SCRIPT => {
+                let inputs = commands::script::make_command_input_from_subaction(
+                    server,
+                    &crypto_key,
+                    plugin_action,
+                    action_params,
+                    feature,
+                    &plugin,
+                    silent,
+                )
+                .await?;
+
+                let mut results: Vec<ScriptCommandResult> = Vec::new();
+                for input in inputs {
+                    results.push(commands::execute(input, silent).await?);
+                }
+
+                Ok(results.iter().any(|r| !r.get_response().is_empty()))
+            }
             y => {
                 log::error!("Unknown command {}", y);
                 Err(AppError::CommandNotFound(y.to_string()))
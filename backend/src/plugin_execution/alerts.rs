@@ -0,0 +1,339 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    common,
+    datastore::{self, TimeSeriesQuery},
+    models::{
+        alert::{AlertRule, WindowValues},
+        error::AppError,
+        plugin::{
+            monitoring::{Aggregation, MonitioringDef, TimeSeriesResponseData},
+            notification::Notification,
+        },
+    },
+};
+
+use super::{monitoring, notifications};
+
+/// without a duration only the latest value is checked - but it should not be older than this.
+/// With a duration a series needs a value within this time before the duration starts
+const MIN_WINDOW_SECONDS: u64 = 120;
+const NOTIFICATION_ID_PREFIX: &str = "alert";
+
+/// the aggregated values of one series within the window of a rule, ie. the values of one disk of a server
+#[derive(Debug, Clone, PartialEq)]
+struct SeriesValues {
+    ipaddress: String,
+    sub_identifiers: Vec<String>,
+    values: WindowValues,
+}
+
+/// Checks all enabled alert rules against the monitoring data in the time series db.
+/// A breached rule raises a notification that stays open until the value doesn't breach the clear threshold of the rule anymore
+pub async fn evaluate_alert_rules() -> Result<(), AppError> {
+    for rule in datastore::get_all_alert_rules()
+        .await?
+        .iter()
+        .filter(|rule| rule.enabled)
+    {
+        if let Err(err) = evaluate_alert_rule(rule).await {
+            log::error!("Could not evaluate alert rule {}: {}", rule.id, err);
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the rule and resolves the notifications it raised
+pub async fn delete_alert_rule(id: &str) -> Result<(), AppError> {
+    datastore::delete_alert_rule(id).await?;
+
+    let rule_notification_id = format!("{}.{}", NOTIFICATION_ID_PREFIX, id);
+
+    for notifications in datastore::get_all_notifications().await? {
+        for notification in notifications.list.iter().filter(|n| {
+            n.id == rule_notification_id || n.id.starts_with(&format!("{}.", rule_notification_id))
+        }) {
+            notifications::resolve_notification(&notifications.ipaddress, &notification.id).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn evaluate_alert_rule(rule: &AlertRule) -> Result<(), AppError> {
+    let monitoring = monitoring::get_monitoring_config_for_series(&rule.series_id)?
+        .ok_or(AppError::DataNotFound(rule.series_id.clone()))?;
+
+    let query = create_alert_query(rule, &monitoring, Utc::now());
+
+    log::trace!("querying values for alert rule {}: {:?}", rule.id, query);
+
+    let data = datastore::query_timeseries_data(&query).await?;
+
+    // the series that have values from before the duration - only needed if all values within the duration are checked
+    let covered = if rule.duration_seconds == 0 {
+        Vec::new()
+    } else {
+        parse_series_values(
+            &datastore::query_timeseries_data(&create_coverage_query(&query)).await?,
+        )
+    };
+
+    for mut series in parse_series_values(&data) {
+        series.values.covers_duration = covered.iter().any(|covered| {
+            covered.ipaddress == series.ipaddress
+                && covered.sub_identifiers == series.sub_identifiers
+        });
+
+        let id = get_notification_id(rule, &series.sub_identifiers);
+        let is_open = notifications::is_notification_open(&series.ipaddress, &id).await?;
+
+        if is_open && rule.is_cleared(&series.values) {
+            log::debug!("alert {} for {} is resolved", id, series.ipaddress);
+
+            notifications::resolve_notification(&series.ipaddress, &id).await?;
+        } else if is_open || rule.is_breached(&series.values) {
+            // an open alert is only updated with the current value
+            notifications::raise_notification(
+                &series.ipaddress,
+                make_notification(rule, &id, &series),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+fn create_alert_query(
+    rule: &AlertRule,
+    monitoring: &MonitioringDef,
+    now: DateTime<Utc>,
+) -> TimeSeriesQuery {
+    let window_seconds = rule.duration_seconds.max(MIN_WINDOW_SECONDS);
+
+    let mut group_by: Vec<String> = vec![common::IDENTIFIER.to_owned()];
+    let mut filters: Vec<(String, String)> = Vec::new();
+
+    if let Some(ipaddress) = &rule.ipaddress {
+        filters.push((common::IDENTIFIER.to_owned(), ipaddress.clone()));
+    }
+
+    if monitoring.sub_identifier.is_some() {
+        group_by.push(common::SUB_IDENTIFIER.to_owned());
+
+        if let Some(sub_identifier) = &rule.sub_identifier {
+            filters.push((common::SUB_IDENTIFIER.to_owned(), sub_identifier.clone()));
+        }
+    }
+
+    if monitoring.values.len() > 1 {
+        group_by.push(common::SUB_IDENTIFIER_2.to_owned());
+
+        if let Some(value_name) = &rule.value_name {
+            filters.push((common::SUB_IDENTIFIER_2.to_owned(), value_name.clone()));
+        }
+    }
+
+    TimeSeriesQuery {
+        series_id: rule.series_id.clone(),
+        group_by,
+        filters,
+        from: now - Duration::seconds(window_seconds as i64),
+        to: None,
+        aggregations: vec![Aggregation::Min, Aggregation::Max, Aggregation::Last],
+        bucket_seconds: None,
+    }
+}
+
+/// the values right before the window of the query - if a series has values there, its values cover the whole window
+fn create_coverage_query(query: &TimeSeriesQuery) -> TimeSeriesQuery {
+    TimeSeriesQuery {
+        from: query.from - Duration::seconds(MIN_WINDOW_SECONDS as i64),
+        to: Some(query.from),
+        ..query.clone()
+    }
+}
+
+/// each row starts with the identifier and the sub identifiers and ends with the aggregated values
+fn parse_series_values(data: &TimeSeriesResponseData) -> Vec<SeriesValues> {
+    data.dataset
+        .iter()
+        .filter(|row| row.len() > 3)
+        .filter_map(|row| {
+            let (identifiers, values) = row.split_at(row.len() - 3);
+
+            Some(SeriesValues {
+                ipaddress: common::convert_value_to_str(&identifiers[0])?,
+                sub_identifiers: identifiers[1..]
+                    .iter()
+                    .filter_map(common::convert_value_to_str)
+                    .collect(),
+                values: WindowValues {
+                    min: values[0].as_f64()?,
+                    max: values[1].as_f64()?,
+                    last: values[2].as_f64()?,
+                    covers_duration: false,
+                },
+            })
+        })
+        .collect()
+}
+
+/// one notification per rule and series - the sub identifiers are part of the id so that it can be used in an url
+fn get_notification_id(rule: &AlertRule, sub_identifiers: &[String]) -> String {
+    let mut id = format!("{}.{}", NOTIFICATION_ID_PREFIX, rule.id);
+
+    for sub_identifier in sub_identifiers {
+        id.push('.');
+        id.push_str(
+            &sub_identifier
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>(),
+        );
+    }
+    id
+}
+
+fn make_notification(rule: &AlertRule, id: &str, series: &SeriesValues) -> Notification {
+    let name = if series.sub_identifiers.is_empty() {
+        rule.name.clone()
+    } else {
+        format!("{} ({})", rule.name, series.sub_identifiers.join(", "))
+    };
+
+    let current = format!(
+        "Current value is {} ({} {})",
+        series.values.last,
+        rule.comparison.as_str(),
+        rule.threshold
+    );
+
+    let message = if rule.message.is_empty() {
+        current
+    } else {
+        format!("{}. {}", rule.message, current)
+    };
+
+    Notification::new(id, &name, &message, rule.notification_level.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::models::{
+        alert::Comparison,
+        plugin::{
+            monitoring::{ChartyType, KeyValue, SeriesType},
+            notification::Level,
+        },
+    };
+
+    use super::*;
+
+    fn make_key_value(name: &str) -> KeyValue {
+        KeyValue {
+            name: name.to_owned(),
+            value_type: "real".to_owned(),
+            value: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_create_alert_query_and_parse() {
+        let rule = AlertRule {
+            id: "disk_free".to_owned(),
+            name: "Disk free".to_owned(),
+            enabled: true,
+            series_id: "disk_usage".to_owned(),
+            ipaddress: Some("192.168.178.2".to_owned()),
+            sub_identifier: None,
+            value_name: Some("free".to_owned()),
+            comparison: Comparison::Less,
+            threshold: 10.0,
+            clear_threshold: Some(15.0),
+            duration_seconds: 300,
+            notification_level: Level::Error,
+            message: String::new(),
+        };
+        let monitoring = MonitioringDef {
+            pre_process: None,
+            id: "disk_usage".to_owned(),
+            name: "Disk usage".to_owned(),
+            series_type: SeriesType::Datetime,
+            chart_type: ChartyType::Line,
+            identifier: make_key_value("IP"),
+            sub_identifier: Some(make_key_value("mount")),
+            values: vec![make_key_value("free"), make_key_value("used")],
+        };
+
+        let now = Utc::now();
+        let query = create_alert_query(&rule, &monitoring, now);
+
+        assert_eq!(
+            query.group_by,
+            vec![
+                common::IDENTIFIER.to_owned(),
+                common::SUB_IDENTIFIER.to_owned(),
+                common::SUB_IDENTIFIER_2.to_owned()
+            ]
+        );
+        assert_eq!(
+            query.filters,
+            vec![
+                (common::IDENTIFIER.to_owned(), "192.168.178.2".to_owned()),
+                (common::SUB_IDENTIFIER_2.to_owned(), "free".to_owned())
+            ]
+        );
+        assert_eq!(query.from, now - Duration::seconds(300));
+        assert_eq!(query.bucket_seconds, None);
+
+        let coverage_query = create_coverage_query(&query);
+        assert_eq!(coverage_query.from, now - Duration::seconds(420));
+        assert_eq!(coverage_query.to, Some(now - Duration::seconds(300)));
+        assert_eq!(coverage_query.filters, query.filters);
+
+        let data = TimeSeriesResponseData {
+            query: String::new(),
+            columns: Vec::new(),
+            dataset: vec![
+                vec![
+                    json!("192.168.178.2"),
+                    json!("/mnt/data"),
+                    json!("free"),
+                    json!(5),
+                    json!(8.5),
+                    json!(7),
+                ],
+                vec![
+                    json!("192.168.178.2"),
+                    json!("/"),
+                    json!("free"),
+                    json!(null),
+                    json!(null),
+                    json!(null),
+                ],
+            ],
+            count: 2,
+        };
+
+        let series = parse_series_values(&data);
+        assert_eq!(
+            series,
+            vec![SeriesValues {
+                ipaddress: "192.168.178.2".to_owned(),
+                sub_identifiers: vec!["/mnt/data".to_owned(), "free".to_owned()],
+                values: WindowValues {
+                    min: 5.0,
+                    max: 8.5,
+                    last: 7.0,
+                    covers_duration: false
+                },
+            }]
+        );
+        assert_eq!(
+            get_notification_id(&rule, &series[0].sub_identifiers),
+            "alert.disk_free._mnt_data.free"
+        );
+    }
+}
@@ -6,8 +6,10 @@ use regex::Regex;
 
 use crate::{
     commands::{
-        self, http::HttpCommandResult, kafka::KafkaCommandResult, replace,
-        socket::SocketCommandResult, CommandInput,
+        self, dns::DnsCommandResult, http::HttpCommandResult, kafka::KafkaCommandResult,
+        modbus::ModbusCommandResult, mqtt::MqttCommandResult, replace, script::ScriptCommandResult,
+        snmp::SnmpCommandResult, socket::SocketCommandResult, ssh::SshCommandResult,
+        tcp::TcpCommandResult, CommandInput,
     },
     datastore::{self},
     models::{
@@ -187,6 +189,34 @@ async fn execute_command(input: CommandInput, silent: &bool) -> Result<String, A
             let result: SocketCommandResult = commands::execute(input, silent).await?;
             result.get_response()
         }
+        commands::ssh::SSH => {
+            let result: SshCommandResult = commands::execute(input, silent).await?;
+            result.get_response()
+        }
+        commands::snmp::SNMP => {
+            let result: SnmpCommandResult = commands::execute(input, silent).await?;
+            result.get_response()
+        }
+        commands::mqtt::MQTT => {
+            let result: MqttCommandResult = commands::execute(input, silent).await?;
+            result.get_response()
+        }
+        commands::tcp::TCP => {
+            let result: TcpCommandResult = commands::execute(input, silent).await?;
+            result.get_response()
+        }
+        commands::dns::DNS => {
+            let result: DnsCommandResult = commands::execute(input, silent).await?;
+            result.get_response()
+        }
+        commands::modbus::MODBUS => {
+            let result: ModbusCommandResult = commands::execute(input, silent).await?;
+            result.get_response()
+        }
+        commands::script::SCRIPT => {
+            let result: ScriptCommandResult = commands::execute(input, silent).await?;
+            result.get_response()
+        }
         _ => {
             let result: HttpCommandResult = commands::execute(input, silent).await?;
             result.get_response()
@@ -230,6 +260,90 @@ async fn get_command_inputs(
             )
             .await?
         }
+        commands::ssh::SSH => {
+            commands::ssh::make_command_input_from_data(
+                server,
+                crypto_key,
+                data,
+                action_params,
+                feature,
+                plugin,
+                silent,
+            )
+            .await?
+        }
+        commands::snmp::SNMP => {
+            commands::snmp::make_command_input_from_data(
+                server,
+                crypto_key,
+                data,
+                action_params,
+                feature,
+                plugin,
+                silent,
+            )
+            .await?
+        }
+        commands::mqtt::MQTT => {
+            commands::mqtt::make_command_input_from_data(
+                server,
+                crypto_key,
+                data,
+                action_params,
+                feature,
+                plugin,
+                silent,
+            )
+            .await?
+        }
+        commands::tcp::TCP => {
+            commands::tcp::make_command_input_from_data(
+                server,
+                crypto_key,
+                data,
+                action_params,
+                feature,
+                plugin,
+                silent,
+            )
+            .await?
+        }
+        commands::dns::DNS => {
+            commands::dns::make_command_input_from_data(
+                server,
+                crypto_key,
+                data,
+                action_params,
+                feature,
+                plugin,
+                silent,
+            )
+            .await?
+        }
+        commands::modbus::MODBUS => {
+            commands::modbus::make_command_input_from_data(
+                server,
+                crypto_key,
+                data,
+                action_params,
+                feature,
+                plugin,
+                silent,
+            )
+            .await?
+        }
+        commands::script::SCRIPT => {
+            commands::script::make_command_input_from_data(
+                server,
+                crypto_key,
+                data,
+                action_params,
+                feature,
+                plugin,
+                silent,
+            )
+            .await?
+        }
         _ => {
             commands::http::make_command_input_from_data(
                 server,
@@ -1,13 +1,18 @@
 mod actions;
+mod alerts;
 mod data;
 mod discovery;
 mod monitoring;
 mod notifications;
+mod reports;
+mod subscriptions;
 
 pub use discovery::auto_discover_servers_in_network;
 pub use discovery::discover_features;
 pub use discovery::discover_features_of_all_servers;
 
+pub use alerts::delete_alert_rule;
+
 pub use actions::check_main_action_conditions;
 pub use actions::execute_action;
 
@@ -16,6 +21,20 @@ pub use data::execute_specific_data_query;
 
 pub use monitoring::get_monitoring_data;
 
+pub use notifications::acknowledge_notification;
+pub use notifications::raise_notification;
+pub use notifications::is_notification_open;
+pub use notifications::resolve_notification;
+pub use notifications::resolve_notification_with_message;
+pub use notifications::send_test_notification;
+pub use notifications::silence_notification;
+
+pub use reports::get_availability_reports;
+pub use reports::send_availability_digest;
+pub use reports::SERVER_STATUS_SERIES;
+
+pub use subscriptions::process_mqtt_subscriptions;
+
 use actions::merge_condition_check_results;
 
 use crate::common;
@@ -113,5 +132,8 @@ pub async fn execute_all_data_dependent(silent: &bool) -> Result<(), AppError> {
     not_finish.await?;
     mon_finish.await?;
 
+    // the rules are checked against the data that was just saved
+    alerts::evaluate_alert_rules().await?;
+
     Ok(())
 }
@@ -1,16 +1,16 @@
 use std::{collections::HashMap, net::IpAddr};
 
 use crate::{
-    commands::CommandInput,
+    commands::{self, CommandInput},
     common,
-    datastore::{self, TimeSeriesData, TimeSeriesPersistence},
+    datastore::{self, TimeSeriesData, TimeSeriesPersistence, TimeSeriesQuery},
     models::{
         error::AppError,
         plugin::{
             data::DataDef,
             monitoring::{
-                ChartyType, KeyValue, MonitioringDef, SeriesType, TimeSeriesResponse,
-                TimeSeriesResponseData, TimeSeriesResponseMetaData,
+                ChartyType, KeyValue, MonitioringDef, MonitoringRange, SeriesType,
+                TimeSeriesResponse, TimeSeriesResponseData, TimeSeriesResponseMetaData,
             },
         },
     },
@@ -21,6 +21,7 @@ mod response_parser;
 pub async fn get_monitoring_data(
     series_id: &str,
     ipaddress: IpAddr,
+    range: MonitoringRange,
 ) -> Result<TimeSeriesResponse, AppError> {
     let monitoring = get_monitoring_config_for_series(series_id)?.ok_or(AppError::Unknown(
         format!("Could not find monitoring config for series {}", series_id),
@@ -28,51 +29,49 @@ pub async fn get_monitoring_data(
 
     log::trace!("querying monitoring data for {}", series_id);
 
-    let select = create_data_select(&monitoring, series_id, format!("{}", ipaddress).as_str());
-
-    let query = vec![("query", select.as_str())];
-
-    let result = common::execute_timeseries_db_query(&query).await?;
-
-    log::trace!("response from db: {}", result);
+    let query = create_data_query(
+        &monitoring,
+        series_id,
+        format!("{}", ipaddress).as_str(),
+        &range,
+    );
 
-    let value = serde_json::from_str::<TimeSeriesResponseData>(&result)?;
+    let value = datastore::query_timeseries_data(&query).await?;
 
-    enrich_response(value, ipaddress, series_id, &monitoring)
+    enrich_response(value, ipaddress, series_id, &monitoring, range)
 }
 
-fn create_data_select(monitoring: &MonitioringDef, series_id: &str, identifier: &str) -> String {
-    let mut cols: Vec<String> = Vec::new();
-    cols.push(common::IDENTIFIER.to_owned());
+fn create_data_query(
+    monitoring: &MonitioringDef,
+    series_id: &str,
+    identifier: &str,
+    range: &MonitoringRange,
+) -> TimeSeriesQuery {
+    let mut group_by: Vec<String> = Vec::new();
+    group_by.push(common::IDENTIFIER.to_owned());
 
     if monitoring.sub_identifier.is_some() {
-        cols.push(common::SUB_IDENTIFIER.to_owned());
+        group_by.push(common::SUB_IDENTIFIER.to_owned());
     }
 
     if monitoring.values.len() > 1 {
-        cols.push(common::SUB_IDENTIFIER_2.to_owned());
+        group_by.push(common::SUB_IDENTIFIER_2.to_owned());
     }
 
-    cols.push(format!("last({})", common::VALUE.to_owned()));
-
-    cols.push(common::TIMESTAMP.to_owned()); // always the last column
-
-    let where_stmnt = format!("{} > sysdate() - 36000000000L", common::TIMESTAMP);
-    let sample_by = "SAMPLE BY 1m FILL(NONE)";
-
-    format!(
-        "select {} from {} where {} AND {} = '{}' {} ORDER BY {} asc",
-        cols.join(","),
-        series_id,
-        where_stmnt,
-        common::IDENTIFIER,
-        identifier,
-        sample_by,
-        common::TIMESTAMP
-    )
+    TimeSeriesQuery {
+        series_id: series_id.to_owned(),
+        group_by,
+        filters: vec![(common::IDENTIFIER.to_owned(), identifier.to_owned())],
+        from: range.from,
+        to: Some(range.to),
+        aggregations: vec![range.aggregation],
+        bucket_seconds: Some(range.bucket_seconds),
+    }
 }
 
-fn get_monitoring_config_for_series(series_id: &str) -> Result<Option<MonitioringDef>, AppError> {
+pub fn get_monitoring_config_for_series(
+    series_id: &str,
+) -> Result<Option<MonitioringDef>, AppError> {
     if series_id == "server_status" {
         Ok(Some(MonitioringDef {
             pre_process: None,
@@ -102,6 +101,7 @@ fn enrich_response(
     ipaddress: IpAddr,
     series: &str,
     monitoring: &MonitioringDef,
+    range: MonitoringRange,
 ) -> Result<TimeSeriesResponse, AppError> {
     let meta_data = TimeSeriesResponseMetaData {
         ipaddress: format!("{}", ipaddress),
@@ -110,6 +110,7 @@ fn enrich_response(
         name: monitoring.name.to_owned(),
         series_type: monitoring.series_type.for_json(),
         chart_type: monitoring.chart_type.for_json(),
+        range,
     };
 
     Ok(data.to_response(meta_data))
@@ -126,8 +127,9 @@ impl MonitoringProcessor {
         }
     }
 
+    /// data of MQTT subscriptions is not polled - it is processed when a message arrives
     pub fn is_relevant_data_for_processing(&self, data: &DataDef) -> bool {
-        !data.monitoring.is_empty()
+        !data.monitoring.is_empty() && !commands::mqtt::is_subscription(data)
     }
 
     pub async fn process(
@@ -139,6 +141,27 @@ impl MonitoringProcessor {
             return Ok(());
         }
 
+        self.extract(data, input_response_tuples)
+    }
+
+    /// processes data that was pushed (ie. received by a MQTT subscription) instead of polled
+    pub async fn process_pushed(
+        &mut self,
+        data: &DataDef,
+        input_response_tuples: &[(CommandInput, String)],
+    ) -> Result<(), AppError> {
+        if data.monitoring.is_empty() {
+            return Ok(());
+        }
+
+        self.extract(data, input_response_tuples)
+    }
+
+    fn extract(
+        &mut self,
+        data: &DataDef,
+        input_response_tuples: &[(CommandInput, String)],
+    ) -> Result<(), AppError> {
         let parser = response_parser::MonitoringDataExtractor::new(input_response_tuples, data);
 
         let parsed_data = parser.parse()?;
@@ -153,6 +176,7 @@ impl MonitoringProcessor {
 
         for (series_id, data_vec) in self.map {
             log::trace!("Saving time series data for {}: {:?}", series_id, data_vec);
+            datastore::cache_latest_timeseries_data(series_id.as_str(), &data_vec)?;
             datastore::save_timeseries_data(&mut persistence, series_id.as_str(), data_vec.clone())
                 .await?;
         }
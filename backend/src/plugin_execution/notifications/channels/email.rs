@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+
+use crate::{common, datastore, models::error::AppError};
+
+use super::{Channel, NotificationMessage};
+
+pub struct EmailChannel {
+    address: String,
+}
+
+impl EmailChannel {
+    pub fn new(address: String) -> Self {
+        EmailChannel { address }
+    }
+}
+
+#[async_trait]
+impl Channel for EmailChannel {
+    async fn send(&self, message: &NotificationMessage) -> Result<(), AppError> {
+        if !common::is_smtp_config_valid()? {
+            return Err(AppError::EmailConfigError("smtp_host".to_owned()));
+        }
+        let from_address = datastore::get_config()?.get_string("email_from")?;
+
+        common::send_email(
+            from_address.as_str(),
+            self.address.as_str(),
+            message.get_title().as_str(),
+            format!("{}\n\nRegards,\nyour Server-Manager", message.get_text()).as_str(),
+        )
+        .await?;
+        Ok(())
+    }
+}
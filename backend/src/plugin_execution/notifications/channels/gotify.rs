@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::models::{error::AppError, plugin::notification::Level};
+
+use super::{Channel, NotificationMessage};
+
+pub struct GotifyChannel {
+    url: String,
+    token: String,
+}
+
+impl GotifyChannel {
+    pub fn new(url: &str, token: &str) -> Self {
+        GotifyChannel {
+            url: url.to_owned(),
+            token: token.to_owned(),
+        }
+    }
+}
+
+#[async_trait]
+impl Channel for GotifyChannel {
+    async fn send(&self, message: &NotificationMessage) -> Result<(), AppError> {
+        let body = json!({
+            "title": message.get_title(),
+            "message": message.get_text(),
+            "priority": priority(&message.notification.notification_level),
+        });
+
+        super::post(
+            format!("{}/message", self.url.trim_end_matches('/')).as_str(),
+            vec![
+                ("Content-Type".to_owned(), "application/json".to_owned()),
+                ("X-Gotify-Key".to_owned(), self.token.clone()),
+            ],
+            body.to_string(),
+        )
+        .await
+    }
+}
+
+/// Gotify priorities go from 0 to 10 - the clients show a notification from 4 on
+fn priority(level: &Level) -> u8 {
+    match level {
+        Level::Info => 2,
+        Level::Warn => 5,
+        Level::Error => 7,
+        Level::Critical => 9,
+    }
+}
@@ -0,0 +1,381 @@
+mod email;
+mod gotify;
+mod ntfy;
+mod telegram;
+mod webhook;
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::{
+    datastore,
+    models::{
+        error::AppError,
+        plugin::notification::{Notification, NotificationState},
+        users::{
+            notification_channel::{NotificationChannel, NotificationChannelType},
+            User,
+        },
+    },
+};
+
+const TIMEOUT_SECONDS: u64 = 5;
+
+/// A notification together with the server it was raised for - the form in which it is sent to the channels
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationMessage {
+    pub ipaddress: String,
+    pub server_name: Option<String>,
+    #[serde(flatten)]
+    pub notification: Notification,
+}
+
+impl NotificationMessage {
+    fn new(ipaddress: &str, notification: &Notification) -> Self {
+        let server_name = datastore::get_all_servers_from_cache()
+            .unwrap_or_default()
+            .iter()
+            .find(|server| format!("{}", server.get_ipaddress()) == ipaddress)
+            .map(|server| server.get_name())
+            .filter(|name| !name.is_empty());
+
+        NotificationMessage {
+            ipaddress: ipaddress.to_owned(),
+            server_name,
+            notification: notification.clone(),
+        }
+    }
+
+    pub fn get_title(&self) -> String {
+        let level = match self.notification.state {
+            NotificationState::Resolved => "Resolved".to_owned(),
+            _ => format!("{:?}", self.notification.notification_level),
+        };
+
+        let source = self
+            .server_name
+            .clone()
+            .or(Some(self.ipaddress.clone()).filter(|ip| !ip.is_empty()));
+
+        match source {
+            Some(source) => format!("{}: {} on {}", level, self.notification.name, source),
+            None => format!("{}: {}", level, self.notification.name),
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        if self.notification.message.is_empty() {
+            self.notification.name.clone()
+        } else {
+            self.notification.message.clone()
+        }
+    }
+}
+
+#[async_trait]
+trait Channel {
+    async fn send(&self, message: &NotificationMessage) -> Result<(), AppError>;
+}
+
+fn make_channel(channel: &NotificationChannel, user: &User) -> Box<dyn Channel + Send + Sync> {
+    match &channel.channel_type {
+        NotificationChannelType::Email { address } => Box::new(email::EmailChannel::new(
+            address.clone().unwrap_or(user.get_email()),
+        )),
+        NotificationChannelType::Webhook { url, headers } => {
+            Box::new(webhook::WebhookChannel::new(url, headers))
+        }
+        NotificationChannelType::Ntfy { url, token } => {
+            Box::new(ntfy::NtfyChannel::new(url, token.as_deref()))
+        }
+        NotificationChannelType::Gotify { url, token } => {
+            Box::new(gotify::GotifyChannel::new(url, token))
+        }
+        NotificationChannelType::Telegram {
+            bot_token,
+            chat_id,
+            api_url,
+        } => Box::new(telegram::TelegramChannel::new(
+            bot_token,
+            chat_id,
+            api_url.as_deref(),
+        )),
+    }
+}
+
+/// a user only receives the notifications of the servers and plugins the user has access to
+fn is_recipient(user: &User, ipaddress: &str, notification: &Notification) -> bool {
+    user.can_access_server_str(ipaddress)
+        && notification
+            .plugin_id
+            .as_deref()
+            .is_none_or(|plugin_id| user.can_access_plugin(plugin_id))
+}
+
+/// Sends the notifications raised for a server to the channels of all users that have access to the server and
+/// accept the level of the notification.
+/// Errors are only logged, since a channel that cannot be reached should not stop the processing of the notifications
+pub async fn send_notifications(ipaddress: &str, notifications: &[Notification]) {
+    if notifications.is_empty() {
+        return;
+    }
+
+    // the users without the password hashes also don't contain the notification channels
+    let users = match datastore::get_all_users(true).await {
+        Ok(users) => users,
+        Err(err) => {
+            log::error!("Could not get users to send notifications: {}", err);
+            return;
+        }
+    };
+    let crypto_key = match datastore::get_crypto_key() {
+        Ok(crypto_key) => crypto_key,
+        Err(err) => {
+            log::error!("Could not get crypto key to send notifications: {}", err);
+            return;
+        }
+    };
+
+    for notification in notifications {
+        let message = NotificationMessage::new(ipaddress, notification);
+
+        for user in users
+            .iter()
+            .filter(|user| is_recipient(user, ipaddress, notification))
+        {
+            for channel in user
+                .get_notification_channels()
+                .iter()
+                .filter(|channel| channel.accepts(&notification.notification_level))
+            {
+                let result = match channel.decrypt_secrets(&crypto_key) {
+                    Ok(channel) => make_channel(&channel, user).send(&message).await,
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = result {
+                    log::error!(
+                        "Could not send notification {} to channel {} of user {}: {}",
+                        notification.id,
+                        channel.id,
+                        user.get_user_id(),
+                        err
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Sends a test message to the given channel of the user, so that the configuration of the channel can be checked
+pub async fn send_test_notification(user: &User, channel_id: &str) -> Result<(), AppError> {
+    let channel = user
+        .get_notification_channels()
+        .into_iter()
+        .find(|channel| channel.id == channel_id)
+        .ok_or(AppError::DataNotFound(channel_id.to_owned()))?
+        .decrypt_secrets(&datastore::get_crypto_key()?)?;
+
+    let message = NotificationMessage {
+        ipaddress: String::new(),
+        server_name: None,
+        notification: Notification::new(
+            "test",
+            "Test notification",
+            "This is a test notification of the Server-Manager",
+            channel.min_level.clone(),
+        ),
+    };
+
+    make_channel(&channel, user).send(&message).await
+}
+
+async fn post(url: &str, headers: Vec<(String, String)>, body: String) -> Result<(), AppError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(TIMEOUT_SECONDS))
+        .build()?;
+
+    let mut request = client.post(url).body(body);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    log::debug!("sending notification to {}", url);
+
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        sync::oneshot,
+    };
+
+    use crate::models::{
+        plugin::notification::Level,
+        users::role::{Permissions, Role},
+    };
+
+    use super::*;
+
+    /// accepts a single request, answers it with 200 and returns the raw request
+    async fn start_http_stand_in() -> (String, oneshot::Receiver<String>) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = oneshot::channel();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+
+            let mut request = String::new();
+            let mut buf = [0u8; 4096];
+            while !is_complete_request(&request) {
+                let len = socket.read(&mut buf).await.unwrap();
+                if len == 0 {
+                    break;
+                }
+                request.push_str(&String::from_utf8_lossy(&buf[..len]));
+            }
+
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            sender.send(request).ok();
+        });
+
+        (url, receiver)
+    }
+
+    fn is_complete_request(request: &str) -> bool {
+        match request.split_once("\r\n\r\n") {
+            Some((head, body)) => {
+                let content_length = head
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length:")
+                            .map(|value| value.trim().parse::<usize>().unwrap_or_default())
+                    })
+                    .unwrap_or_default();
+                body.len() >= content_length
+            }
+            None => false,
+        }
+    }
+
+    fn make_message() -> NotificationMessage {
+        NotificationMessage {
+            ipaddress: "192.168.178.2".to_owned(),
+            server_name: Some("nas".to_owned()),
+            notification: Notification::new(
+                "disk_full",
+                "Disk full",
+                "Less than 10% free",
+                Level::Error,
+            ),
+        }
+    }
+
+    fn make_user(channel_type: NotificationChannelType) -> (User, NotificationChannel) {
+        let channel = NotificationChannel {
+            id: "channel".to_owned(),
+            enabled: true,
+            min_level: Level::Warn,
+            channel_type,
+        };
+
+        let mut user = User::new(
+            "admin".to_owned(),
+            "Admin".to_owned(),
+            "admin@localhost".to_owned(),
+            String::new(),
+        );
+        user.set_notification_channels(vec![channel.clone()]);
+
+        (user, channel)
+    }
+
+    #[tokio::test]
+    async fn test_webhook_channel() {
+        let (url, receiver) = start_http_stand_in().await;
+        let (user, channel) = make_user(NotificationChannelType::Webhook {
+            url: format!("{}/hook", url),
+            headers: vec!["X-Token=secret".to_owned()],
+        });
+
+        make_channel(&channel, &user)
+            .send(&make_message())
+            .await
+            .unwrap();
+
+        let request = receiver.await.unwrap();
+        assert!(request.starts_with("POST /hook "));
+        assert!(request.contains("x-token: secret"));
+        assert!(request.contains("\"server_name\":\"nas\""));
+        assert!(request.contains("\"notification_level\":\"Error\""));
+    }
+
+    #[tokio::test]
+    async fn test_telegram_channel() {
+        let (url, receiver) = start_http_stand_in().await;
+        let (user, channel) = make_user(NotificationChannelType::Telegram {
+            bot_token: "123:abc".to_owned(),
+            chat_id: "42".to_owned(),
+            api_url: Some(url),
+        });
+
+        make_channel(&channel, &user)
+            .send(&make_message())
+            .await
+            .unwrap();
+
+        let request = receiver.await.unwrap();
+        assert!(request.starts_with("POST /bot123:abc/sendMessage "));
+        assert!(request.contains("\"chat_id\":\"42\""));
+        assert!(request.contains("Error: Disk full on nas"));
+    }
+
+    #[test]
+    fn test_is_recipient() {
+        let (mut user, _) = make_user(NotificationChannelType::Email { address: None });
+        let mut notification = make_message().notification;
+        notification.plugin_id = Some("docker".to_owned());
+
+        assert!(is_recipient(&user, "192.168.178.2", &notification));
+
+        user.update_role_and_permissions(
+            Role::Operator,
+            Permissions {
+                servers: vec!["192.168.178.3".parse().unwrap()],
+                plugins: vec!["docker".to_owned()],
+            },
+        );
+        assert!(!is_recipient(&user, "192.168.178.2", &notification));
+        assert!(is_recipient(&user, "192.168.178.3", &notification));
+
+        notification.plugin_id = Some("proxmox".to_owned());
+        assert!(!is_recipient(&user, "192.168.178.3", &notification));
+
+        notification.plugin_id = None;
+        assert!(is_recipient(&user, "192.168.178.3", &notification));
+    }
+
+    #[test]
+    fn test_channel_accepts_level() {
+        let (_, mut channel) = make_user(NotificationChannelType::Email { address: None });
+
+        assert!(!channel.accepts(&Level::Info));
+        assert!(channel.accepts(&Level::Warn));
+        assert!(channel.accepts(&Level::Critical));
+
+        channel.enabled = false;
+        assert!(!channel.accepts(&Level::Critical));
+    }
+}
@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+
+use crate::models::{error::AppError, plugin::notification::Level};
+
+use super::{Channel, NotificationMessage};
+
+pub struct NtfyChannel {
+    url: String,
+    token: Option<String>,
+}
+
+impl NtfyChannel {
+    pub fn new(url: &str, token: Option<&str>) -> Self {
+        NtfyChannel {
+            url: url.to_owned(),
+            token: token.map(|t| t.to_owned()),
+        }
+    }
+}
+
+#[async_trait]
+impl Channel for NtfyChannel {
+    async fn send(&self, message: &NotificationMessage) -> Result<(), AppError> {
+        let level = &message.notification.notification_level;
+
+        let mut headers = vec![
+            ("Title".to_owned(), message.get_title()),
+            ("Priority".to_owned(), priority(level).to_owned()),
+            ("Tags".to_owned(), format!("{:?}", level).to_lowercase()),
+        ];
+        if let Some(token) = &self.token {
+            headers.push(("Authorization".to_owned(), format!("Bearer {}", token)));
+        }
+
+        super::post(&self.url, headers, message.get_text()).await
+    }
+}
+
+/// ntfy priorities go from 1 (min) to 5 (max)
+fn priority(level: &Level) -> &str {
+    match level {
+        Level::Info => "3",
+        Level::Warn | Level::Error => "4",
+        Level::Critical => "5",
+    }
+}
@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::models::error::AppError;
+
+use super::{Channel, NotificationMessage};
+
+const DEFAULT_API_URL: &str = "https://api.telegram.org";
+
+pub struct TelegramChannel {
+    bot_token: String,
+    chat_id: String,
+    api_url: String,
+}
+
+impl TelegramChannel {
+    pub fn new(bot_token: &str, chat_id: &str, api_url: Option<&str>) -> Self {
+        TelegramChannel {
+            bot_token: bot_token.to_owned(),
+            chat_id: chat_id.to_owned(),
+            api_url: api_url.unwrap_or(DEFAULT_API_URL).to_owned(),
+        }
+    }
+}
+
+#[async_trait]
+impl Channel for TelegramChannel {
+    async fn send(&self, message: &NotificationMessage) -> Result<(), AppError> {
+        let body = json!({
+            "chat_id": self.chat_id,
+            "text": format!("{}\n{}", message.get_title(), message.get_text()),
+        });
+
+        super::post(
+            format!(
+                "{}/bot{}/sendMessage",
+                self.api_url.trim_end_matches('/'),
+                self.bot_token
+            )
+            .as_str(),
+            vec![("Content-Type".to_owned(), "application/json".to_owned())],
+            body.to_string(),
+        )
+        .await
+    }
+}
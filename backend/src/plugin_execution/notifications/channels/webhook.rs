@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+
+use crate::models::error::AppError;
+
+use super::{Channel, NotificationMessage};
+
+pub struct WebhookChannel {
+    url: String,
+    headers: Vec<String>,
+}
+
+impl WebhookChannel {
+    pub fn new(url: &str, headers: &[String]) -> Self {
+        WebhookChannel {
+            url: url.to_owned(),
+            headers: headers.to_vec(),
+        }
+    }
+}
+
+#[async_trait]
+impl Channel for WebhookChannel {
+    async fn send(&self, message: &NotificationMessage) -> Result<(), AppError> {
+        let mut headers = vec![("Content-Type".to_owned(), "application/json".to_owned())];
+
+        for header in &self.headers {
+            let (name, value) = header.split_once('=').ok_or(AppError::InvalidArgument(
+                "header".to_owned(),
+                Some(header.clone()),
+            ))?;
+            headers.push((name.trim().to_owned(), value.trim().to_owned()));
+        }
+
+        super::post(&self.url, headers, serde_json::to_string(message)?).await
+    }
+}
@@ -1,20 +1,41 @@
+mod channels;
+
 use std::collections::HashMap;
 
+use chrono::{DateTime, Duration, Utc};
+
 use crate::{
     commands::CommandInput,
     common, datastore,
     models::{
         error::AppError,
+        maintenance::MaintenanceWindow,
         plugin::{
             data::DataDef,
-            notification::{Notification, NotificationDef, Notifications},
+            notification::{
+                Notification, NotificationDef, NotificationState, Notifications,
+                ResolvedNotification,
+            },
             Plugin,
         },
     },
 };
 
+pub use channels::send_test_notification;
+
+/// result of the notification script of one definition for a server
+#[derive(Debug, Clone)]
+struct ScriptResult {
+    plugin_id: String,
+    notification_def: NotificationDef,
+    matched: bool,
+}
+
+/// results of the notification scripts of one server: notification id -> result
+type ScriptResults = HashMap<String, ScriptResult>;
+
 pub struct NotificationProcessor {
-    map: HashMap<String, Notifications>,
+    map: HashMap<String, ScriptResults>,
 }
 
 impl NotificationProcessor {
@@ -25,10 +46,19 @@ impl NotificationProcessor {
     }
 
     pub async fn finish(&self) -> Result<(), AppError> {
-        let values: Vec<Notifications> = self.map.values().cloned().collect();
-        log::debug!("notifications to persist: {:?}", values);
-        for notifications in values {
+        log::debug!("notification results to persist: {:?}", self.map);
+
+        for (ipaddress, results) in &self.map {
+            let existing = get_notifications(ipaddress).await?;
+            let now = Utc::now();
+
+            let (mut notifications, resolved) = apply_results(existing, results, now);
+            let to_deliver = take_undelivered(&mut notifications, now).await?;
+
             datastore::insert_or_update_notifications(notifications).await?;
+            save_history(ipaddress, resolved).await?;
+
+            channels::send_notifications(ipaddress, &to_deliver).await;
         }
         Ok(())
     }
@@ -61,9 +91,15 @@ impl NotificationProcessor {
         for input_response_tuple in input_response_tuples {
             let input = &input_response_tuple.0;
 
+            let Some(ipaddress) = input.get_ipaddress() else {
+                continue;
+            };
+            let ipaddress = format!("{}", ipaddress);
+
             for notification_def in &notification_defs_referencing_data {
                 log::trace!("notification_def {:?}", notification_def);
 
+                // a script that cannot be evaluated neither raises nor resolves the notification
                 if let Ok(result) =
                     common::script_match(&notification_def.script, &input_response_tuple.1)
                 {
@@ -74,29 +110,20 @@ impl NotificationProcessor {
                             notification_def.id,
                         );
                         log::trace!("input {:?}", input);
-
-                        if let Some(ipaddress) = input.get_ipaddress() {
-                            let ipaddress = format!("{}", ipaddress);
-
-                            let notification = Notification {
-                                id: notification_def.id.clone(),
-                                name: notification_def.name.clone(),
-                                message: notification_def.message.clone(),
-                                notification_level: notification_def.notification_level.clone(),
-                            };
-
-                            log::trace!("Created notification {:?}", notification);
-
-                            self.map
-                                .entry(ipaddress.clone())
-                                .or_insert_with(|| Notifications {
-                                    ipaddress: ipaddress.clone(),
-                                    list: Vec::new(),
-                                })
-                                .list
-                                .push(notification);
-                        }
                     }
+
+                    // with a list of inputs per server, the notification is raised if the script matches for one of them
+                    let script_result = self
+                        .map
+                        .entry(ipaddress.clone())
+                        .or_default()
+                        .entry(notification_def.id.clone())
+                        .or_insert_with(|| ScriptResult {
+                            plugin_id: plugin.id.clone(),
+                            notification_def: notification_def.clone(),
+                            matched: false,
+                        });
+                    script_result.matched |= result;
                 }
             }
         }
@@ -104,3 +131,371 @@ impl NotificationProcessor {
         Ok(())
     }
 }
+
+/// Raises a notification for the server that is not based on a notification definition of a plugin.
+/// If the notification is already open, only the message and the last seen timestamp are updated
+pub async fn raise_notification(
+    ipaddress: &str,
+    notification: Notification,
+) -> Result<(), AppError> {
+    let mut notifications = get_notifications(ipaddress).await?;
+
+    match notifications
+        .list
+        .iter_mut()
+        .find(|n| n.id == notification.id)
+    {
+        Some(existing) => {
+            existing.message = notification.message.clone();
+            existing.notification_level = notification.notification_level.clone();
+            existing.last_seen = notification.last_seen;
+        }
+        None => notifications.list.push(notification),
+    };
+
+    let to_deliver = take_undelivered(&mut notifications, Utc::now()).await?;
+
+    datastore::insert_or_update_notifications(notifications).await?;
+
+    channels::send_notifications(ipaddress, &to_deliver).await;
+    Ok(())
+}
+
+/// Marks the open notification as acknowledged - it stays visible until it is resolved
+pub async fn acknowledge_notification(ipaddress: &str, id: &str) -> Result<(), AppError> {
+    let mut notifications = get_notifications(ipaddress).await?;
+
+    notifications
+        .list
+        .iter_mut()
+        .find(|n| n.id == id)
+        .ok_or(AppError::DataNotFound(id.to_owned()))?
+        .acknowledge(Utc::now());
+
+    datastore::insert_or_update_notifications(notifications).await?;
+    Ok(())
+}
+
+/// Suppresses the delivery of the notification to the notification channels for the given number of hours.
+/// If the notification is still open afterwards, it is delivered with the next check
+pub async fn silence_notification(ipaddress: &str, id: &str, hours: u32) -> Result<(), AppError> {
+    let mut notifications = get_notifications(ipaddress).await?;
+
+    notifications
+        .list
+        .iter_mut()
+        .find(|n| n.id == id)
+        .ok_or(AppError::DataNotFound(id.to_owned()))?
+        .silence(Utc::now() + Duration::hours(hours as i64));
+
+    datastore::insert_or_update_notifications(notifications).await?;
+    Ok(())
+}
+
+/// Resolves the notification manually and moves it to the history.
+/// If the condition of the notification still matches, it is raised again with the next check
+pub async fn resolve_notification(ipaddress: &str, id: &str) -> Result<(), AppError> {
+    let mut notifications = get_notifications(ipaddress).await?;
+
+    let index = notifications
+        .list
+        .iter()
+        .position(|n| n.id == id)
+        .ok_or(AppError::DataNotFound(id.to_owned()))?;
+
+    let mut notification = notifications.list.remove(index);
+    notification.resolve(Utc::now());
+
+    datastore::insert_or_update_notifications(notifications).await?;
+    save_history(ipaddress, vec![notification]).await
+}
+
+/// true if the notification is open or acknowledged for the server
+pub async fn is_notification_open(ipaddress: &str, id: &str) -> Result<bool, AppError> {
+    Ok(get_notifications(ipaddress)
+        .await?
+        .list
+        .iter()
+        .any(|n| n.id == id))
+}
+
+/// Resolves the notification with a final message, ie. the downtime of a server.
+/// If the notification was sent to the notification channels, the resolution is sent as well
+pub async fn resolve_notification_with_message(
+    ipaddress: &str,
+    id: &str,
+    message: &str,
+) -> Result<(), AppError> {
+    let mut notifications = get_notifications(ipaddress).await?;
+
+    let Some(index) = notifications.list.iter().position(|n| n.id == id) else {
+        return Ok(());
+    };
+
+    let mut notification = notifications.list.remove(index);
+    notification.message = message.to_owned();
+    notification.resolve(Utc::now());
+
+    datastore::insert_or_update_notifications(notifications).await?;
+    save_history(ipaddress, vec![notification.clone()]).await?;
+
+    if notification.delivered && !notification.is_silenced(Utc::now()) {
+        channels::send_notifications(ipaddress, &[notification]).await;
+    }
+    Ok(())
+}
+
+async fn get_notifications(ipaddress: &str) -> Result<Notifications, AppError> {
+    match datastore::get_notification(ipaddress).await {
+        Ok(notifications) => Ok(notifications),
+        Err(AppError::DataNotFound(_)) => Ok(Notifications::new(ipaddress)),
+        Err(err) => Err(err),
+    }
+}
+
+/// Marks the open notifications that were not sent yet as delivered and returns them, unless they are silenced.
+/// Notifications raised during a maintenance window of the server or feature are silenced until the end of the window
+async fn take_undelivered(
+    notifications: &mut Notifications,
+    now: DateTime<Utc>,
+) -> Result<Vec<Notification>, AppError> {
+    if notifications
+        .list
+        .iter()
+        .all(|n| n.delivered || n.state != NotificationState::Open)
+    {
+        return Ok(Vec::new());
+    }
+
+    let windows = datastore::get_all_maintenance_windows().await?;
+
+    Ok(select_undelivered(notifications, &windows, now))
+}
+
+fn select_undelivered(
+    notifications: &mut Notifications,
+    windows: &[MaintenanceWindow],
+    now: DateTime<Utc>,
+) -> Vec<Notification> {
+    let mut to_deliver = Vec::new();
+
+    for notification in notifications
+        .list
+        .iter_mut()
+        .filter(|n| !n.delivered && n.state == NotificationState::Open)
+    {
+        let maintenance_end = windows
+            .iter()
+            .filter(|w| w.applies_to(&notifications.ipaddress, notification.plugin_id.as_deref()))
+            .filter_map(|w| w.get_active_end(now))
+            .max();
+
+        if let Some(end) = maintenance_end {
+            if notification.silenced_until.is_none_or(|until| until < end) {
+                notification.silence(end);
+            }
+        }
+
+        if !notification.is_silenced(now) {
+            notification.delivered = true;
+            to_deliver.push(notification.clone());
+        }
+    }
+    to_deliver
+}
+
+async fn save_history(ipaddress: &str, resolved: Vec<Notification>) -> Result<(), AppError> {
+    for notification in resolved {
+        datastore::insert_notification_history(&ResolvedNotification {
+            ipaddress: ipaddress.to_owned(),
+            notification,
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+/// Applies the script results of a check to the existing notifications of a server.
+/// Returns the updated notifications and the ones that were resolved since the script doesn't match anymore.
+/// Notifications without a result (ie. of other plugins) are kept as they are
+fn apply_results(
+    existing: Notifications,
+    results: &ScriptResults,
+    now: DateTime<Utc>,
+) -> (Notifications, Vec<Notification>) {
+    let mut resolved = Vec::new();
+    let mut list = Vec::new();
+
+    for mut notification in existing.list {
+        match results.get(&notification.id) {
+            Some(ScriptResult {
+                notification_def,
+                matched: true,
+                ..
+            }) => {
+                notification.message = notification_def.message.clone();
+                notification.notification_level = notification_def.notification_level.clone();
+                notification.last_seen = now;
+                list.push(notification);
+            }
+            Some(ScriptResult { matched: false, .. }) => {
+                notification.resolve(now);
+                resolved.push(notification);
+            }
+            None => list.push(notification),
+        }
+    }
+
+    for result in results.values().filter(|result| result.matched) {
+        let notification_def = &result.notification_def;
+
+        if list.iter().any(|n| n.id == notification_def.id) {
+            continue;
+        }
+
+        let mut notification = Notification::new(
+            &notification_def.id,
+            &notification_def.name,
+            &notification_def.message,
+            notification_def.notification_level.clone(),
+        );
+        notification.first_seen = now;
+        notification.last_seen = now;
+        notification.plugin_id = Some(result.plugin_id.clone());
+
+        if notification_def.auto_ack {
+            notification.acknowledge(now);
+        }
+
+        log::trace!("Created notification {:?}", notification);
+
+        list.push(notification);
+    }
+
+    (
+        Notifications {
+            ipaddress: existing.ipaddress,
+            list,
+        },
+        resolved,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{
+        maintenance::MaintenanceSchedule,
+        plugin::{common::Script, notification::Level},
+    };
+
+    use super::*;
+
+    fn make_result(id: &str, auto_ack: bool, matched: bool) -> (String, ScriptResult) {
+        (
+            id.to_owned(),
+            ScriptResult {
+                plugin_id: "plugin".to_owned(),
+                notification_def: NotificationDef {
+                    id: id.to_owned(),
+                    name: id.to_owned(),
+                    data_id: "data".to_owned(),
+                    message: format!("{} message", id),
+                    auto_ack,
+                    script: Script::default(),
+                    notification_level: Level::Warn,
+                },
+                matched,
+            },
+        )
+    }
+
+    #[test]
+    fn test_apply_results() {
+        let earlier = Utc::now() - chrono::Duration::minutes(5);
+        let now = Utc::now();
+
+        let mut still_matching = Notification::new("cpu", "cpu", "old", Level::Warn);
+        still_matching.first_seen = earlier;
+        still_matching.acknowledge(earlier);
+
+        let existing = Notifications {
+            ipaddress: "192.168.178.2".to_owned(),
+            list: vec![
+                still_matching,
+                Notification::new("disk", "disk", "", Level::Error),
+                Notification::new("other_plugin", "other", "", Level::Info),
+            ],
+        };
+
+        let results: ScriptResults = [
+            make_result("cpu", false, true),
+            make_result("disk", false, false),
+            make_result("update", true, true),
+        ]
+        .into_iter()
+        .collect();
+
+        let (notifications, resolved) = apply_results(existing, &results, now);
+
+        let ids: Vec<&str> = notifications.list.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["cpu", "other_plugin", "update"]);
+
+        let cpu = &notifications.list[0];
+        assert_eq!(cpu.state, NotificationState::Acknowledged);
+        assert_eq!(cpu.first_seen, earlier);
+        assert_eq!(cpu.last_seen, now);
+        assert_eq!(cpu.message, "cpu message");
+
+        let update = &notifications.list[2];
+        assert_eq!(update.state, NotificationState::Acknowledged);
+        assert_eq!(update.plugin_id.as_deref(), Some("plugin"));
+        assert!(!update.delivered);
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].id, "disk");
+        assert_eq!(resolved[0].state, NotificationState::Resolved);
+        assert_eq!(resolved[0].resolved_at, Some(now));
+    }
+
+    #[test]
+    fn test_select_undelivered_during_maintenance() {
+        let now = Utc::now();
+
+        let mut in_maintenance = Notification::new("backup", "backup", "", Level::Error);
+        in_maintenance.plugin_id = Some("nas".to_owned());
+        let mut other_plugin = Notification::new("cpu", "cpu", "", Level::Warn);
+        other_plugin.plugin_id = Some("proxmox".to_owned());
+        let mut silenced = Notification::new("update", "update", "", Level::Info);
+        silenced.silence(now + Duration::hours(1));
+
+        let mut notifications = Notifications {
+            ipaddress: "192.168.178.2".to_owned(),
+            list: vec![in_maintenance, other_plugin, silenced],
+        };
+        let windows = vec![MaintenanceWindow {
+            id: "nightly".to_owned(),
+            name: String::new(),
+            ipaddress: "192.168.178.2".to_owned(),
+            plugin_id: Some("nas".to_owned()),
+            schedule: MaintenanceSchedule::Range {
+                start: now - Duration::minutes(5),
+                end: now + Duration::minutes(30),
+            },
+        }];
+
+        let to_deliver = select_undelivered(&mut notifications, &windows, now);
+
+        assert_eq!(to_deliver.len(), 1);
+        assert_eq!(to_deliver[0].id, "cpu");
+
+        let list = &notifications.list;
+        assert_eq!(list[0].silenced_until, Some(now + Duration::minutes(30)));
+        assert!(!list[0].delivered);
+        assert!(list[1].delivered);
+        assert!(!list[2].delivered);
+
+        // delivered after the end of the window
+        let to_deliver = select_undelivered(&mut notifications, &windows, now + Duration::hours(2));
+        assert_eq!(to_deliver.len(), 2);
+    }
+}
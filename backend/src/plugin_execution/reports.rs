@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    common,
+    datastore::{self, TimeSeriesQuery},
+    models::{
+        error::AppError,
+        plugin::monitoring::{Aggregation, TimeSeriesResponseData},
+        report::{AvailabilityReport, ReportPeriod},
+    },
+};
+
+use super::monitoring;
+
+pub const SERVER_STATUS_SERIES: &str = "server_status";
+
+/// the status checks are aggregated to buckets of this size - a bucket is only up if all checks within it were successful
+const BUCKET_SECONDS: i64 = 60;
+
+/// (ipaddress, sub identifier)
+type SeriesKey = (String, Option<String>);
+
+#[derive(Debug, Clone, PartialEq)]
+struct AvailabilityStats {
+    uptime_percentage: Option<f64>,
+    outages: u32,
+    mttr_seconds: Option<i64>,
+    longest_outage_seconds: Option<i64>,
+}
+
+/// Computes the availability from a series with the value 1 for up and 0 for down, ie. the server status.
+/// For series with a sub identifier (ie. the health of the features of a server) there is one report per sub identifier
+pub async fn get_availability_reports(
+    series_id: &str,
+    ipaddress: Option<&str>,
+    period: ReportPeriod,
+) -> Result<Vec<AvailabilityReport>, AppError> {
+    let monitoring = monitoring::get_monitoring_config_for_series(series_id)?
+        .ok_or(AppError::DataNotFound(series_id.to_owned()))?;
+
+    let end = Utc::now();
+    let start = end - period.get_duration();
+
+    let query = create_availability_query(
+        series_id,
+        monitoring.sub_identifier.is_some(),
+        ipaddress,
+        start,
+    );
+
+    log::trace!("querying availability: {:?}", query);
+
+    let data = datastore::query_timeseries_data(&query).await?;
+
+    Ok(group_samples(&data, monitoring.sub_identifier.is_some())
+        .into_iter()
+        .map(|((ipaddress, sub_identifier), samples)| {
+            let stats = compute_availability(&samples, end);
+
+            AvailabilityReport {
+                ipaddress,
+                sub_identifier,
+                series_id: series_id.to_owned(),
+                period: period.clone(),
+                start,
+                end,
+                uptime_percentage: stats.uptime_percentage,
+                outages: stats.outages,
+                mttr_seconds: stats.mttr_seconds,
+                longest_outage_seconds: stats.longest_outage_seconds,
+            }
+        })
+        .collect())
+}
+
+/// Sends the availability of the servers to the mail addresses of all users - each user only gets the servers the user can access
+pub async fn send_availability_digest(period: ReportPeriod) -> Result<(), AppError> {
+    if !common::is_smtp_config_valid()? {
+        return Err(AppError::EmailConfigError("smtp_host".to_owned()));
+    }
+    let from_address = datastore::get_config()?.get_string("email_from")?;
+
+    let reports = get_availability_reports(SERVER_STATUS_SERIES, None, period.clone()).await?;
+    let servers = datastore::get_all_servers_from_cache()?;
+
+    let lines: Vec<(&str, String)> = reports
+        .iter()
+        .map(|report| {
+            let name = servers
+                .iter()
+                .find(|server| format!("{}", server.get_ipaddress()) == report.ipaddress)
+                .map(|server| server.get_name())
+                .filter(|name| !name.is_empty())
+                .map(|name| format!("{} ({})", name, report.ipaddress))
+                .unwrap_or(report.ipaddress.clone());
+
+            (report.ipaddress.as_str(), format_report_line(&name, report))
+        })
+        .collect();
+
+    let subject = format!(
+        "Availability report of the last {}",
+        format!("{:?}", period).to_lowercase()
+    );
+    let start = reports
+        .first()
+        .map(|report| report.start.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+
+    for user in datastore::get_all_users(false).await? {
+        if user.get_email().is_empty() {
+            continue;
+        }
+
+        let user_lines: Vec<&str> = lines
+            .iter()
+            .filter(|(ipaddress, _)| user.can_access_server_str(ipaddress))
+            .map(|(_, line)| line.as_str())
+            .collect();
+        if user_lines.is_empty() {
+            continue;
+        }
+
+        let body = format!(
+            "Availability from {} to {}\n\n{}\n\nRegards,\nyour Server-Manager",
+            start,
+            Utc::now().format("%Y-%m-%d %H:%M UTC"),
+            user_lines.join("\n")
+        );
+
+        if let Err(err) =
+            common::send_email(&from_address, &user.get_email(), &subject, &body).await
+        {
+            log::error!(
+                "Could not send availability report to user {}: {}",
+                user.get_user_id(),
+                err
+            );
+        }
+    }
+    Ok(())
+}
+
+fn format_report_line(name: &str, report: &AvailabilityReport) -> String {
+    let Some(uptime_percentage) = report.uptime_percentage else {
+        return format!("{}: no data", name);
+    };
+
+    let mut line = format!(
+        "{}: {:.2}% uptime, {} outage(s)",
+        name, uptime_percentage, report.outages
+    );
+
+    if let (Some(mttr), Some(longest)) = (report.mttr_seconds, report.longest_outage_seconds) {
+        line.push_str(&format!(
+            ", MTTR {}, longest outage {}",
+            common::format_duration(Duration::seconds(mttr)),
+            common::format_duration(Duration::seconds(longest))
+        ));
+    }
+    line
+}
+
+fn create_availability_query(
+    series_id: &str,
+    has_sub_identifier: bool,
+    ipaddress: Option<&str>,
+    start: DateTime<Utc>,
+) -> TimeSeriesQuery {
+    let mut group_by: Vec<String> = vec![common::IDENTIFIER.to_owned()];
+    if has_sub_identifier {
+        group_by.push(common::SUB_IDENTIFIER.to_owned());
+    }
+
+    TimeSeriesQuery {
+        series_id: series_id.to_owned(),
+        group_by,
+        filters: ipaddress
+            .map(|ipaddress| (common::IDENTIFIER.to_owned(), ipaddress.to_owned()))
+            .into_iter()
+            .collect(),
+        from: start,
+        to: None,
+        aggregations: vec![Aggregation::Min],
+        bucket_seconds: Some(BUCKET_SECONDS as u64),
+    }
+}
+
+/// Returns the samples (timestamp, up) per server and sub identifier - ordered by timestamp
+fn group_samples(
+    data: &TimeSeriesResponseData,
+    has_sub_identifier: bool,
+) -> BTreeMap<SeriesKey, Vec<(DateTime<Utc>, bool)>> {
+    let mut map: BTreeMap<SeriesKey, Vec<(DateTime<Utc>, bool)>> = BTreeMap::new();
+
+    for row in &data.dataset {
+        let value_index = if has_sub_identifier { 2 } else { 1 };
+
+        let (Some(ipaddress), Some(value), Some(timestamp)) = (
+            row.first().and_then(common::convert_value_to_str),
+            row.get(value_index).and_then(|v| v.as_f64()),
+            row.get(value_index + 1)
+                .and_then(|v| v.as_str())
+                .and_then(|v| DateTime::parse_from_rfc3339(v).ok()),
+        ) else {
+            continue;
+        };
+
+        let sub_identifier = if has_sub_identifier {
+            row.get(1).and_then(common::convert_value_to_str)
+        } else {
+            None
+        };
+
+        map.entry((ipaddress, sub_identifier))
+            .or_default()
+            .push((timestamp.with_timezone(&Utc), value > 0.0));
+    }
+    map
+}
+
+/// Each sample is valid until the next one but at most for one bucket - so that gaps without data (ie. the server-manager was not running) are not counted
+fn compute_availability(
+    samples: &[(DateTime<Utc>, bool)],
+    end: DateTime<Utc>,
+) -> AvailabilityStats {
+    let bucket = Duration::seconds(BUCKET_SECONDS);
+
+    let mut up = Duration::zero();
+    let mut down = Duration::zero();
+    let mut outages: Vec<Duration> = Vec::new();
+    let mut outage_start: Option<DateTime<Utc>> = None;
+
+    for (index, (timestamp, is_up)) in samples.iter().enumerate() {
+        let valid_until = samples
+            .get(index + 1)
+            .map(|(next, _)| *next)
+            .unwrap_or(end)
+            .min(*timestamp + bucket);
+        let covered = (valid_until - *timestamp).max(Duration::zero());
+
+        if *is_up {
+            up += covered;
+
+            if let Some(start) = outage_start.take() {
+                outages.push(*timestamp - start);
+            }
+        } else {
+            down += covered;
+            outage_start.get_or_insert(*timestamp);
+
+            if index == samples.len() - 1 {
+                // still ongoing
+                outages.push(valid_until - outage_start.unwrap_or(*timestamp));
+            }
+        }
+    }
+
+    let total = (up + down).num_seconds();
+
+    AvailabilityStats {
+        uptime_percentage: (total > 0).then(|| up.num_seconds() as f64 * 100.0 / total as f64),
+        outages: outages.len() as u32,
+        mttr_seconds: (!outages.is_empty())
+            .then(|| outages.iter().map(|o| o.num_seconds()).sum::<i64>() / outages.len() as i64),
+        longest_outage_seconds: outages.iter().map(|o| o.num_seconds()).max(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_compute_availability() {
+        let data = TimeSeriesResponseData {
+            query: String::new(),
+            columns: Vec::new(),
+            dataset: [1, 1, 0, 0, 1, 1, 1, 0, 1, 1]
+                .iter()
+                .enumerate()
+                .map(|(minute, value)| {
+                    vec![
+                        json!("192.168.178.2"),
+                        json!(value),
+                        json!(format!("2023-09-01T02:{:02}:00.000000Z", minute)),
+                    ]
+                })
+                .collect(),
+            count: 10,
+        };
+
+        let grouped = group_samples(&data, false);
+        let samples = grouped
+            .get(&("192.168.178.2".to_owned(), None))
+            .expect("samples of the server");
+        assert_eq!(samples.len(), 10);
+
+        let end = samples[9].0 + Duration::minutes(1);
+        let stats = compute_availability(samples, end);
+
+        assert_eq!(
+            stats,
+            AvailabilityStats {
+                uptime_percentage: Some(70.0),
+                outages: 2,
+                mttr_seconds: Some(90),
+                longest_outage_seconds: Some(120),
+            }
+        );
+    }
+
+    #[test]
+    fn test_compute_availability_ongoing_outage_and_gap() {
+        let start = DateTime::parse_from_rfc3339("2023-09-01T02:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // the hour without data is neither up nor down
+        let samples = vec![
+            (start, true),
+            (start + Duration::hours(1), false),
+            (start + Duration::hours(1) + Duration::minutes(1), false),
+        ];
+
+        let stats = compute_availability(&samples, start + Duration::hours(2));
+
+        assert_eq!(stats.uptime_percentage, Some(100.0 / 3.0));
+        assert_eq!(stats.outages, 1);
+        assert_eq!(stats.longest_outage_seconds, Some(120));
+    }
+}
@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    commands::{self, CommandInput},
+    common::{self, MqttMessage},
+    datastore,
+    models::{error::AppError, plugin::data::DataDef},
+    plugin_execution::monitoring::MonitoringProcessor,
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Subscription {
+    topic: String,
+    data: DataDef,
+    input: CommandInput,
+}
+
+/// Subscribes to all MQTT topics that are used as data source by the plugins of the configured servers
+/// and feeds every received message into the monitoring so that the values are persisted without polling.
+///
+/// The list of subscriptions is refreshed regularly, so that new servers or features are picked up.
+pub async fn process_mqtt_subscriptions() -> Result<(), AppError> {
+    let mut receiver = common::subscribe_to_mqtt_messages();
+    let mut refresh = tokio::time::interval(REFRESH_INTERVAL);
+    let mut subscriptions: Vec<Subscription> = Vec::new();
+
+    loop {
+        tokio::select! {
+            _ = refresh.tick() => {
+                match get_subscriptions().await {
+                    Ok(refreshed) => subscriptions = refreshed,
+                    Err(err) => {
+                        log::error!("Could not refresh the MQTT subscriptions. Keeping the previous ones. Error was {}", err);
+                        continue;
+                    }
+                }
+
+                for subscription in &subscriptions {
+                    if let Err(err) = common::subscribe_mqtt_topic(subscription.topic.as_str()).await {
+                        log::error!("Could not subscribe to the MQTT topic {}. Error was {}", subscription.topic, err);
+                    }
+                }
+            }
+            received = receiver.recv() => match received {
+                Ok(message) => {
+                    if let Err(err) = process_message(&subscriptions, message).await {
+                        log::error!("Could not process MQTT message. Error was {}", err);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("Skipped {} MQTT messages since the processing is too slow", skipped);
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+async fn get_subscriptions() -> Result<Vec<Subscription>, AppError> {
+    let servers = datastore::get_all_servers_from_cache()?;
+    let plugins = datastore::get_all_plugins()?;
+    let crypto_key = datastore::get_crypto_key()?;
+
+    let mut subscriptions = Vec::new();
+
+    for server in servers {
+        for plugin in &plugins {
+            let Some(feature) = server.find_feature(plugin.id.as_str()) else {
+                continue;
+            };
+
+            for data in plugin
+                .data
+                .iter()
+                .filter(|d| !d.monitoring.is_empty() && commands::mqtt::is_subscription(d))
+            {
+                let inputs = commands::mqtt::make_command_input_from_data(
+                    &server,
+                    crypto_key.as_str(),
+                    data,
+                    None,
+                    &feature,
+                    plugin,
+                    &true,
+                )
+                .await?;
+
+                for input in inputs {
+                    if let Some(topic) = commands::mqtt::get_subscription_topic(&input)? {
+                        subscriptions.push(Subscription {
+                            topic,
+                            data: data.clone(),
+                            input,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    log::debug!("Found {} MQTT subscriptions", subscriptions.len());
+
+    Ok(subscriptions)
+}
+
+async fn process_message(
+    subscriptions: &[Subscription],
+    message: MqttMessage,
+) -> Result<(), AppError> {
+    let matching: Vec<&Subscription> = subscriptions
+        .iter()
+        .filter(|s| rumqttc::matches(message.topic.as_str(), s.topic.as_str()))
+        .collect();
+
+    if matching.is_empty() {
+        return Ok(());
+    }
+
+    let mut monitoring_processor = MonitoringProcessor::new();
+
+    for subscription in matching {
+        let response = match &subscription.data.post_process {
+            Some(script) => super::pre_or_post_process(message.payload.as_str(), script)?,
+            None => message.payload.clone(),
+        };
+
+        monitoring_processor
+            .process_pushed(
+                &subscription.data,
+                &[(subscription.input.clone(), response)],
+            )
+            .await?;
+    }
+
+    monitoring_processor.finish().await
+}
@@ -12,13 +12,14 @@ pub use appdata::AppData;
 
 use actix_files as fs;
 use actix_web::{
-    cookie::Key, dev::ServiceRequest, middleware::Logger, web, App, HttpRequest, HttpServer, Result,
+    cookie::Key, dev::ServiceRequest, middleware::Logger, web, App, HttpMessage, HttpRequest,
+    HttpServer, Result,
 };
 use std::{path::PathBuf, time::Duration};
 
 use crate::{
     datastore::{self},
-    models::error::AppError,
+    models::{error::AppError, users::User},
 };
 
 const AUTO_LOGOUT_AFTER_MINUTES: u64 = 30;
@@ -50,13 +51,20 @@ pub async fn start_webserver(
                 .build(),
             )
             .app_data(web::Data::new(app_data.clone()))
-            .wrap(Logger::default())
+            // the query is not logged, since the token of the event stream is part of it
+            .wrap(
+                Logger::new(r#"%a "%{request_line}xi" %s %b "%{Referer}i" "%{User-Agent}i" %T"#)
+                    .custom_request_replace("request_line", |req| {
+                        format!("{} {} {:?}", req.method(), req.path(), req.version())
+                    }),
+            )
             .service(
                 web::scope("/backend")
                     .configure(init_token_secured_api)
                     .wrap(HttpAuthentication::bearer(validator_fn)),
             )
             .service(web::scope("/backend_nt").configure(init_no_token_api))
+            .service(routes::get_metrics)
             .configure(init_static)
     })
     .bind(bind_address)?
@@ -71,14 +79,15 @@ async fn validator_fn(
 ) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
     let token = credentials.token();
 
-    match datastore::is_valid_token(token) {
-        Ok(valid) => {
-            if valid {
-                Ok(req)
-            } else {
-                log::warn!("Token is invalid");
-                Err((AuthenticationError::new(Bearer::default()).into(), req))
-            }
+    match get_user_of_token(token).await {
+        Ok(Some(user)) => {
+            // the route handlers check the role and permissions of the user
+            req.extensions_mut().insert(user);
+            Ok(req)
+        }
+        Ok(None) => {
+            log::warn!("Token is invalid");
+            Err((AuthenticationError::new(Bearer::default()).into(), req))
         }
         Err(err) => {
             log::error!("Error while validating token: {}", err);
@@ -87,11 +96,37 @@ async fn validator_fn(
     }
 }
 
+/// the user of a valid token of a session or an API token - None if the token is invalid or the user was deleted in the meantime.
+/// The role of the user is restricted to the scope of an API token or the restriction of the session
+async fn get_user_of_token(token: &str) -> Result<Option<User>, AppError> {
+    let (user_id, max_role) = match datastore::get_token_info(token).await? {
+        Some(token_info) => (token_info.get_user_id(), token_info.get_max_role()),
+        None => match datastore::use_api_token(token).await? {
+            Some(api_token) => (api_token.user_id, Some(api_token.scope.get_role())),
+            None => return Ok(None),
+        },
+    };
+
+    match datastore::get_user(&user_id).await {
+        Ok(user) => {
+            let mut user = user.copy_no_passwd();
+            if let Some(max_role) = max_role {
+                user.restrict_role(max_role);
+            }
+            Ok(Some(user))
+        }
+        Err(AppError::UserNotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
 fn init_no_token_api(cfg: &mut web::ServiceConfig) {
     cfg.service(routes::get_one_time_key);
     cfg.service(routes::authenticate);
     cfg.service(routes::get_users_exist);
     cfg.service(routes::post_first_user);
+    cfg.service(routes::post_password_reset);
+    cfg.service(routes::put_user_password);
 
     cfg.service(routes::get_events_from_stream);
 }
@@ -125,13 +160,39 @@ fn init_token_secured_api(cfg: &mut web::ServiceConfig) {
     cfg.service(routes::get_users);
     cfg.service(routes::get_user_by_id);
     cfg.service(routes::post_user);
+    cfg.service(routes::post_user_invitation);
+    cfg.service(routes::put_user);
     cfg.service(routes::delete_user);
     cfg.service(routes::put_user_changepassword);
+    cfg.service(routes::get_user_notification_channels);
+    cfg.service(routes::put_user_notification_channels);
+    cfg.service(routes::post_user_notification_channel_test);
+    cfg.service(routes::get_user_api_tokens);
+    cfg.service(routes::post_user_api_token);
+    cfg.service(routes::delete_user_api_token);
+    cfg.service(routes::post_user_two_factor);
+    cfg.service(routes::post_user_two_factor_verify);
+    cfg.service(routes::delete_user_two_factor);
 
     cfg.service(routes::get_monitoring_data);
     cfg.service(routes::get_monitoring_ids);
 
     cfg.service(routes::get_notifications);
+    cfg.service(routes::get_notifications_history);
+    cfg.service(routes::post_notification_acknowledge);
+    cfg.service(routes::post_notification_resolve);
+    cfg.service(routes::post_notification_silence);
+
+    cfg.service(routes::get_availability_reports);
+
+    cfg.service(routes::get_maintenance_windows);
+    cfg.service(routes::put_maintenance_window);
+    cfg.service(routes::delete_maintenance_window);
+
+    cfg.service(routes::get_alert_rules);
+    cfg.service(routes::get_alert_rule);
+    cfg.service(routes::put_alert_rule);
+    cfg.service(routes::delete_alert_rule);
 
     cfg.service(routes::get_config);
     cfg.service(routes::post_config);
@@ -142,6 +203,8 @@ fn init_static(cfg: &mut web::ServiceConfig) {
     cfg.route("/favicon.ico", web::get().to(fav_icon));
     cfg.route("/", web::get().to(index_html));
     cfg.route("/index.html", web::get().to(index_html));
+    // target of the invitation and password reset links
+    cfg.route("/setpassword", web::get().to(index_html));
     cfg.route("/{filename:main.*\\.js}", web::get().to(named_file));
     cfg.route("/{filename:polyfills.*\\.js}", web::get().to(named_file));
     cfg.route("/{filename:runtime.*\\.js}", web::get().to(named_file));
@@ -7,23 +7,33 @@ use std::net::IpAddr;
 use std::time::Duration;
 use std::vec;
 
-use crate::common::{ClientKey, OneTimeKey, Token};
+use crate::common::{ClientKey, LoginAttempts, OneTimeKey, Token};
+use crate::models::alert::AlertRule;
 use crate::event_handling::Event;
 use crate::models::config::dns_server::DNSServer;
 use crate::models::config::Configuration;
 use crate::models::error::AppError;
+use crate::models::maintenance::MaintenanceWindow;
+use crate::models::plugin::monitoring::{Aggregation, MonitoringRange};
 use crate::models::plugin::notification::Notifications;
+use crate::models::report::{AvailabilityReport, ReportPeriod};
 use crate::models::request::common::QueryParamsAsMap;
 use crate::models::request::plugin::PluginsAction;
 use crate::models::request::server::{
     NetworkActionType, NetworksAction, ServerAction, ServerActionType, ServersAction,
     ServersActionType,
 };
-use crate::models::request::user::PasswordChange;
+use crate::models::request::user::{
+    ApiTokenCreation, PasswordChange, PasswordResetRequest, PasswordSetting, TwoFactorCode,
+};
+use crate::models::response::data_result::ConditionCheckResult;
 use crate::models::response::status::Status;
 use crate::models::response::system_information::SystemInformation;
 use crate::models::server::Server;
-use crate::models::token::UserToken;
+use crate::models::token::{ApiToken, CreatedApiToken, UserAction, UserToken};
+use crate::models::users::notification_channel::NotificationChannel;
+use crate::models::users::role::{Permissions, Role};
+use crate::models::users::two_factor::{TwoFactor, TwoFactorEnrollment};
 use crate::models::users::User;
 use crate::webserver::appdata::AppData;
 use crate::{common, event_handling, other_functions};
@@ -33,12 +43,19 @@ use actix_web::{delete, Responder};
 use actix_web::{get, post, put, web, HttpRequest, HttpResponse};
 use actix_web_lab::sse;
 use http::{header, HeaderName, HeaderValue};
-use sqlx::types::chrono::NaiveDateTime;
+use sqlx::types::chrono::{DateTime, NaiveDateTime, Utc};
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+/// a new password reset link is only sent after this time - it would replace the link that was sent before
+const PASSWORD_RESET_MIN_INTERVAL_MINUTES: i64 = 15;
 
 #[post("/networks/actions")]
 pub async fn post_networks_action(
+    user: web::ReqData<User>,
     query: web::Json<NetworksAction>,
 ) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     let params_map = QueryParamsAsMap::from(query.params.clone());
 
     let dns_server_result = datastore::get_all_dnsservers().await;
@@ -79,8 +96,12 @@ pub async fn post_networks_action(
 }
 
 #[get("/servers")]
-pub async fn get_servers() -> Result<HttpResponse, AppError> {
-    let servers = datastore::get_all_servers(true).await?;
+pub async fn get_servers(user: web::ReqData<User>) -> Result<HttpResponse, AppError> {
+    let servers: Vec<Server> = datastore::get_all_servers(true)
+        .await?
+        .into_iter()
+        .filter(|server| user.can_access_server(&server.get_ipaddress()))
+        .collect();
 
     // client doesn't need to know the credentials and or parameters normally
     // only if a user wants to configure a feature, the information is required on the client side
@@ -91,7 +112,12 @@ pub async fn get_servers() -> Result<HttpResponse, AppError> {
 }
 
 #[post("/servers")]
-pub async fn post_servers(query: web::Json<Server>) -> Result<HttpResponse, AppError> {
+pub async fn post_servers(
+    user: web::ReqData<User>,
+    query: web::Json<Server>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     datastore::insert_server(&query.0).await?;
 
     Ok(HttpResponse::Ok().finish())
@@ -99,6 +125,7 @@ pub async fn post_servers(query: web::Json<Server>) -> Result<HttpResponse, AppE
 
 #[post("/servers/actions")]
 pub async fn post_servers_actions(
+    user: web::ReqData<User>,
     query: web::Json<ServersAction>,
 ) -> Result<HttpResponse, AppError> {
     let params_map = QueryParamsAsMap::from(query.params.clone());
@@ -120,12 +147,22 @@ pub async fn post_servers_actions(
                 None => Vec::new(),
             };
 
-            let list = other_functions::statuscheck::status_check(ips_to_check, true).await?;
+            let list: Vec<Status> = other_functions::statuscheck::status_check(ips_to_check, true)
+                .await?
+                .into_iter()
+                .filter(|status| user.can_access_server(&status.ipaddress))
+                .collect();
 
             Ok(HttpResponse::Ok().json(list))
         }
         ServersActionType::FeatureScan => {
-            let servers = datastore::get_all_servers(true).await?;
+            user.check_role(Role::Operator)?;
+
+            let servers = datastore::get_all_servers(true)
+                .await?
+                .into_iter()
+                .filter(|server| user.can_access_server(&server.get_ipaddress()))
+                .collect();
 
             let upnp_activated = !datastore::is_plugin_disabled("upnp").await.unwrap_or(true);
 
@@ -136,13 +173,19 @@ pub async fn post_servers_actions(
             Ok(HttpResponse::Ok().json(list))
         }
         ServersActionType::ActionConditionCheck => {
-            Ok(HttpResponse::Ok().json(datastore::get_all_condition_results()?.to_vec()))
+            let list: Vec<ConditionCheckResult> = datastore::get_all_condition_results()?
+                .into_iter()
+                .filter(|result| user.can_access_server(&result.ipaddress))
+                .collect();
+
+            Ok(HttpResponse::Ok().json(list))
         }
     }
 }
 
 #[post("/servers/{ipaddress}/actions")]
 pub async fn post_servers_by_ipaddress_action(
+    user: web::ReqData<User>,
     data: web::Data<AppData>,
     query: web::Json<ServerAction>,
     path: web::Path<String>,
@@ -150,6 +193,7 @@ pub async fn post_servers_by_ipaddress_action(
     let Ok(ipaddress): Result<IpAddr,_> = path.into_inner().parse() else {
         return Err(AppError::InvalidArgument("ipaddress".to_owned(), None));
     };
+    user.check_server_access(&ipaddress)?;
 
     let server = datastore::get_server(&ipaddress).await?;
 
@@ -157,6 +201,8 @@ pub async fn post_servers_by_ipaddress_action(
 
     match query.action_type {
         ServerActionType::FeatureScan => {
+            user.check_role(Role::Operator)?;
+
             let list = plugin_execution::discover_features(ipaddress, crypto_key, &true).await?;
 
             Ok(HttpResponse::Ok().json(list))
@@ -167,11 +213,14 @@ pub async fn post_servers_by_ipaddress_action(
             Ok(HttpResponse::Ok().json(list.first().unwrap_or(&Status::error(ipaddress))))
         }
         ServerActionType::ExecuteFeatureAction => {
+            user.check_role(Role::Operator)?;
+
             let params_map = QueryParamsAsMap::from(query.params.clone());
 
             let feature_id = params_map
                 .get("feature_id")
                 .ok_or(AppError::ArgumentNotFound("feature_id".to_owned()))?;
+            user.check_plugin_access(feature_id)?;
 
             let action_id = params_map
                 .get("action_id")
@@ -213,7 +262,12 @@ pub async fn post_servers_by_ipaddress_action(
 }
 
 #[put("/servers/{ipaddress}")]
-pub async fn put_servers_by_ipaddress(query: web::Json<Server>) -> Result<HttpResponse, AppError> {
+pub async fn put_servers_by_ipaddress(
+    user: web::ReqData<User>,
+    query: web::Json<Server>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     datastore::update_server(&query.0).await?;
 
     Ok(HttpResponse::Ok().finish())
@@ -222,11 +276,13 @@ pub async fn put_servers_by_ipaddress(query: web::Json<Server>) -> Result<HttpRe
 #[get("/servers/{ipaddress}")]
 #[logfn(err = "Error", fmt = "Could not get server: {:?}")]
 pub async fn get_servers_by_ipaddress(
+    user: web::ReqData<User>,
     session: Session,
     path: web::Path<String>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> Result<HttpResponse, AppError> {
     let ipaddress = path.into_inner().parse()?;
+    user.check_server_access(&ipaddress)?;
     let params = query.into_inner();
 
     let full_data: bool = params
@@ -236,6 +292,9 @@ pub async fn get_servers_by_ipaddress(
     let server = datastore::get_server(&ipaddress).await?;
 
     if full_data {
+        // contains the credentials of the features - only needed to configure the server
+        user.check_role(Role::Admin)?;
+
         let client_key = ClientKey::get_from_session(session)?.ok_or(AppError::Unknown(
             "Could not find client key in session".to_owned(),
         ))?;
@@ -252,8 +311,11 @@ pub async fn get_servers_by_ipaddress(
 
 #[delete("/servers/{ipaddress}")]
 pub async fn delete_servers_by_ipaddress(
+    user: web::ReqData<User>,
     path: web::Path<String>,
 ) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     let ipaddress = path.into_inner().parse()?;
 
     datastore::delete_server(&ipaddress).await?;
@@ -291,8 +353,11 @@ pub async fn get_plugins_actions(
 
 #[put("/plugins/actions")]
 pub async fn put_plugins_actions(
+    user: web::ReqData<User>,
     query: web::Json<PluginsAction>,
 ) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     let action = query.into_inner();
     let params_map = QueryParamsAsMap::from(action.params);
 
@@ -311,7 +376,12 @@ pub async fn put_plugins_actions(
 }
 
 #[post("/configurations/dnsservers")]
-pub async fn post_dnsservers(query: web::Json<DNSServer>) -> Result<HttpResponse, AppError> {
+pub async fn post_dnsservers(
+    user: web::ReqData<User>,
+    query: web::Json<DNSServer>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     let server = query.into_inner();
 
     datastore::insert_dnsserver(&server).await?;
@@ -330,7 +400,12 @@ pub async fn get_dnsservers() -> Result<HttpResponse, AppError> {
 }
 
 #[delete("/configurations/dnsservers/{ipaddress}")]
-pub async fn delete_dnsservers(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+pub async fn delete_dnsservers(
+    user: web::ReqData<User>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     let ipaddress = path.into_inner().parse()?;
 
     datastore::delete_dnsserver(&ipaddress).await?;
@@ -352,17 +427,23 @@ pub async fn get_smtp_config_valid() -> Result<HttpResponse, AppError> {
 }
 
 #[get("/users")]
-pub async fn get_users() -> Result<HttpResponse, AppError> {
+pub async fn get_users(user: web::ReqData<User>) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     let result = datastore::get_all_users(false).await?;
 
     Ok(HttpResponse::Ok().json(result))
 }
 
 #[get("/users/{user_id}")]
-pub async fn get_user_by_id(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+pub async fn get_user_by_id(
+    user: web::ReqData<User>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
     let user_id = path.into_inner();
+    user.check_self_or_admin(&user_id)?;
 
-    let result = datastore::get_user(&user_id).await?;
+    let result = datastore::get_user(&user_id).await?.copy_no_passwd();
 
     Ok(HttpResponse::Ok().json(result))
 }
@@ -375,8 +456,53 @@ pub async fn get_users_exist() -> Result<HttpResponse, AppError> {
 }
 
 #[post("/users")]
-pub async fn post_user(query: web::Json<User>) -> Result<HttpResponse, AppError> {
-    save_user_common(query).await
+pub async fn post_user(
+    user: web::ReqData<User>,
+    query: web::Json<User>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
+    save_user_common(query.into_inner()).await
+}
+
+/// Changes the name, mail address, role and permissions of a user - the password and notification channels are kept
+#[put("/users/{user_id}")]
+pub async fn put_user(
+    user: web::ReqData<User>,
+    path: web::Path<String>,
+    query: web::Json<User>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
+    let changed = query.into_inner();
+    let mut existing = datastore::get_user(&path.into_inner()).await?;
+
+    if existing.get_role() == Role::Admin && changed.get_role() != Role::Admin {
+        check_other_admin_exists(&existing.get_user_id()).await?;
+    }
+
+    existing.update_name_and_email(changed.get_full_name(), changed.get_email());
+    existing.update_role_and_permissions(changed.get_role(), changed.get_permissions());
+    datastore::update_user(&existing).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// there has to be an admin left - otherwise nobody could manage the users anymore
+async fn check_other_admin_exists(user_id: &str) -> Result<(), AppError> {
+    let other_admin_exists = datastore::get_all_users(false)
+        .await?
+        .iter()
+        .any(|user| user.get_user_id() != user_id && user.get_role() == Role::Admin);
+
+    if other_admin_exists {
+        Ok(())
+    } else {
+        Err(AppError::InvalidArgument(
+            "role".to_owned(),
+            Some(format!("{} is the last admin", user_id)),
+        ))
+    }
 }
 
 #[post("/users_first")]
@@ -387,15 +513,18 @@ pub async fn post_first_user(query: web::Json<User>) -> Result<HttpResponse, App
         log::error!("Called function that is used for initial user save that allows and update without authorization. However, there are already users. So this is not the initial user creation.");
         Ok(HttpResponse::Unauthorized().finish())
     } else {
-        save_user_common(query).await
+        // the first user has to be able to manage everything else
+        let mut user = query.into_inner();
+        user.update_role_and_permissions(Role::Admin, Permissions::default());
+
+        save_user_common(user).await
     }
 }
 
-async fn save_user_common(query: web::Json<User>) -> Result<HttpResponse, AppError> {
+async fn save_user_common(mut user: User) -> Result<HttpResponse, AppError> {
     let initial_password = common::generate_short_random_string();
     let password_hash = common::hash_password(initial_password.as_str())?;
 
-    let mut user = query.0;
     user.update_password_hash(password_hash);
 
     let update_result = datastore::insert_user(&user).await?;
@@ -432,20 +561,461 @@ async fn save_user_common(query: web::Json<User>) -> Result<HttpResponse, AppErr
     }
 }
 
+/// Creates a user without a usable password and sends a link by mail, so that the user can set an own password
+#[post("/users/invitations")]
+pub async fn post_user_invitation(
+    current_user: web::ReqData<User>,
+    req: HttpRequest,
+    query: web::Json<User>,
+) -> Result<HttpResponse, AppError> {
+    current_user.check_role(Role::Admin)?;
+
+    if !common::is_smtp_config_valid()? {
+        return Err(AppError::EmailConfigError("smtp_host".to_owned()));
+    }
+
+    let mut user = query.into_inner();
+    if user.get_email().trim().is_empty() {
+        return Err(AppError::MissingArgument("email".to_owned()));
+    }
+    // nobody knows this password - it is replaced with the link of the invitation
+    user.update_password_hash(common::hash_password(
+        common::generate_long_random_string().as_str(),
+    )?);
+
+    if !datastore::insert_user(&user).await? {
+        return Err(AppError::DatabaseError(format!(
+            "Could not insert user '{}'",
+            user.get_user_id()
+        )));
+    }
+
+    let public_url = get_public_url(&req, true)?;
+    if let Err(err) = send_user_action_mail(&user, UserAction::Invitation, &public_url).await {
+        // without the mail the user could never log in
+        datastore::delete_user_action_tokens_of_user(&user.get_user_id()).await?;
+        datastore::delete_user(&user.get_user_id()).await?;
+        return Err(err);
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Sends a link to set a new password - the response is the same for unknown users, so that it cannot be used to find out user ids
+#[post("/users/passwordreset")]
+pub async fn post_password_reset(
+    req: HttpRequest,
+    query: web::Json<PasswordResetRequest>,
+) -> Result<HttpResponse, AppError> {
+    if let Err(err) = send_password_reset_mail(&req, &query.user_id).await {
+        log::warn!(
+            "Could not send the password reset mail for user '{}': {}",
+            query.user_id,
+            err
+        );
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+async fn send_password_reset_mail(req: &HttpRequest, user_id: &str) -> Result<(), AppError> {
+    if !common::is_smtp_config_valid()? {
+        return Err(AppError::EmailConfigError("smtp_host".to_owned()));
+    }
+    // the host header of an unauthenticated request could point the link to a foreign server
+    let public_url = get_public_url(req, false)?;
+    let user = datastore::get_user(user_id).await?;
+
+    // otherwise anybody could invalidate the link of the user and flood the mailbox
+    let min_interval = chrono::Duration::minutes(PASSWORD_RESET_MIN_INTERVAL_MINUTES);
+    if datastore::has_recent_user_action_token(user_id, UserAction::PasswordReset, min_interval)
+        .await?
+    {
+        return Err(AppError::Forbidden(
+            "a password reset was requested recently".to_owned(),
+        ));
+    }
+
+    send_user_action_mail(&user, UserAction::PasswordReset, &public_url).await
+}
+
+/// Sets the password with the token of an invitation or password reset link. The token can only be used once
+#[put("/users/password")]
+pub async fn put_user_password(
+    req: HttpRequest,
+    query: web::Json<PasswordSetting>,
+) -> Result<HttpResponse, AppError> {
+    let custom_header = req
+        .headers()
+        .get(HeaderName::from_static("x-custom"))
+        .ok_or(AppError::Unknown("Missing header".to_owned()))?;
+
+    let otk_tuple = get_existing_otk(custom_header).await?;
+
+    let secret = common::make_aes_secrect(query.user_id.as_str(), otk_tuple.1.as_str());
+    let password = common::aes_decrypt(&query.password, secret.as_str())?;
+    if password.is_empty() {
+        return Err(AppError::MissingArgument("password".to_owned()));
+    }
+
+    let user_action_token = datastore::take_user_action_token(&query.token, &query.user_id)
+        .await?
+        .ok_or(AppError::Forbidden(
+            "the link is invalid or has expired".to_owned(),
+        ))?;
+
+    let mut user = datastore::get_user(&user_action_token.get_user_id()).await?;
+    user.update_password_hash(common::hash_password(password.as_str())?);
+    datastore::update_user(&user).await?;
+
+    // a reset is the usual reaction to a compromised account - the old sessions and API tokens must not work anymore
+    datastore::delete_tokens_of_user(&user.get_user_id()).await?;
+    datastore::delete_api_tokens_of_user(&user.get_user_id()).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// The base url of the links in the mails. The configured url is needed if the server runs behind a reverse proxy
+fn get_public_url(req: &HttpRequest, use_request_host: bool) -> Result<String, AppError> {
+    let configured = datastore::get_config()?
+        .get_string("public_url")
+        .ok()
+        .filter(|url| !url.trim().is_empty());
+
+    match configured {
+        Some(url) => Ok(url.trim().trim_end_matches('/').to_owned()),
+        None if use_request_host => {
+            let connection_info = req.connection_info();
+            Ok(format!(
+                "{}://{}",
+                connection_info.scheme(),
+                connection_info.host()
+            ))
+        }
+        None => Err(AppError::EmailConfigError("public_url".to_owned())),
+    }
+}
+
+async fn send_user_action_mail(
+    user: &User,
+    action: UserAction,
+    public_url: &str,
+) -> Result<(), AppError> {
+    let token = common::generate_long_random_string();
+    datastore::insert_user_action_token(&token, &user.get_user_id(), action).await?;
+
+    let link = format!(
+        "{}/setpassword?user_id={}&token={}",
+        public_url,
+        common::percent_encode(&user.get_user_id()),
+        token
+    );
+    let (subject, body) = match action {
+        UserAction::Invitation => (
+            "Your invitation to the Server-Manager",
+            format!(
+                "Hello {},\n\nan account with the user id '{}' was created for you. Please set your password within {} hours with the following link:\n\n{}\n\nRegards,\nyour Server-Manager",
+                user.get_full_name(),
+                user.get_user_id(),
+                action.get_validity().num_hours(),
+                link
+            ),
+        ),
+        UserAction::PasswordReset => (
+            "Reset of your password for the Server-Manager",
+            format!(
+                "Hello {},\n\na reset of the password of your user id '{}' was requested. You can set a new password within {} minutes with the following link:\n\n{}\n\nIf you didn't request the reset, you can ignore this mail.\n\nRegards,\nyour Server-Manager",
+                user.get_full_name(),
+                user.get_user_id(),
+                action.get_validity().num_minutes(),
+                link
+            ),
+        ),
+    };
+
+    let from_address = datastore::get_config()?.get_string("email_from")?;
+    common::send_email(&from_address, &user.get_email(), subject, &body).await?;
+
+    Ok(())
+}
+
 #[delete("/users/{user_id}")]
-pub async fn delete_user(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+pub async fn delete_user(
+    user: web::ReqData<User>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     let user_id = path.into_inner();
 
+    if datastore::get_user(&user_id).await?.get_role() == Role::Admin {
+        check_other_admin_exists(&user_id).await?;
+    }
+
     datastore::delete_user(&user_id).await?;
+    datastore::delete_api_tokens_of_user(&user_id).await?;
+    datastore::delete_user_action_tokens_of_user(&user_id).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[get("/users/{user_id}/notificationchannels")]
+pub async fn get_user_notification_channels(
+    current_user: web::ReqData<User>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    current_user.check_self_or_admin(&user_id)?;
+
+    let user = datastore::get_user(&user_id).await?;
+    let crypto_key = datastore::get_crypto_key()?;
+
+    let channels = user
+        .get_notification_channels()
+        .iter()
+        .map(|channel| channel.decrypt_secrets(&crypto_key))
+        .collect::<Result<Vec<NotificationChannel>, AppError>>()?;
+
+    Ok(HttpResponse::Ok().json(channels))
+}
+
+#[put("/users/{user_id}/notificationchannels")]
+pub async fn put_user_notification_channels(
+    current_user: web::ReqData<User>,
+    path: web::Path<String>,
+    query: web::Json<Vec<NotificationChannel>>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    current_user.check_self_or_admin(&user_id)?;
+
+    let channels = query.into_inner();
+    check_notification_channel_urls(&current_user, &channels)?;
+
+    let crypto_key = datastore::get_crypto_key()?;
+    let encrypted_channels = channels
+        .iter()
+        .map(|channel| channel.encrypt_secrets(&crypto_key))
+        .collect::<Result<Vec<NotificationChannel>, AppError>>()?;
+
+    let mut user = datastore::get_user(&user_id).await?;
+
+    user.set_notification_channels(encrypted_channels);
+    datastore::update_user(&user).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[post("/users/{user_id}/notificationchannels/{channel_id}/test")]
+pub async fn post_user_notification_channel_test(
+    current_user: web::ReqData<User>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, channel_id) = path.into_inner();
+    current_user.check_self_or_admin(&user_id)?;
+    let user = datastore::get_user(&user_id).await?;
+    let tested_channels: Vec<NotificationChannel> = user
+        .get_notification_channels()
+        .into_iter()
+        .filter(|channel| channel.id == channel_id)
+        .collect();
+    check_notification_channel_urls(&current_user, &tested_channels)?;
+
+    plugin_execution::send_test_notification(&user, &channel_id).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// channels with an url let the server send requests to any host - only operators and admins can configure them
+fn check_notification_channel_urls(
+    current_user: &User,
+    channels: &[NotificationChannel],
+) -> Result<(), AppError> {
+    if channels.iter().any(|channel| channel.get_url().is_some()) {
+        current_user.check_role(Role::Operator)?;
+    }
+    Ok(())
+}
+
+#[get("/users/{user_id}/apitokens")]
+pub async fn get_user_api_tokens(
+    current_user: web::ReqData<User>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    current_user.check_self_or_admin(&user_id)?;
+
+    Ok(HttpResponse::Ok().json(datastore::get_api_tokens_of_user(&user_id).await?))
+}
+
+/// Creates a personal API token - the token is only part of this response and cannot be read again
+#[post("/users/{user_id}/apitokens")]
+pub async fn post_user_api_token(
+    current_user: web::ReqData<User>,
+    path: web::Path<String>,
+    query: web::Json<ApiTokenCreation>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    current_user.check_self_or_admin(&user_id)?;
+
+    let creation = query.into_inner();
+    // prevents that a token with a limited scope creates a token with more rights
+    current_user.check_role(creation.scope.get_role())?;
+
+    if creation.name.trim().is_empty() {
+        return Err(AppError::MissingArgument("name".to_owned()));
+    }
+    if creation
+        .expires
+        .is_some_and(|expires| expires <= Utc::now())
+    {
+        return Err(AppError::InvalidArgument(
+            "expires".to_owned(),
+            creation.expires.map(|expires| expires.to_rfc3339()),
+        ));
+    }
+    // also fails if the user doesn't exist
+    datastore::get_user(&user_id).await?;
+
+    let token = common::generate_long_random_string();
+    let api_token = ApiToken::new(
+        common::generate_short_random_string(),
+        user_id,
+        creation.name,
+        creation.scope,
+        common::hash_token(&token),
+        creation.expires,
+    );
+    datastore::insert_api_token(&api_token).await?;
+
+    Ok(HttpResponse::Ok().json(CreatedApiToken {
+        api_token: api_token.copy_no_hash(),
+        token,
+    }))
+}
+
+#[delete("/users/{user_id}/apitokens/{id}")]
+pub async fn delete_user_api_token(
+    current_user: web::ReqData<User>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, id) = path.into_inner();
+    current_user.check_self_or_admin(&user_id)?;
+
+    datastore::delete_api_token(&user_id, &id).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Starts the enrollment of the two-factor authentication - it is only active after the first code was verified
+#[post("/users/{user_id}/twofactor")]
+pub async fn post_user_two_factor(
+    current_user: web::ReqData<User>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    check_self(&current_user, &user_id)?;
+
+    let mut user = datastore::get_user(&user_id).await?;
+    if user.is_two_factor_enabled() {
+        return Err(AppError::Forbidden(
+            "two-factor authentication is already enabled".to_owned(),
+        ));
+    }
+
+    let secret = common::generate_totp_secret();
+    user.set_two_factor(TwoFactor::new(&secret, &datastore::get_crypto_key()?)?);
+    datastore::update_user(&user).await?;
+
+    Ok(HttpResponse::Ok().json(TwoFactorEnrollment {
+        otpauth_uri: common::make_otpauth_uri(&secret, &user_id),
+        secret,
+    }))
+}
+
+/// Finishes the enrollment with the first code of the authenticator app and returns the recovery codes
+#[post("/users/{user_id}/twofactor/verify")]
+pub async fn post_user_two_factor_verify(
+    current_user: web::ReqData<User>,
+    path: web::Path<String>,
+    query: web::Json<TwoFactorCode>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    check_self(&current_user, &user_id)?;
+
+    let mut attempts = common::lock_login_attempts(&user_id).await;
+    attempts.check_not_locked(Utc::now())?;
+
+    let mut user = datastore::get_user(&user_id).await?;
+    let mut two_factor = user.get_two_factor();
+    if two_factor.enabled || !two_factor.is_enrollment_started() {
+        return Err(AppError::Forbidden(
+            "there is no open enrollment of a two-factor authentication".to_owned(),
+        ));
+    }
+
+    let valid = two_factor.verify(
+        &query.code,
+        &datastore::get_crypto_key()?,
+        Utc::now().timestamp() as u64,
+    )?;
+    if !valid {
+        attempts.record_failure(Utc::now());
+        return Err(AppError::InvalidTwoFactorCode);
+    }
+    attempts.reset();
+
+    let recovery_codes = two_factor.enable();
+    user.set_two_factor(two_factor);
+    datastore::update_user(&user).await?;
+
+    Ok(HttpResponse::Ok().json(recovery_codes))
+}
+
+/// Disables the two-factor authentication - admins can reset it for users that lost their device.
+/// Users that disable their own two-factor authentication need a current code, so that a stolen session is not enough
+#[delete("/users/{user_id}/twofactor")]
+pub async fn delete_user_two_factor(
+    current_user: web::ReqData<User>,
+    req: HttpRequest,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    current_user.check_self_or_admin(&user_id)?;
+
+    let mut attempts = common::lock_login_attempts(&user_id).await;
+    let mut user = datastore::get_user(&user_id).await?;
+    if current_user.get_user_id() == user_id && user.is_two_factor_enabled() {
+        let code = get_two_factor_code(&req).ok_or(AppError::TwoFactorRequired)?;
+        check_two_factor_code(&mut user, code, &mut attempts).await?;
+    }
+
+    user.set_two_factor(TwoFactor::default());
+    datastore::update_user(&user).await?;
 
     Ok(HttpResponse::Ok().finish())
 }
 
+fn check_self(current_user: &User, user_id: &str) -> Result<(), AppError> {
+    if current_user.get_user_id() != user_id {
+        return Err(AppError::Forbidden(
+            "users can only set up their own two-factor authentication".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
 #[put("/user/{user_id}/changepassword")]
 pub async fn put_user_changepassword(
+    current_user: web::ReqData<User>,
     req: HttpRequest,
     query: web::Json<PasswordChange>,
 ) -> Result<HttpResponse, AppError> {
+    if current_user.get_user_id() != query.user_id {
+        return Err(AppError::Forbidden(
+            "users can only change their own password".to_owned(),
+        ));
+    }
+
     let headers = req.headers();
     let custom_header = headers
         .get(HeaderName::from_static("x-custom"))
@@ -505,29 +1075,91 @@ pub async fn authenticate(session: Session, req: HttpRequest) -> Result<HttpResp
 
     let decrypted = common::aes_decrypt(&auth_values.1, secret.as_str())?;
 
-    let user = datastore::get_user(user_id.as_str()).await?;
+    // held until the login is finished, so that parallel logins of the user cannot use the same TOTP code
+    let mut attempts = common::lock_login_attempts(user_id.as_str()).await;
+    attempts.check_not_locked(Utc::now())?;
+
+    let mut user = datastore::get_user(user_id.as_str()).await?;
 
     let password_check_result = user.check_password(&decrypted)?;
 
-    if password_check_result {
-        let client_key = ClientKey::new().register_for_session(session)?;
+    if !password_check_result {
+        attempts.record_failure(Utc::now());
+        return Err(AppError::InvalidPassword);
+    }
 
-        let token = common::generate_long_random_string();
+    let mut two_factor_setup_required = false;
+    if user.is_two_factor_enabled() {
+        let code = get_two_factor_code(&req).ok_or(AppError::TwoFactorRequired)?;
 
-        datastore::insert_token(&token)?;
+        check_two_factor_code(&mut user, code, &mut attempts).await?;
+    } else if user.get_role() == Role::Admin && is_two_factor_required_for_admins() {
+        two_factor_setup_required = true;
+    }
 
-        Ok(HttpResponse::Ok().json(UserToken {
-            user_id,
-            token,
-            client_key: client_key.key,
-        }))
-    } else {
-        Err(AppError::InvalidPassword)
+    let client_key = ClientKey::new().register_for_session(session)?;
+
+    attempts.reset();
+
+    let token = common::generate_long_random_string();
+
+    // an admin without two-factor authentication can only view until it is set up
+    let max_role = two_factor_setup_required.then_some(Role::Viewer);
+    datastore::insert_token(&token, &user_id, max_role).await?;
+
+    Ok(HttpResponse::Ok().json(UserToken {
+        user_id,
+        token,
+        client_key: client_key.key,
+        two_factor_setup_required,
+    }))
+}
+
+fn is_two_factor_required_for_admins() -> bool {
+    datastore::get_config()
+        .ok()
+        .and_then(|config| config.get_bool("totp_required_for_admins").ok())
+        .unwrap_or(false)
+}
+
+/// the TOTP or recovery code of the request
+fn get_two_factor_code(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(HeaderName::from_static("x-totp"))
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Verifies a TOTP or recovery code and persists the last used step / the used recovery code.
+/// The caller holds the login attempts of the user while the user is read and updated, so that a code is only accepted once
+async fn check_two_factor_code(
+    user: &mut User,
+    code: &str,
+    attempts: &mut LoginAttempts,
+) -> Result<(), AppError> {
+    attempts.check_not_locked(Utc::now())?;
+
+    let mut two_factor = user.get_two_factor();
+    let valid = two_factor.verify(
+        code,
+        &datastore::get_crypto_key()?,
+        Utc::now().timestamp() as u64,
+    )?;
+
+    if !valid {
+        attempts.record_failure(Utc::now());
+        return Err(AppError::InvalidTwoFactorCode);
     }
+
+    user.set_two_factor(two_factor);
+    datastore::update_user(user).await?;
+    attempts.reset();
+
+    Ok(())
 }
 
 #[get("monitoring/ids")]
 async fn get_monitoring_ids(
+    user: web::ReqData<User>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> Result<HttpResponse, AppError> {
     let ipaddress_param = query
@@ -535,6 +1167,7 @@ async fn get_monitoring_ids(
         .ok_or(AppError::MissingURLParameter("ipaddress".to_owned()))?;
 
     let ipaddress = ipaddress_param.parse::<IpAddr>()?;
+    user.check_server_access(&ipaddress)?;
     let server = datastore::get_server(&ipaddress).await?;
 
     let mut names: Vec<String> = Vec::new();
@@ -559,6 +1192,7 @@ async fn get_monitoring_ids(
 
 #[get("monitoring/data")]
 async fn get_monitoring_data(
+    user: web::ReqData<User>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> Result<HttpResponse, AppError> {
     //nm=true
@@ -573,23 +1207,327 @@ async fn get_monitoring_data(
         .ok_or(AppError::MissingURLParameter("series_id".to_owned()))?;
 
     let ipaddress = ipaddress_param.parse::<IpAddr>()?;
+    user.check_server_access(&ipaddress)?;
+
+    let parse_timestamp = |name: &str| -> Result<Option<DateTime<Utc>>, AppError> {
+        query
+            .get(name)
+            .map(|value| {
+                DateTime::parse_from_rfc3339(value)
+                    .map(|timestamp| timestamp.with_timezone(&Utc))
+                    .map_err(|_| AppError::InvalidArgument(name.to_owned(), Some(value.clone())))
+            })
+            .transpose()
+    };
 
-    let response = plugin_execution::get_monitoring_data(series_id, ipaddress).await?;
+    let range = MonitoringRange::new(
+        parse_timestamp("from")?,
+        parse_timestamp("to")?,
+        query
+            .get("bucket_seconds")
+            .map(|bucket_seconds| bucket_seconds.parse())
+            .transpose()?,
+        match query.get("aggregation") {
+            Some(aggregation) => aggregation.parse()?,
+            None => Aggregation::default(),
+        },
+    )?;
+
+    let response = plugin_execution::get_monitoring_data(series_id, ipaddress, range).await?;
 
     Ok(HttpResponse::Ok().json(response))
 }
 
+/// Prometheus endpoint - not secured by the tokens of the UI. It is only available if METRICS_TOKEN is set,
+/// which the scraper has to send as bearer token
+#[get("/metrics")]
+pub async fn get_metrics(req: HttpRequest) -> Result<HttpResponse, AppError> {
+    let metrics_token = datastore::get_config()?
+        .get_string("metrics_token")
+        .unwrap_or_default();
+
+    if metrics_token.is_empty() {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+
+    let expected = format!("Bearer {}", metrics_token);
+
+    let authorized = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| common::is_same_token(value, &expected));
+
+    if !authorized {
+        return Err(AppError::UnAuthorized);
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(other_functions::metrics::render_metrics().await?))
+}
+
 #[get("notifications")]
 async fn get_notifications(
+    user: web::ReqData<User>,
     _query: web::Query<std::collections::HashMap<String, String>>,
 ) -> Result<HttpResponse, AppError> {
-    let notifications: Vec<Notifications> = datastore::get_all_notifications().await?;
+    let notifications: Vec<Notifications> = datastore::get_all_notifications()
+        .await?
+        .into_iter()
+        .filter(|notifications| user.can_access_server_str(&notifications.ipaddress))
+        .collect();
 
     Ok(HttpResponse::Ok().json(notifications))
 }
 
+#[get("notifications/history")]
+async fn get_notifications_history(
+    user: web::ReqData<User>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    match query.get("ipaddress") {
+        Some(ipaddress) => user.check_server_access(&ipaddress.parse()?)?,
+        // the history is paged in the database, so it cannot be filtered afterwards
+        None if user.is_restricted_to_servers() => {
+            return Err(AppError::Forbidden(
+                "the history can only be read per server".to_owned(),
+            ))
+        }
+        None => {}
+    }
+
+    let page: u32 = match query.get("page") {
+        Some(page) => page.parse()?,
+        None => 0,
+    };
+    let page_size: u32 = match query.get("page_size") {
+        Some(page_size) => page_size.parse()?,
+        None => DEFAULT_PAGE_SIZE,
+    };
+
+    let history = datastore::get_notification_history(
+        query.get("ipaddress").map(|ipaddress| ipaddress.as_str()),
+        page,
+        page_size,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(history))
+}
+
+#[post("notifications/{ipaddress}/{id}/acknowledge")]
+async fn post_notification_acknowledge(
+    user: web::ReqData<User>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (ipaddress, id) = path.into_inner();
+    user.check_role(Role::Operator)?;
+    user.check_server_access(&ipaddress.parse()?)?;
+
+    plugin_execution::acknowledge_notification(&ipaddress, &id).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[post("notifications/{ipaddress}/{id}/resolve")]
+async fn post_notification_resolve(
+    user: web::ReqData<User>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (ipaddress, id) = path.into_inner();
+    user.check_role(Role::Operator)?;
+    user.check_server_access(&ipaddress.parse()?)?;
+
+    plugin_execution::resolve_notification(&ipaddress, &id).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[post("notifications/{ipaddress}/{id}/silence")]
+async fn post_notification_silence(
+    user: web::ReqData<User>,
+    path: web::Path<(String, String)>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    let (ipaddress, id) = path.into_inner();
+    user.check_role(Role::Operator)?;
+    user.check_server_access(&ipaddress.parse()?)?;
+    let hours: u32 = query
+        .get("hours")
+        .ok_or(AppError::MissingURLParameter("hours".to_owned()))?
+        .parse()?;
+
+    plugin_execution::silence_notification(&ipaddress, &id, hours).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[get("reports/availability")]
+async fn get_availability_reports(
+    user: web::ReqData<User>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    let period: ReportPeriod = match query.get("period") {
+        Some(period) => period.parse()?,
+        None => ReportPeriod::default(),
+    };
+    let series = query
+        .get("series")
+        .map(|series| series.as_str())
+        .unwrap_or(plugin_execution::SERVER_STATUS_SERIES);
+
+    let reports: Vec<AvailabilityReport> = plugin_execution::get_availability_reports(
+        series,
+        query.get("ipaddress").map(|ipaddress| ipaddress.as_str()),
+        period,
+    )
+    .await?
+    .into_iter()
+    .filter(|report| user.can_access_server_str(&report.ipaddress))
+    .collect();
+
+    Ok(HttpResponse::Ok().json(reports))
+}
+
+#[get("maintenancewindows")]
+async fn get_maintenance_windows(user: web::ReqData<User>) -> Result<HttpResponse, AppError> {
+    let windows: Vec<MaintenanceWindow> = datastore::get_all_maintenance_windows()
+        .await?
+        .into_iter()
+        .filter(|window| user.can_access_server_str(&window.ipaddress))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(windows))
+}
+
+#[put("maintenancewindows/{id}")]
+async fn put_maintenance_window(
+    user: web::ReqData<User>,
+    path: web::Path<String>,
+    query: web::Json<MaintenanceWindow>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Operator)?;
+
+    let window = MaintenanceWindow {
+        id: path.into_inner(),
+        ..query.into_inner()
+    };
+    window.validate()?;
+    check_maintenance_window_access(&user, &window.ipaddress)?;
+    check_existing_maintenance_window_access(&user, &window.id).await?;
+
+    datastore::insert_or_update_maintenance_window(&window).await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[delete("maintenancewindows/{id}")]
+async fn delete_maintenance_window(
+    user: web::ReqData<User>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Operator)?;
+
+    let id = path.into_inner();
+    check_existing_maintenance_window_access(&user, &id).await?;
+
+    datastore::delete_maintenance_window(&id).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+fn check_maintenance_window_access(user: &User, ipaddress: &str) -> Result<(), AppError> {
+    if user.can_access_server_str(ipaddress) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(format!(
+            "no access to the server {}",
+            ipaddress
+        )))
+    }
+}
+
+/// a window of a server the user cannot access must not be replaced or deleted
+async fn check_existing_maintenance_window_access(user: &User, id: &str) -> Result<(), AppError> {
+    match datastore::get_all_maintenance_windows()
+        .await?
+        .into_iter()
+        .find(|window| window.id == id)
+    {
+        Some(existing) => check_maintenance_window_access(user, &existing.ipaddress),
+        None => Ok(()),
+    }
+}
+
+#[get("alertrules")]
+async fn get_alert_rules(user: web::ReqData<User>) -> Result<HttpResponse, AppError> {
+    let rules: Vec<AlertRule> = datastore::get_all_alert_rules()
+        .await?
+        .into_iter()
+        .filter(|rule| can_access_alert_rule(&user, rule))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(rules))
+}
+
+#[get("alertrules/{id}")]
+async fn get_alert_rule(
+    user: web::ReqData<User>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let rule = datastore::get_alert_rule(&path.into_inner()).await?;
+
+    if !can_access_alert_rule(&user, &rule) {
+        return Err(AppError::Forbidden(format!(
+            "no access to the alert rule {}",
+            rule.id
+        )));
+    }
+
+    Ok(HttpResponse::Ok().json(rule))
+}
+
+/// rules without a server apply to all servers and are visible to every user
+fn can_access_alert_rule(user: &User, rule: &AlertRule) -> bool {
+    rule.ipaddress
+        .as_deref()
+        .is_none_or(|ipaddress| user.can_access_server_str(ipaddress))
+}
+
+#[put("alertrules/{id}")]
+async fn put_alert_rule(
+    user: web::ReqData<User>,
+    path: web::Path<String>,
+    query: web::Json<AlertRule>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
+    let rule = AlertRule {
+        id: path.into_inner(),
+        ..query.into_inner()
+    };
+    rule.validate()?;
+
+    datastore::insert_or_update_alert_rule(&rule).await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[delete("alertrules/{id}")]
+async fn delete_alert_rule(
+    user: web::ReqData<User>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
+    plugin_execution::delete_alert_rule(&path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
 #[get("configuration")]
-async fn get_config(req: HttpRequest) -> Result<HttpResponse, AppError> {
+async fn get_config(user: web::ReqData<User>, req: HttpRequest) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     let decrypted_password = get_decrypted_password_from_header(req).await?;
 
     Ok(HttpResponse::Ok().json(datastore::export_config(decrypted_password.as_str()).await?))
@@ -597,9 +1535,12 @@ async fn get_config(req: HttpRequest) -> Result<HttpResponse, AppError> {
 
 #[post("configuration")]
 async fn post_config(
+    user: web::ReqData<User>,
     query: web::Json<Configuration>,
     req: HttpRequest,
 ) -> Result<HttpResponse, AppError> {
+    user.check_role(Role::Admin)?;
+
     let decrypted_password = get_decrypted_password_from_header(req).await?;
 
     let config = query.into_inner();
@@ -617,12 +1558,19 @@ pub async fn get_events_from_stream(
     match params.get("token") {
         Some(token_param) => {
             let token = Token::from(token_param.to_owned());
-            if token.is_valid(true).await {
+            if let Some(user) = get_user_of_event_token(&token).await {
                 match event_handling::subscribe().await {
                     Ok(event_subscriber) => {
                         let stream = tokio_stream::wrappers::BroadcastStream::new(event_subscriber);
 
                         let mapped = stream
+                            .filter(move |event_result| {
+                                let visible = match event_result {
+                                    Ok(event) => is_event_visible(&user, event),
+                                    Err(_) => true,
+                                };
+                                std::future::ready(visible)
+                            })
                             .filter_map(map_events_to_sse_data_events)
                             .map(Ok::<_, Infallible>);
 
@@ -638,7 +1586,7 @@ pub async fn get_events_from_stream(
                     }
                 }
             } else {
-                log::error!("Token {:?} is invalid", token);
+                log::error!("Event token is invalid");
                 HttpResponse::Unauthorized().finish().respond_to(&req)
             }
         }
@@ -647,12 +1595,37 @@ pub async fn get_events_from_stream(
 }
 
 #[get("eventservicetoken")]
-pub async fn get_eventservicetoken() -> Result<HttpResponse, AppError> {
-    let token = common::Token::generate().await?;
-    log::info!("Sending event service token {:?}", token);
+pub async fn get_eventservicetoken(user: web::ReqData<User>) -> Result<HttpResponse, AppError> {
+    let token = common::Token::generate(&user.get_user_id()).await?;
     Ok(HttpResponse::Ok().json(token))
 }
 
+/// the user of the token has to exist still
+async fn get_user_of_event_token(token: &Token) -> Option<User> {
+    match token.take_user_id().await {
+        Ok(Some(user_id)) => datastore::get_user(&user_id)
+            .await
+            .ok()
+            .map(|user| user.copy_no_passwd()),
+        Ok(None) => None,
+        Err(err) => {
+            log::error!("Could not validate event token: {}", err);
+            None
+        }
+    }
+}
+
+/// users that are restricted to some servers only get the events of these servers and only admins get the events of other users
+fn is_event_visible(user: &User, event: &Event) -> bool {
+    if let Some(ipaddress) = event.get_ipaddress() {
+        return user.can_access_server(&ipaddress);
+    }
+    if let Some(user_id) = event.get_user_id() {
+        return user.check_self_or_admin(user_id).is_ok();
+    }
+    true
+}
+
 async fn map_events_to_sse_data_events(
     event_result: Result<Event, BroadcastStreamRecvError>,
 ) -> Option<sse::Event> {
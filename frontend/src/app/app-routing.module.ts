@@ -4,10 +4,12 @@ import { RegisterGuard } from './auth/register.guard';
 import { LoginComponent } from './login/login.component';
 import { MainComponent } from './main/main.component';
 import { RegisterComponent } from './register/register.component';
+import { SetPasswordComponent } from './setpassword/setpassword.component';
 
 export const APP_ROUTES: Routes = [
   { path: '', redirectTo: '/login', pathMatch: 'full' },
   { path: 'login', component: LoginComponent, canActivate: [RegisterGuard] },
   { path: 'register', component: RegisterComponent },
+  { path: 'setpassword', component: SetPasswordComponent },
   { path: 'home', component: MainComponent, canActivate: [AuthGuard] },
 ];
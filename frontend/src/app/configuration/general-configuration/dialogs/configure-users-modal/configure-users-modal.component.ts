@@ -3,7 +3,7 @@ import { FormControl, Validators, FormsModule, ReactiveFormsModule } from '@angu
 import { MatDialog } from '@angular/material/dialog';
 import { Store } from '@ngrx/store';
 import { Observable, of } from 'rxjs';
-import { User } from 'src/app/services/users/types';
+import { Permissions, ROLES, Role, User } from 'src/app/services/users/types';
 import { UserService } from 'src/app/services/users/users.service';
 import { ConfirmDialogComponent } from 'src/app/ui/confirm-dialog/confirm-dialog.component';
 import { MessageDialogComponent } from 'src/app/ui/message_dialog/message-dialog.component';
@@ -11,10 +11,11 @@ import { selectAllUsers } from 'src/app/state/user/user.selectors';
 import { SubscriptionHandler } from 'src/app/shared/subscriptionHandler';
 import { MatTableModule } from '@angular/material/table';
 import { MatButtonModule } from '@angular/material/button';
-import { NgIf } from '@angular/common';
+import { NgFor, NgIf } from '@angular/common';
 import { MatInputModule } from '@angular/material/input';
 import { MatFormFieldModule } from '@angular/material/form-field';
 import { FlexModule } from '@angular/flex-layout/flex';
+import { MatSelectModule } from '@angular/material/select';
 
 @Component({
     selector: 'app-configure-users-modal',
@@ -28,8 +29,10 @@ import { FlexModule } from '@angular/flex-layout/flex';
         FormsModule,
         ReactiveFormsModule,
         NgIf,
+        NgFor,
         MatButtonModule,
         MatTableModule,
+        MatSelectModule,
     ],
 })
 export class ConfigureUsersModalComponent implements OnInit, OnDestroy {
@@ -39,6 +42,7 @@ export class ConfigureUsersModalComponent implements OnInit, OnDestroy {
   private subscriptionHandler = new SubscriptionHandler(this);
 
   buttonTextAdd = 'Add User';
+  buttonTextInvite = 'Invite User';
   buttonTextDelete = 'Delete User';
 
   userIdLabel = 'User Id';
@@ -62,7 +66,26 @@ export class ConfigureUsersModalComponent implements OnInit, OnDestroy {
 
   email = new FormControl('', [Validators.required, Validators.email]);
 
-  displayedColumns = ['delete', 'user_id', 'full_name'];
+  roleLabel = 'Role';
+  roleHint =
+    'Viewers can only look at the servers, operators can also execute actions';
+  roles = ROLES;
+
+  role = new FormControl<Role>('viewer', [Validators.required]);
+
+  serversLabel = 'Servers';
+  serversHint =
+    'Comma separated ip addresses the user can access - empty for all servers';
+
+  servers = new FormControl('');
+
+  pluginsLabel = 'Plugins';
+  pluginsHint =
+    'Comma separated ids of the plugins whose actions the user can execute - empty for all plugins';
+
+  plugins = new FormControl('');
+
+  displayedColumns = ['delete', 'user_id', 'full_name', 'role'];
 
   users$: Observable<User[]> = of([]);
 
@@ -80,16 +103,10 @@ export class ConfigureUsersModalComponent implements OnInit, OnDestroy {
   }
 
   onClickSaveUser = () => {
-    if (
-      this.userId.value !== null &&
-      this.fullName.value !== null &&
-      this.email.value !== null
-    ) {
+    const user = this.makeUser();
+    if (user) {
       this.subscriptionHandler.subscription = this.userService
-        .saveUser(
-          new User(this.userId.value, this.fullName.value, this.email.value),
-          false
-        )
+        .saveUser(user, false)
         .subscribe((response) => {
           if (response) {
             if (response.password !== null) {
@@ -112,6 +129,55 @@ export class ConfigureUsersModalComponent implements OnInit, OnDestroy {
     }
   };
 
+  // the user gets a link by mail and sets the password without an initial password
+  onClickInviteUser = () => {
+    const user = this.makeUser();
+    if (user) {
+      this.subscriptionHandler.subscription = this.userService
+        .inviteUser(user)
+        .subscribe(() => {
+          this.dialog.open(MessageDialogComponent, {
+            data: {
+              title: 'Invitation',
+              message:
+                'An invitation was sent to ' + user.email + ' for user ' + user.user_id,
+            },
+          });
+        });
+    }
+  };
+
+  private makeUser = (): User | undefined => {
+    if (
+      this.userId.value === null ||
+      this.fullName.value === null ||
+      this.email.value === null
+    ) {
+      return undefined;
+    }
+    return new User(
+      this.userId.value,
+      this.fullName.value,
+      this.email.value,
+      this.role.value ?? 'viewer',
+      new Permissions(
+        this.splitList(this.servers.value),
+        this.splitList(this.plugins.value)
+      )
+    );
+  };
+
+  onChangeRole = (user: User, role: Role) => {
+    this.userService.updateUser({ ...user, role });
+  };
+
+  private splitList = (value: string | null): string[] => {
+    return (value ?? '')
+      .split(',')
+      .map((entry) => entry.trim())
+      .filter((entry) => entry.length > 0);
+  };
+
   onClickDeleteUsers = () => {
     this.subscriptionHandler.subscription = this.users$.subscribe((users) => {
       const usersToDelete = users.filter((user) =>
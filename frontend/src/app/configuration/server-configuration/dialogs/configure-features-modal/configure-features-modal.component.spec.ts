@@ -16,6 +16,8 @@ import { MockStore, provideMockStore } from '@ngrx/store/testing';
 import { State } from 'src/app/state';
 import { Store } from '@ngrx/store';
 import { ToastrModule } from 'ngx-toastr';
+import { Feature, Param, Server } from 'src/app/services/servers/types';
+import { ParamDefinition } from 'src/app/services/plugins/types';
 
 describe('ConfigureFeaturesModalComponent', () => {
   let component: ConfigureFeaturesModalComponent;
@@ -77,4 +79,40 @@ describe('ConfigureFeaturesModalComponent', () => {
   it('should create', () => {
     expect(component).toBeTruthy();
   });
+
+  it('should keep the pinned fingerprint when the feature is saved', () => {
+    const feature = new Feature(
+      'proxmox',
+      'Proxmox',
+      [
+        new Param('port', '8006'),
+        new Param('certificate_fingerprint', 'AB:CD:EF'),
+      ],
+      []
+    );
+    component.selectedServerFullData = new Server('192.168.178.2', 'pve', '', [
+      feature,
+    ]);
+    component.selectedFeature = feature;
+    component.paramsFromPlugin = [
+      new ParamDefinition('port', 'number', '8006'),
+    ];
+    component.paramsFromFeature = feature.params;
+    component.createInputControls();
+    component.setInitialValuesOnInputControls();
+    component.form.controls['param.port'].setValue('8007');
+
+    const dispatchSpy = spyOn(mockStore, 'dispatch');
+    component.onClickSaveFeatureSettings();
+
+    expect(dispatchSpy).toHaveBeenCalled();
+    expect(feature.params).toEqual([
+      new Param('port', '8007'),
+      new Param('certificate_fingerprint', 'AB:CD:EF'),
+    ]);
+
+    // only removed by the explicit reset
+    component.onClickResetPinnedFingerprint('certificate_fingerprint');
+    expect(feature.params).toEqual([new Param('port', '8007')]);
+  });
 });
@@ -25,6 +25,13 @@ import { MatSelectModule } from '@angular/material/select';
 import { MatFormFieldModule } from '@angular/material/form-field';
 import { FlexModule } from '@angular/flex-layout/flex';
 
+// fingerprints that the backend pins on first use (see certificate_pinning.rs) - they are no params of the plugin,
+// so they are kept when the feature is saved and only removed by an explicit reset
+export const PINNED_FINGERPRINT_PARAMS = [
+  'certificate_fingerprint',
+  'host_key_fingerprint',
+];
+
 @Component({
     selector: 'app-configure-features-modal',
     templateUrl: './configure-features-modal.component.html',
@@ -117,7 +124,7 @@ export class ConfigureFeaturesModalComponent implements OnInit {
   setInitialValuesOnInputControls = () => {
     this.paramsFromFeature.forEach((param) => {
       if (this.form) {
-        this.form.controls[`param.${param.name}`].setValue(param.value);
+        this.form.controls[`param.${param.name}`]?.setValue(param.value);
       }
     });
     this.credentialFromFeature.forEach((credential) => {
@@ -244,9 +251,27 @@ export class ConfigureFeaturesModalComponent implements OnInit {
         params.push(new Param(param.name, value));
       }
     });
+    this.getPinnedFingerprints()
+      .filter((pinned) => !params.some((param) => param.name === pinned.name))
+      .forEach((pinned) => params.push(new Param(pinned.name, pinned.value)));
     return params;
   };
 
+  getPinnedFingerprints = (): Param[] => {
+    return this.paramsFromFeature.filter(
+      (param) =>
+        PINNED_FINGERPRINT_PARAMS.includes(param.name) && param.value !== ''
+    );
+  };
+
+  // the next connection trusts the certificate it sees and pins it again
+  onClickResetPinnedFingerprint = (name: string) => {
+    this.paramsFromFeature = this.paramsFromFeature.filter(
+      (param) => param.name !== name
+    );
+    this.onClickSaveFeatureSettings();
+  };
+
   getValuesFromForm = (prefix: string): Map<string, string> => {
     const map = new Map();
 
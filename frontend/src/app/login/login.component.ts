@@ -50,9 +50,21 @@ export class LoginComponent implements OnDestroy {
     Validators.minLength(4),
   ]);
 
+  totpCodeLabel = 'Authentication Code';
+  totpCodePlaceholder = '';
+  totpCodeHint = 'Code of your authenticator app or one of your recovery codes';
+  totpCode: FormControl = new FormControl('');
+  totpRequired = false;
+
   buttonTextLogin = 'Login';
+  buttonTextForgotPassword = 'Forgot Password';
+  passwordResetMessage = '';
 
-  form = new FormGroup({ userId: this.userId, password: this.password });
+  form = new FormGroup({
+    userId: this.userId,
+    password: this.password,
+    totpCode: this.totpCode,
+  });
 
 
   ngOnDestroy(): void {
@@ -89,11 +101,27 @@ export class LoginComponent implements OnDestroy {
     return '';
   };
 
+  // sends a mail with a link to set a new password to the address of the user
+  onClickForgotPassword = () => {
+    this.subscriptionHandler.subscription = this.authService
+      .requestPasswordReset(this.userId.value)
+      .pipe(take(1))
+      .subscribe({
+        next: () => {
+          this.passwordResetMessage =
+            'If the user exists, a mail with a link to reset the password was sent';
+        },
+        error: () => {
+          this.passwordResetMessage = 'The password reset could not be requested';
+        },
+      });
+  };
+
   onClickLogin = () => {
     // even if no TLS/HTTPS is used, we don't want to transfer a cleartext password
     // so we use a encryption here and the server is then checking the password against the hash value on the server side
     this.subscriptionHandler.subscription = this.authService
-      .login(this.userId.value, this.password.value)
+      .login(this.userId.value, this.password.value, this.totpRequired ? this.totpCode.value : undefined)
       .pipe(take(1))
       .subscribe({
         next: (userToken) => {
@@ -103,10 +131,23 @@ export class LoginComponent implements OnDestroy {
             this.router.navigate(['/home']);
           }
         },
-        error: () => {
-          this.form.setErrors({
-            wrongLogin: 'User Id and/or password is incorrect',
-          });
+        error: (err) => {
+          const message: string = err?.error?.error ?? '';
+
+          if (message.includes('two-factor authentication is required')) {
+            this.totpRequired = true;
+            this.form.setErrors({
+              wrongLogin: 'Please enter the code of your authenticator app',
+            });
+          } else if (message.includes('two-factor authentication was invalid')) {
+            this.form.setErrors({
+              wrongLogin: 'The authentication code is incorrect',
+            });
+          } else {
+            this.form.setErrors({
+              wrongLogin: 'User Id and/or password is incorrect',
+            });
+          }
         },
       });
   };
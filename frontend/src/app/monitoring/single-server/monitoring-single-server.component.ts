@@ -10,6 +10,9 @@ import {
 import { NGXLogger } from 'ngx-logger';
 import { MonitoringService } from 'src/app/services/monitoring/monitoring.service';
 import {
+  Aggregation,
+  MonitoringRange,
+  RANGE_OPTIONS,
   SUB_IDENTIFIER,
   SUB_IDENTIFIER2,
   TimeSeriesIds,
@@ -21,6 +24,10 @@ import { sortByString } from 'src/app/shared/utils';
 import { ChartData, ChartDataList } from 'src/types/ChartData';
 import { ChartWrapperComponent } from '../../ui/chart-wrapper/chart-wrapper.component';
 import { NgIf, NgFor } from '@angular/common';
+import { FormsModule } from '@angular/forms';
+import { MatFormFieldModule } from '@angular/material/form-field';
+import { MatSelectModule } from '@angular/material/select';
+import { MatOptionModule } from '@angular/material/core';
 
 @Component({
     selector: 'app-monitoring-single-server',
@@ -30,6 +37,10 @@ import { NgIf, NgFor } from '@angular/common';
     imports: [
         NgIf,
         NgFor,
+        FormsModule,
+        MatFormFieldModule,
+        MatSelectModule,
+        MatOptionModule,
         ChartWrapperComponent,
     ],
 })
@@ -47,6 +58,11 @@ export class MonitoringSingleServerComponent
 
   chartTypes: Map<string, string> = new Map();
 
+  rangeOptions = RANGE_OPTIONS;
+  aggregations: Aggregation[] = ['last', 'avg', 'min', 'max', 'sum'];
+  selectedRangeHours = RANGE_OPTIONS[0].hours;
+  selectedAggregation: Aggregation = 'last';
+
   private subscriptionHandler = new SubscriptionHandler(this);
 
   ngOnInit(): void {
@@ -64,7 +80,7 @@ export class MonitoringSingleServerComponent
             if (this.server && this.seriesData) {
               for (const seriesId of this.seriesData.seriesIds) {
                 this.subscriptionHandler.subscription = this.monitoringService
-                  .loadMonitoringData(this.server, seriesId)
+                  .loadMonitoringData(this.server, seriesId, this.getRange())
                   .subscribe((data) => {
                     this.updateDataMap(data);
                   });
@@ -75,6 +91,22 @@ export class MonitoringSingleServerComponent
     }
   }
 
+  onChangeRange = () => {
+    this.loadData();
+  };
+
+  private getRange = (): MonitoringRange => {
+    const to = new Date();
+    const from = new Date(to.getTime() - this.selectedRangeHours * 3600 * 1000);
+
+    return new MonitoringRange(
+      from.toISOString(),
+      to.toISOString(),
+      undefined,
+      this.selectedAggregation
+    );
+  };
+
   ngOnDestroy(): void {
     this.subscriptionHandler.onDestroy(this.logger);
   }
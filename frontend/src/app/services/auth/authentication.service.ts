@@ -25,7 +25,7 @@ export class AuthenticationService {
     this.userToken$ = store.select(selectToken());
   }
 
-  login(userId: string, password: string): Observable<UserToken> {
+  login(userId: string, password: string, totpCode?: string): Observable<UserToken> {
     return this.encryptionService.requestOneTimeKey().pipe(
       take(1),
       mergeMap((otk) => {
@@ -36,11 +36,14 @@ export class AuthenticationService {
         );
         const base64_enc = btoa(`${userId}:${encrypted_password}`);
 
-        const headers = new HttpHeaders({
+        let headers = new HttpHeaders({
           'Content-Type': 'application/json',
           Authorization: `Basic ${base64_enc}`,
           'X-custom': `${otk.id}`,
         });
+        if (totpCode) {
+          headers = headers.set('X-totp', totpCode.trim());
+        }
 
         return this.http
           .post<UserToken>(`backend_nt/users/authenticate`, '', {
@@ -61,6 +64,45 @@ export class AuthenticationService {
     );
   }
 
+  // the response is the same for unknown users
+  requestPasswordReset = (userId: string): Observable<void> => {
+    return this.http.post<void>(
+      '/backend_nt/users/passwordreset',
+      JSON.stringify({ user_id: userId }),
+      {
+        headers: new HttpHeaders({ 'Content-Type': 'application/json' }),
+      }
+    );
+  };
+
+  // sets the password with the token of an invitation or password reset link
+  setPassword = (
+    userId: string,
+    token: string,
+    password: string
+  ): Observable<void> => {
+    return this.encryptionService.requestOneTimeKey().pipe(
+      take(1),
+      mergeMap((otk) => {
+        const secret = this.encryptionService.makeSecret(userId, otk.key);
+        const body = JSON.stringify({
+          user_id: userId,
+          token: token,
+          password: this.encryptionService.encrypt(password, secret),
+        });
+
+        const headers = new HttpHeaders({
+          'Content-Type': 'application/json',
+          'X-custom': `${otk.id}`,
+        });
+
+        return this.http.put<void>('/backend_nt/users/password', body, {
+          headers: headers,
+        });
+      })
+    );
+  };
+
   logout = () => {
     this.userToken$.pipe(take(1)).subscribe((token) => {
       if (token) {
@@ -16,6 +16,9 @@ import { selectAllTokens } from 'src/app/state/usertoken/usertoken.selectors';
 import { AuthenticationService } from '../auth/authentication.service';
 import { SystemInformation } from '../general/types';
 
+// the browser would reconnect with the same token, but it can only be used once
+const RECONNECT_DELAY_MS = 5000;
+
 @Injectable({
   providedIn: 'root',
 })
@@ -54,28 +57,7 @@ export class EventService {
       .pipe(tap((userTokens) => this.logger.debug('userTokens', userTokens)))
       .subscribe((userTokens) => {
         if (userTokens && userTokens.length > 0) {
-          this.authService
-            .getEventServiceToken()
-            .pipe(
-              tap((eventServiceToken) =>
-                this.logger.debug('eventServiceToken', eventServiceToken)
-              ),
-              take(1)
-            )
-            .subscribe((eventServiceToken) => {
-              const event_token = eventServiceToken.token;
-              this.source = new EventSource(
-                `/backend_nt/events?token=${event_token}`
-              );
-
-              this.subscribeToEvents();
-              this.subscribeForToasterMessages();
-
-              for (const eventHandler of this.eventHandlers) {
-                eventHandler.start();
-              }
-              this.logger.info('Event connection established');
-            });
+          this.connect(true);
         } else {
           for (const eventHandler of this.eventHandlers) {
             eventHandler.stop();
@@ -89,6 +71,36 @@ export class EventService {
       });
   }
 
+  // the token of the event stream can only be used once - a new one is requested for every connection
+  private connect = (initial: boolean) => {
+    this.authService
+      .getEventServiceToken()
+      .pipe(take(1))
+      .subscribe((eventServiceToken) => {
+        const event_token = eventServiceToken.token;
+        this.source = new EventSource(`/backend_nt/events?token=${event_token}`);
+
+        this.subscribeToEvents();
+
+        if (initial) {
+          this.subscribeForToasterMessages();
+
+          for (const eventHandler of this.eventHandlers) {
+            eventHandler.start();
+          }
+        }
+        this.logger.info('Event connection established');
+      });
+  };
+
+  private reconnect = () => {
+    this.userToken$.pipe(take(1)).subscribe((userTokens) => {
+      if (userTokens && userTokens.length > 0 && !this.source) {
+        this.connect(false);
+      }
+    });
+  };
+
   private subscribeToEvents = () => {
     if (this.source) {
       this.source.addEventListener('message', (message) => {
@@ -124,6 +136,10 @@ export class EventService {
 
       this.source.onerror = (e) => {
         this.errorService.newError(Source.EventService, undefined, e);
+
+        this.source?.close();
+        this.source = undefined;
+        setTimeout(() => this.reconnect(), RECONNECT_DELAY_MS);
       };
     }
   };
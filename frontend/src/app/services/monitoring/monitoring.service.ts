@@ -3,7 +3,7 @@ import { HttpClient, HttpParams } from '@angular/common/http';
 import { Observable, map, catchError, throwError } from 'rxjs';
 import { ErrorService, Source } from '../errors/error.service';
 import { Server } from '../servers/types';
-import { TimeSeriesIds, TimeSeriesResponse } from './types';
+import { MonitoringRange, TimeSeriesIds, TimeSeriesResponse } from './types';
 
 @Injectable({
   providedIn: 'root',
@@ -29,15 +29,24 @@ export class MonitoringService {
 
   loadMonitoringData = (
     server: Server,
-    series_id: string
+    series_id: string,
+    range?: MonitoringRange
   ): Observable<TimeSeriesResponse> => {
-    const options = server
-      ? {
-          params: new HttpParams()
-            .set('ipaddress', server.ipaddress)
-            .set('series_id', series_id),
-        }
-      : {};
+    let params = new HttpParams()
+      .set('ipaddress', server.ipaddress)
+      .set('series_id', series_id);
+
+    if (range) {
+      params = params
+        .set('from', range.from)
+        .set('to', range.to)
+        .set('aggregation', range.aggregation);
+
+      if (range.bucket_seconds) {
+        params = params.set('bucket_seconds', range.bucket_seconds);
+      }
+    }
+    const options = server ? { params } : {};
 
     return this.http
       .get<TimeSeriesResponse>('/backend/monitoring/data', options)
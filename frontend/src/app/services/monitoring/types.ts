@@ -31,10 +31,30 @@ export class TimeSeriesResponseMetaData {
     public name: string,
     public series_id: string,
     public series_type: 'category' | 'datetime',
-    public chart_type: 'bar' | 'line'
+    public chart_type: 'bar' | 'line',
+    public range?: MonitoringRange
   ) {}
 }
 
+export type Aggregation = 'avg' | 'min' | 'max' | 'last' | 'sum';
+
+export class MonitoringRange {
+  constructor(
+    public from: string,
+    public to: string,
+    public bucket_seconds?: number,
+    public aggregation: Aggregation = 'last'
+  ) {}
+}
+
+// ranges that can be selected for the charts - the bucket size is chosen by the backend
+export const RANGE_OPTIONS: { name: string; hours: number }[] = [
+  { name: '10 hours', hours: 10 },
+  { name: 'Day', hours: 24 },
+  { name: 'Week', hours: 24 * 7 },
+  { name: 'Month', hours: 24 * 30 },
+];
+
 export class TimeSeriesResponseData {
   constructor(
     public query: string,
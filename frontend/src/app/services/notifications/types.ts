@@ -1,5 +1,18 @@
 export class Notification {
-  constructor(public id: string, public name: string, public message: string, public notification_level: 'Info' | 'Warn' | 'Error' | 'Critical') {}
+  constructor(
+    public id: string,
+    public name: string,
+    public message: string,
+    public notification_level: 'Info' | 'Warn' | 'Error' | 'Critical',
+    public state: 'Open' | 'Acknowledged' | 'Resolved' = 'Open',
+    public first_seen?: string,
+    public last_seen?: string,
+    public acknowledged_at?: string,
+    public resolved_at?: string,
+    public plugin_id?: string,
+    public silenced_until?: string,
+    public delivered: boolean = true
+  ) {}
 }
 
 
@@ -1,8 +1,32 @@
+export type Role = 'admin' | 'operator' | 'viewer';
+
+export const ROLES: Role[] = ['admin', 'operator', 'viewer'];
+
+// empty lists mean that the user is not restricted
+export class Permissions {
+  constructor(
+    public servers: string[] = [],
+    public plugins: string[] = []
+  ) {}
+}
+
+// the secret and the recovery codes are never sent to the client
+export class TwoFactor {
+  constructor(public enabled = false) {}
+}
+
+export class TwoFactorEnrollment {
+  constructor(public secret: string, public otpauth_uri: string) {}
+}
+
 export class User {
   constructor(
     public user_id: string,
     public full_name: string,
-    public email: string
+    public email: string,
+    public role: Role = 'viewer',
+    public permissions: Permissions = new Permissions(),
+    public two_factor: TwoFactor = new TwoFactor()
   ) {}
 }
 
@@ -10,7 +34,8 @@ export class UserToken {
   constructor(
     public user_id: string,
     public token: string,
-    public client_key: string
+    public client_key: string,
+    public two_factor_setup_required = false
   ) {}
 }
 
@@ -1,6 +1,6 @@
 import { Injectable } from '@angular/core';
 import { HttpClient, HttpHeaders } from '@angular/common/http';
-import { User, UserInitialPassword } from './types';
+import { TwoFactorEnrollment, User, UserInitialPassword } from './types';
 import { ErrorService, Source } from '../errors/error.service';
 import { Observable, catchError, map, throwError, take } from 'rxjs';
 import { defaultHeadersForJSON } from '../common';
@@ -130,6 +130,38 @@ export class UserService {
       );
   };
 
+  // the server sends a mail with a link to set the password
+  inviteUser = (user: User): Observable<void> => {
+    return this.http
+      .post<void>('/backend/users/invitations', JSON.stringify(user), {
+        headers: defaultHeadersForJSON(),
+      })
+      .pipe(
+        catchError((err) => {
+          this.errorService.newError(Source.UserService, user.user_id, err);
+          return throwError(() => err);
+        })
+      );
+  };
+
+  updateUser = (user: User) => {
+    const subscription = this.http
+      .put('/backend/users/' + user.user_id, JSON.stringify(user), {
+        headers: defaultHeadersForJSON(),
+      })
+      .subscribe({
+        next: () => {
+          this.store.dispatch(upsertOne({ user }));
+        },
+        error: (err) => {
+          this.errorService.newError(Source.UserService, user.user_id, err);
+        },
+        complete: () => {
+          subscription.unsubscribe();
+        },
+      });
+  };
+
   deleteUsers = (usersToDelete: User[]) => {
     for (const [, user] of usersToDelete.entries()) {
       const subscription = this.http
@@ -181,4 +213,55 @@ export class UserService {
         },
       });
   };
+
+  startTwoFactorEnrollment = (userId: string): Observable<TwoFactorEnrollment> => {
+    return this.http
+      .post<TwoFactorEnrollment>('/backend/users/' + userId + '/twofactor', '', {
+        headers: defaultHeadersForJSON(),
+      })
+      .pipe(
+        catchError((err) => {
+          this.errorService.newError(Source.UserService, userId, err);
+          return throwError(() => err);
+        })
+      );
+  };
+
+  // returns the recovery codes - they are only shown once
+  verifyTwoFactorEnrollment = (userId: string, code: string): Observable<string[]> => {
+    return this.http
+      .post<string[]>(
+        '/backend/users/' + userId + '/twofactor/verify',
+        JSON.stringify({ code: code.trim() }),
+        {
+          headers: defaultHeadersForJSON(),
+        }
+      )
+      .pipe(
+        catchError((err) => {
+          this.errorService.newError(Source.UserService, userId, err);
+          return throwError(() => err);
+        })
+      );
+  };
+
+  // users need a current code to disable their own two-factor authentication
+  disableTwoFactor = (userId: string, code?: string) => {
+    let headers = defaultHeadersForJSON();
+    if (code) {
+      headers = headers.set('X-totp', code.trim());
+    }
+    const subscription = this.http
+      .delete('/backend/users/' + userId + '/twofactor', {
+        headers,
+      })
+      .subscribe({
+        error: (err) => {
+          this.errorService.newError(Source.UserService, userId, err);
+        },
+        complete: () => {
+          subscription.unsubscribe();
+        },
+      });
+  };
 }
@@ -0,0 +1,111 @@
+import { Component, OnDestroy, OnInit, inject } from '@angular/core';
+import { FormControl, FormGroup, Validators, FormsModule, ReactiveFormsModule } from '@angular/forms';
+import { ActivatedRoute, Router } from '@angular/router';
+import { take } from 'rxjs';
+import { AuthenticationService } from '../services/auth/authentication.service';
+import { SubscriptionHandler } from '../shared/subscriptionHandler';
+import { MatButtonModule } from '@angular/material/button';
+import { NgIf } from '@angular/common';
+import { MatInputModule } from '@angular/material/input';
+import { MatFormFieldModule } from '@angular/material/form-field';
+import { FlexModule } from '@angular/flex-layout/flex';
+
+// target of the links of the invitation and password reset mails
+@Component({
+    selector: 'app-setpassword',
+    templateUrl: './setpassword.component.html',
+    styleUrls: ['./setpassword.component.scss'],
+    standalone: true,
+    imports: [
+        FormsModule,
+        ReactiveFormsModule,
+        FlexModule,
+        MatFormFieldModule,
+        MatInputModule,
+        NgIf,
+        MatButtonModule,
+    ],
+})
+export class SetPasswordComponent implements OnInit, OnDestroy {
+  private authService = inject(AuthenticationService);
+  private route = inject(ActivatedRoute);
+  private router = inject(Router);
+
+  private subscriptionHandler = new SubscriptionHandler(this);
+
+  private token = '';
+
+  userId = '';
+
+  passwordLabel = 'New Password';
+  password: FormControl = new FormControl('', [
+    Validators.required,
+    Validators.minLength(4),
+  ]);
+
+  passwordRepeatLabel = 'Repeat Password';
+  passwordRepeat: FormControl = new FormControl('', [Validators.required]);
+
+  buttonText = 'Set Password';
+
+  form = new FormGroup({
+    password: this.password,
+    passwordRepeat: this.passwordRepeat,
+  });
+
+  ngOnInit(): void {
+    this.subscriptionHandler.subscription = this.route.queryParamMap.subscribe(
+      (params) => {
+        this.userId = params.get('user_id') ?? '';
+        this.token = params.get('token') ?? '';
+      }
+    );
+  }
+
+  ngOnDestroy(): void {
+    this.subscriptionHandler.onDestroy();
+  }
+
+  getErrorMessagPassword = (): string => {
+    if (this.password.hasError('required')) {
+      return 'You need to enter the password';
+    }
+    if (this.password.hasError('minlength')) {
+      return 'The password has to be at least 4 chars long';
+    }
+    return '';
+  };
+
+  getMessage = (): string => {
+    if (this.form.hasError('setPassword')) {
+      return this.form.getError('setPassword');
+    }
+    return '';
+  };
+
+  isValid = (): boolean => {
+    return (
+      this.password.valid &&
+      this.password.value === this.passwordRepeat.value &&
+      this.userId !== '' &&
+      this.token !== ''
+    );
+  };
+
+  onClickSetPassword = () => {
+    this.subscriptionHandler.subscription = this.authService
+      .setPassword(this.userId, this.token, this.password.value)
+      .pipe(take(1))
+      .subscribe({
+        next: () => {
+          this.router.navigate(['/login']);
+        },
+        error: () => {
+          this.form.setErrors({
+            setPassword:
+              'The link is invalid or has expired - please request a new one',
+          });
+        },
+      });
+  };
+}
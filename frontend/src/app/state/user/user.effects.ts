@@ -1,7 +1,7 @@
 // hydration.effects.ts
 import { Injectable, inject } from '@angular/core';
 import { Actions, createEffect, ofType } from '@ngrx/effects';
-import { of } from 'rxjs';
+import { of, throwError } from 'rxjs';
 import { map, switchMap, tap, catchError } from 'rxjs/operators';
 import {
   addMany,
@@ -21,7 +21,12 @@ export class UserEffects {
   loadAll$ = createEffect(() => {
     return this.action$.pipe(
       ofType(loadAll),
-      switchMap(() => this.userService.listUsers()),
+      switchMap(() =>
+        this.userService.listUsers().pipe(
+          // only admins can list the users
+          catchError((e) => (e.status === 403 ? of([]) : throwError(() => e)))
+        )
+      ),
       map((users) => loadAllSuccess({ users: users })),
       catchError((e) => of(loadAllFailure({ error: e })))
     );